@@ -0,0 +1,214 @@
+use glam::f32::Vec2;
+
+use crate::clip;
+use crate::geometry::{convex_hull, signed_area};
+use crate::perimeter::{offset_contour, offset_region};
+
+/// Generates the loops of a brim around the parts of the first layer.
+///
+/// # Remarks
+///
+/// - Only the **outer contours** of the parts are followed, the brim never enters their holes.
+/// - The k-th loop is the outline of the parts grown by `k * spacing`, so the first loop is one
+///   `spacing` away from the parts.
+/// - The grown outlines of neighbouring parts are **merged**, so parts closer than the brim width
+///   share a single loop instead of overlapping ones.
+///
+/// # Arguments
+///
+/// * `first_layer_contours` - The closed contours of the first layer, without repeating their first
+///   points, wound either way.
+/// * `loops` - The number of brim loops.
+/// * `spacing` - The distance between two neighbouring loops.
+///
+/// # Returns
+///
+/// The brim loops from the innermost to the outermost, counter-clockwise except for the gaps
+/// enclosed between merged parts, which are clockwise.
+pub fn generate_brim(
+    first_layer_contours: &[Vec<Vec2>],
+    loops: usize,
+    spacing: f32,
+) -> Vec<Vec<Vec2>> {
+    let outlines: Vec<Vec<Vec2>> = clip::union(first_layer_contours, &[])
+        .into_iter()
+        .filter(|contour| signed_area(contour) > 0.0)
+        .collect();
+
+    (1..=loops)
+        .flat_map(|k| offset_region(&outlines, -spacing * k as f32))
+        .collect()
+}
+
+/// Generates the loops of a skirt around all the parts of the first layer.
+///
+/// # Remarks
+///
+/// - Unlike the brim, the skirt follows the **convex hull** of every part together, so it is a single
+///   loop per ring however many parts the layer has.
+/// - The k-th loop is the hull grown by `k * offset`, so the first loop is `offset` away from the
+///   parts and the loops are `offset` apart.
+///
+/// # Arguments
+///
+/// * `all_first_layer_contours` - The contours of the first layer, without repeating their first
+///   points.
+/// * `offset` - The distance between the hull and the first loop, and between two loops.
+/// * `loops` - The number of skirt loops.
+///
+/// # Returns
+///
+/// The counter-clockwise skirt loops from the innermost to the outermost.
+pub fn generate_skirt(
+    all_first_layer_contours: &[Vec<Vec2>],
+    offset: f32,
+    loops: usize,
+) -> Vec<Vec<Vec2>> {
+    let points: Vec<Vec2> = all_first_layer_contours.concat();
+    let hull = convex_hull(&points);
+    if hull.len() < 3 {
+        return Vec::new();
+    }
+
+    (1..=loops)
+        .flat_map(|k| offset_contour(&hull, -offset * k as f32))
+        .collect()
+}
+
+/// Generates a draft shield, a thin wall around the whole print shielding it from drafts.
+///
+/// # Remarks
+///
+/// - The shield follows the **envelope** of the model, the union of the contours of every layer,
+///   grown by `distance`. A part overhanging higher up is thus enclosed from the first layer.
+/// - The shield is a single wall, the same loop on every layer so it stands straight. When the
+///   grown envelope is still made of several separate parts, the loop is their **convex hull**.
+///
+/// # Arguments
+///
+/// * `all_contours_by_layer` - The closed contours of each layer, from the first one, without
+///   repeating their first points.
+/// * `distance` - The distance between the envelope of the model and the shield.
+/// * `height_layers` - The number of layers the shield rises, from the first one.
+///
+/// # Returns
+///
+/// The counter-clockwise loop of the shield on each of its layers, from the first one, at most one
+/// per layer of the model. Empty if the model has no contours.
+pub fn generate_draft_shield(
+    all_contours_by_layer: &[Vec<Vec<Vec2>>],
+    distance: f32,
+    height_layers: usize,
+) -> Vec<Vec<Vec2>> {
+    let envelope: Vec<Vec<Vec2>> = clip::union(&all_contours_by_layer.concat(), &[])
+        .into_iter()
+        .filter(|contour| signed_area(contour) > 0.0)
+        .collect();
+    let mut outlines: Vec<Vec<Vec2>> = offset_region(&envelope, -distance)
+        .into_iter()
+        .filter(|contour| signed_area(contour) > 0.0)
+        .collect();
+    let outline = match outlines.len() {
+        0 => return Vec::new(),
+        1 => outlines.remove(0),
+        _ => convex_hull(&outlines.concat()),
+    };
+
+    vec![outline; height_layers.min(all_contours_by_layer.len())]
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::geometry::point_in_polygon;
+
+    /// Test for the `generate_brim` function around a square.
+    #[test]
+    fn test_generate_brim_square() {
+        let brim = generate_brim(&[crate::fixtures::square(Vec2::ZERO, 10.0)], 3, 0.5);
+        assert_eq!(brim.len(), 3);
+        for (k, ring) in brim.iter().enumerate() {
+            let offset = 0.5 * (k + 1) as f32;
+            assert_eq!(ring.len(), 4);
+            for corner in [
+                Vec2::ZERO,
+                Vec2::new(10.0, 0.0),
+                Vec2::splat(10.0),
+                Vec2::new(0.0, 10.0),
+            ] {
+                let expected = corner + (corner - Vec2::splat(5.0)).signum() * offset;
+                assert!(ring.iter().any(|p| p.abs_diff_eq(expected, 1e-4)));
+            }
+        }
+    }
+
+    /// Test for the `generate_brim` function merging the brims of two nearby parts.
+    #[test]
+    fn test_generate_brim_merged() {
+        let parts = [
+            crate::fixtures::square(Vec2::ZERO, 5.0),
+            crate::fixtures::square(Vec2::new(6.0, 0.0), 5.0),
+        ];
+        let brim = generate_brim(&parts, 2, 0.4);
+        // The first loops are 0.2mm apart, the second ones overlap and merge.
+        assert_eq!(brim.len(), 3);
+        assert!(brim[2]
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec2::new(11.8, -0.8), 1e-4)));
+        assert!(brim[2]
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec2::new(-0.8, -0.8), 1e-4)));
+    }
+
+    /// Test for the `generate_skirt` function enclosing two separate parts in single loops.
+    #[test]
+    fn test_generate_skirt_two_parts() {
+        let parts = [
+            crate::fixtures::square(Vec2::ZERO, 5.0),
+            crate::fixtures::square(Vec2::new(10.0, 3.0), 5.0),
+        ];
+        let skirt = generate_skirt(&parts, 2.0, 2);
+        assert_eq!(skirt.len(), 2);
+
+        for ring in &skirt {
+            assert!(signed_area(ring) > 0.0);
+            assert!(parts.iter().flatten().all(|&p| point_in_polygon(p, ring)));
+        }
+        // The gap between the parts is bridged by the hull instead of being followed.
+        assert!(point_in_polygon(Vec2::new(7.5, 4.0), &skirt[0]));
+        assert!(!point_in_polygon(Vec2::new(0.0, -2.5), &skirt[0]));
+        assert!(point_in_polygon(Vec2::new(0.0, -2.5), &skirt[1]));
+    }
+
+    /// Test for the `generate_draft_shield` function around a model wider at the top.
+    #[test]
+    fn test_generate_draft_shield_envelope() {
+        let layers = vec![
+            vec![crate::fixtures::square(Vec2::ZERO, 5.0)],
+            vec![crate::fixtures::square(Vec2::splat(-5.0), 15.0)],
+            vec![crate::fixtures::square(Vec2::new(20.0, 0.0), 2.0)],
+            vec![crate::fixtures::square(Vec2::ZERO, 5.0)],
+        ];
+        let shield = generate_draft_shield(&layers, 2.0, 3);
+        assert_eq!(shield.len(), 3);
+        assert!(shield.iter().all(|ring| *ring == shield[0]));
+
+        // The base already encloses the widest layer above it, and the separate part of the third
+        // layer through the hull.
+        let base = &shield[0];
+        assert!(signed_area(base) > 0.0);
+        assert!(layers
+            .iter()
+            .flatten()
+            .flatten()
+            .all(|&p| point_in_polygon(p, base)));
+        assert!(base.iter().any(|p| p.abs_diff_eq(Vec2::splat(-7.0), 1e-4)));
+        assert!(base
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec2::new(24.0, -2.0), 1e-4)));
+
+        assert_eq!(generate_draft_shield(&layers, 2.0, 10).len(), 4);
+        assert!(generate_draft_shield(&[], 2.0, 3).is_empty());
+    }
+}
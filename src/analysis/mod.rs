@@ -0,0 +1,123 @@
+use glam::f32::Vec2;
+
+use crate::geometry::signed_area;
+
+/// Matches the contours of a layer with those of the next layer, e.g. to follow each island of a
+/// model up the print.
+///
+/// # Remarks
+///
+/// - Two contours can only match when they are wound the same way, an outer contour with an outer
+///   contour and a hole with a hole, and their **centroids** are closer than the size of the larger
+///   one, the square root of its area.
+/// - Among the candidates, contours prefer the nearest centroid and the most **similar area**:
+///   the score is the distance between the centroids relative to the size of the larger contour,
+///   plus the log of the ratio of their areas.
+/// - A pair matches when either contour is the best candidate of the other. An island splitting in
+///   two thus maps to both halves, and two islands merging both map to the merged one.
+/// - A contour of `prev` with no candidate **dies** and is reported with `None`. A contour of `next`
+///   with no candidate is **born** and appears in no pair.
+///
+/// # Arguments
+///
+/// * `prev` - The contours of a layer, without repeating their first points.
+/// * `next` - The contours of the layer above, without repeating their first points.
+///
+/// # Returns
+///
+/// The matching pairs of an index into `prev` and an index into `next`, sorted by the index in
+/// `prev` and then in `next`, with the contours of `prev` that die paired with `None`.
+pub fn match_contours(prev: &[Vec<Vec2>], next: &[Vec<Vec2>]) -> Vec<(usize, Option<usize>)> {
+    let prev_shapes: Vec<(f32, Vec2)> = prev.iter().map(|c| area_and_centroid(c)).collect();
+    let next_shapes: Vec<(f32, Vec2)> = next.iter().map(|c| area_and_centroid(c)).collect();
+    let score = |i: usize, j: usize| {
+        let ((a, p), (b, q)) = (prev_shapes[i], next_shapes[j]);
+        let size = a.abs().max(b.abs()).sqrt();
+        let distance = p.distance(q);
+        if a * b <= 0.0 || distance > size {
+            return None;
+        }
+        Some(distance / size + (a / b).ln().abs())
+    };
+    let best_of = |candidates: &mut dyn Iterator<Item = (usize, f32)>| {
+        candidates
+            .min_by(|x, y| x.1.total_cmp(&y.1))
+            .map(|(k, _)| k)
+    };
+    let best_next: Vec<Option<usize>> = (0..prev.len())
+        .map(|i| best_of(&mut (0..next.len()).filter_map(|j| Some((j, score(i, j)?)))))
+        .collect();
+    let best_prev: Vec<Option<usize>> = (0..next.len())
+        .map(|j| best_of(&mut (0..prev.len()).filter_map(|i| Some((i, score(i, j)?)))))
+        .collect();
+
+    let mut pairs = Vec::new();
+    for (i, &best) in best_next.iter().enumerate() {
+        let matches: Vec<usize> = (0..next.len())
+            .filter(|&j| best == Some(j) || best_prev[j] == Some(i))
+            .collect();
+        if matches.is_empty() {
+            pairs.push((i, None));
+        }
+        pairs.extend(matches.into_iter().map(|j| (i, Some(j))));
+    }
+    pairs
+}
+
+/// Returns the signed area of a polygon and its centroid, or its first point if it has no area.
+fn area_and_centroid(polygon: &[Vec2]) -> (f32, Vec2) {
+    let area = signed_area(polygon);
+    let n = polygon.len();
+    if area == 0.0 {
+        return (area, polygon.first().copied().unwrap_or(Vec2::ZERO));
+    }
+    let weighted: Vec2 = (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            (a + b) * a.perp_dot(b)
+        })
+        .sum();
+    (area, weighted / (6.0 * area))
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use glam::f32::Vec3;
+
+    /// Test for the `match_contours` function on a block splitting into two columns.
+    #[test]
+    fn test_match_contours_split() {
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(10.0, 5.0, 10.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(0.0, 5.0, 0.0),
+            Vec3::new(3.0, 10.0, 3.0),
+        ));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(7.0, 5.0, 7.0),
+            Vec3::new(10.0, 10.0, 10.0),
+        ));
+        let mesh = crate::mesh::Mesh::from_triangles(&triangles);
+        let layers = crate::slicer::slice_mesh(&mesh, 0.2).unwrap();
+        let (block, columns) = (layers[24].regions(), layers[25].regions());
+        assert_eq!(block.len(), 1);
+        assert_eq!(columns.len(), 2);
+
+        assert_eq!(
+            match_contours(&block, &columns),
+            vec![(0, Some(0)), (0, Some(1))]
+        );
+        // Seen from the top, the columns merge into the block.
+        let merged = match_contours(&columns, &block);
+        assert_eq!(merged, vec![(0, Some(0)), (1, Some(0))]);
+        // Each column follows itself up, and the block dies or is born at the ends.
+        assert_eq!(
+            match_contours(&columns, &layers[30].regions()),
+            vec![(0, Some(0)), (1, Some(1))]
+        );
+        let far = vec![block[0].iter().map(|&p| p + Vec2::splat(50.0)).collect()];
+        assert_eq!(match_contours(&block, &far), vec![(0, None)]);
+        assert_eq!(match_contours(&[], &block), Vec::new());
+    }
+}
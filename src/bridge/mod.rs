@@ -0,0 +1,196 @@
+use glam::f32::{Vec2, Vec3};
+
+use crate::clip;
+use crate::geometry::{from_planar, point_in_polygon, signed_area, to_planar};
+use crate::infill::rectilinear;
+use crate::perimeter::offset_region;
+use crate::slicer::{Layer, EPSILON};
+
+/// How far beyond the edge of a bridge the layer below is searched for anchors, in millimeters.
+const ANCHOR_MARGIN: f32 = 0.5;
+
+/// The step between two candidate bridge directions, in degrees.
+const ANGLE_STEP: usize = 5;
+
+/// The number of probe lines across a bridge, used to score each candidate direction.
+const PROBE_LINES: f32 = 16.0;
+
+/// An area of a layer printed over nothing, held up only by the layer below around its edges.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BridgeRegion {
+    /// The unsupported area, an outer contour wound counter-clockwise followed by its clockwise holes.
+    pub area: Vec<Vec<Vec2>>,
+    /// The parts of the layer below around the area, where the bridge lines can rest.
+    pub anchors: Vec<Vec<Vec2>>,
+}
+
+/// Finds the areas of a layer that are not supported by the layer below.
+///
+/// # Remarks
+///
+/// - Each connected unsupported area is a separate `BridgeRegion`, with the holes it encloses.
+/// - The anchors of a bridge are the region of the layer below within `ANCHOR_MARGIN` of it. A
+///   bridge without anchors hangs in the air and rather needs support, see `support`.
+///
+/// # Arguments
+///
+/// * `layer` - The layer to inspect.
+/// * `below` - The layer printed just before it.
+///
+/// # Returns
+///
+/// The unsupported areas of `layer`, with their anchors.
+pub fn detect_bridges(layer: &Layer, below: &Layer) -> Vec<BridgeRegion> {
+    let support = below.regions();
+    let unsupported = clip::difference(&layer.regions(), &support);
+    let (outers, holes): (Vec<_>, Vec<_>) = unsupported
+        .into_iter()
+        .partition(|contour| signed_area(contour) > 0.0);
+
+    outers
+        .into_iter()
+        .map(|outer| {
+            let inner: Vec<Vec<Vec2>> = holes
+                .iter()
+                .filter(|hole| point_in_polygon(hole[0], &outer))
+                .cloned()
+                .collect();
+            let area: Vec<Vec<Vec2>> = std::iter::once(outer).chain(inner).collect();
+            let grown = offset_region(&area, -ANCHOR_MARGIN);
+            let anchors = clip::intersection(&grown, &support);
+            BridgeRegion { area, anchors }
+        })
+        .collect()
+}
+
+/// Returns whether a point of a layer rests on the layer below, e.g. to find the overhanging stretches
+/// of a wall.
+///
+/// # Remarks
+///
+/// - The point is supported when it lies inside the regions of the layer below by the **even-odd**
+///   rule, so holes are unsupported. Points on an outline count as inside it, see
+///   `point_in_polygon`.
+///
+/// # Arguments
+///
+/// * `point` - The point to test, in the plane of the layers.
+/// * `below` - The regions of the layer below, see `Layer::regions`.
+pub fn is_supported(point: Vec2, below: &[Vec<Vec2>]) -> bool {
+    below
+        .iter()
+        .filter(|contour| point_in_polygon(point, contour))
+        .count()
+        % 2
+        == 1
+}
+
+/// Chooses the direction of the lines printed over a bridge.
+///
+/// # Remarks
+///
+/// - Lines are tried every 5 degrees. The best direction has the **most lines landing on anchors at
+///   both ends**, e.g. crossing the gap between two pillars, and among those the **shortest** lines,
+///   i.e. the smallest unsupported span.
+/// - The angle follows the convention of `rectilinear`, counter-clockwise from the X axis, so the
+///   infill of the bridge can be generated with it directly.
+///
+/// # Arguments
+///
+/// * `region` - The bridge, see `detect_bridges`.
+///
+/// # Returns
+///
+/// The direction of the bridge lines in degrees, in `[0, 180)`.
+pub fn bridge_angle(region: &BridgeRegion) -> f32 {
+    let (min, max) = region
+        .area
+        .iter()
+        .flatten()
+        .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(min, max), &p| {
+            (min.min(p), max.max(p))
+        });
+    let spacing = (max - min).max_element() / PROBE_LINES;
+    if spacing <= EPSILON {
+        return 0.0;
+    }
+    let contours: Vec<Vec<Vec3>> = region
+        .area
+        .iter()
+        .map(|c| c.iter().map(|&p| from_planar(p, 0.0)).collect())
+        .collect();
+    let anchored = |p: Vec2| {
+        region
+            .anchors
+            .iter()
+            .filter(|anchor| point_in_polygon(p, anchor))
+            .count()
+            % 2
+            == 1
+    };
+
+    let mut best = (0.0, 0.0, f32::INFINITY);
+    for angle in (0..180).step_by(ANGLE_STEP).map(|a| a as f32) {
+        let lines = rectilinear(&contours, spacing, angle);
+        if lines.is_empty() {
+            continue;
+        }
+        let mut landed = 0;
+        let mut span = 0.0;
+        for [a, b] in &lines {
+            let (a, b) = (to_planar(*a), to_planar(*b));
+            let outwards = (b - a).normalize_or_zero() * ANCHOR_MARGIN / 2.0;
+            if anchored(a - outwards) && anchored(b + outwards) {
+                landed += 1;
+            }
+            span += a.distance(b);
+        }
+        let landed = landed as f32 / lines.len() as f32;
+        let span = span / lines.len() as f32;
+        if landed > best.1 + EPSILON || (landed >= best.1 - EPSILON && span < best.2) {
+            best = (angle, landed, span);
+        }
+    }
+
+    best.0
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `detect_bridges` and `bridge_angle` functions on a slab over two pillars.
+    #[test]
+    fn test_bridge_between_pillars() {
+        // Two 2x2x1 pillars 2mm apart, under a 6x1x1 slab.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(2.0, 2.0, 1.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(6.0, 2.0, 1.0),
+        ));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(6.0, 3.0, 1.0),
+        ));
+        let mesh = crate::mesh::Mesh::from_triangles(&triangles);
+        let layers = crate::slicer::slice_mesh(&mesh, 0.25).unwrap();
+        assert_eq!(layers.len(), 12);
+
+        // The first layer of the slab spans the gap between the pillars.
+        let bridges = detect_bridges(&layers[8], &layers[7]);
+        assert_eq!(bridges.len(), 1);
+        let bridge = &bridges[0];
+        assert_eq!(bridge.area.len(), 1);
+        assert!(bridge.area[0]
+            .iter()
+            .all(|p| (2.0 - 1e-4..=4.0 + 1e-4).contains(&p.x)));
+        assert_eq!(bridge.anchors.len(), 2);
+
+        // The gap is shorter along Z, but only lines along X rest on the pillars.
+        assert_eq!(bridge_angle(bridge), 0.0);
+
+        // The other layers are supported.
+        assert!(detect_bridges(&layers[9], &layers[8]).is_empty());
+        assert!(detect_bridges(&layers[5], &layers[4]).is_empty());
+    }
+}
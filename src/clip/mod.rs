@@ -0,0 +1,737 @@
+use std::collections::HashMap;
+
+use glam::f32::Vec2;
+use glam::f64::DVec2;
+
+use crate::slicer::EPSILON;
+
+/// Computes the union of two sets of polygons.
+///
+/// # Remarks
+///
+/// - Each set is read with the **non-zero** fill rule, so outer contours wound either way are
+///   supported, and holes wound against their outer contour are subtracted from it.
+/// - Output contours keep the region on their left: outer contours are counter-clockwise and holes
+///   are clockwise.
+///
+/// # Arguments
+///
+/// * `subject` - The first set of closed polygons, without repeating their first points.
+/// * `clip` - The second set of closed polygons, without repeating their first points.
+///
+/// # Returns
+///
+/// The contours of the area covered by either set.
+pub fn union(subject: &[Vec<Vec2>], clip: &[Vec<Vec2>]) -> Vec<Vec<Vec2>> {
+    combine(subject, clip, |s, c| s != 0 || c != 0)
+}
+
+/// Computes the intersection of two sets of polygons.
+///
+/// See `union` for how the polygons are read and wound.
+///
+/// # Returns
+///
+/// The contours of the area covered by both sets.
+pub fn intersection(subject: &[Vec<Vec2>], clip: &[Vec<Vec2>]) -> Vec<Vec<Vec2>> {
+    combine(subject, clip, |s, c| s != 0 && c != 0)
+}
+
+/// Computes the difference of two sets of polygons.
+///
+/// See `union` for how the polygons are read and wound.
+///
+/// # Returns
+///
+/// The contours of the area covered by `subject` but not by `clip`.
+pub fn difference(subject: &[Vec<Vec2>], clip: &[Vec<Vec2>]) -> Vec<Vec<Vec2>> {
+    combine(subject, clip, |s, c| s != 0 && c == 0)
+}
+
+/// The largest distance between a rounded join of `offset` and the true arc.
+const ARC_TOLERANCE: f32 = 0.01;
+
+/// How `offset` joins the moved edges around a corner they pull apart from.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum JoinType {
+    /// Extends the edges until they meet. Corners whose miter would be longer than the limit, in
+    /// multiples of the offset distance, are squared off instead.
+    Miter(f32),
+    /// Joins the edges with an arc centered on the corner.
+    Round,
+    /// Cuts the corner with a line at the offset distance from it.
+    #[default]
+    Square,
+}
+
+/// Offsets a set of polygons by a signed distance, like clipper's `ClipperOffset`.
+///
+/// # Remarks
+///
+/// - Outer contours must be counter-clockwise and holes clockwise, as produced by this module. A
+///   **positive** `delta` grows the region, moving outer contours out and holes in, and a negative
+///   one shrinks it.
+/// - Each edge is moved along its normal. Where the moved edges pull apart, around convex corners
+///   when growing and concave ones when shrinking, they are joined according to `join`: a rounded
+///   join is tessellated so it strays at most `ARC_TOLERANCE` from the true arc.
+/// - Where the moved edges cross, the raw offset loops over itself. As in clipper, the result keeps
+///   the areas the raw loops wind around **positively**, so loops may merge, split or vanish.
+///
+/// # Arguments
+///
+/// * `polygons` - The loops of the region, without repeating their first points.
+/// * `delta` - The signed distance to move the boundary outwards.
+/// * `join` - How corners are joined.
+///
+/// # Returns
+///
+/// The loops of the offset region, outer contours counter-clockwise and holes clockwise.
+pub fn offset(polygons: &[Vec<Vec2>], delta: f32, join: JoinType) -> Vec<Vec<Vec2>> {
+    if delta == 0.0 {
+        return combine(polygons, &[], |w, _| w > 0);
+    }
+
+    let raw: Vec<Vec<Vec2>> = polygons
+        .iter()
+        .map(|polygon| {
+            let mut points: Vec<Vec2> = polygon.clone();
+            points.dedup_by(|a, b| a.abs_diff_eq(*b, EPSILON));
+            while points.len() > 1 && points[0].abs_diff_eq(points[points.len() - 1], EPSILON) {
+                points.pop();
+            }
+            points
+        })
+        .filter(|points| points.len() >= 3)
+        .map(|points| raw_offset(&points, delta, join))
+        .collect();
+    combine(&raw, &[], |w, _| w > 0)
+}
+
+/// Moves every edge of a loop by `delta` to its right, joining the moved edges at each vertex, see
+/// `offset`.
+///
+/// Where the moved edges cross, the vertex itself is inserted between them. The small loop this
+/// creates winds negatively and disappears when the result is resolved.
+fn raw_offset(polygon: &[Vec2], delta: f32, join: JoinType) -> Vec<Vec2> {
+    let n = polygon.len();
+    let mut offset = Vec::with_capacity(n * 2);
+    // Outward normals of a counter-clockwise loop, the edges turning left around convex corners.
+    let normal = |a: Vec2, b: Vec2| -(b - a).normalize().perp();
+
+    for i in 0..n {
+        let current = polygon[i];
+        let normal_in = normal(polygon[(i + n - 1) % n], current);
+        let normal_out = normal(current, polygon[(i + 1) % n]);
+        let sin = normal_in.perp_dot(normal_out);
+        let cos = normal_in.dot(normal_out);
+
+        if sin.abs() <= EPSILON && cos > 0.0 {
+            // The edges are collinear.
+            offset.push(current + normal_in * delta);
+            continue;
+        }
+        if sin * delta < 0.0 {
+            offset.push(current + normal_in * delta);
+            offset.push(current);
+            offset.push(current + normal_out * delta);
+            continue;
+        }
+
+        let angle = sin.atan2(cos);
+        match join {
+            // The miter point sits on the bisector, at `delta / cos(half angle)` from the vertex.
+            JoinType::Miter(limit) if 1.0 + cos >= 2.0 / (limit * limit) => {
+                offset.push(current + (normal_in + normal_out) * (delta / (1.0 + cos)));
+            }
+            JoinType::Miter(_) | JoinType::Square => {
+                // Each moved edge is extended until it is `delta` away from the vertex along the
+                // bisector, where the cut starts.
+                let extension = delta.abs() * (angle.abs() / 4.0).tan();
+                offset.push(current + normal_in * delta + normal_in.perp() * extension);
+                offset.push(current + normal_out * delta - normal_out.perp() * extension);
+            }
+            JoinType::Round => {
+                let step = 2.0 * (1.0 - ARC_TOLERANCE / delta.abs()).max(-1.0).acos();
+                let steps = (angle.abs() / step).ceil().max(1.0) as usize;
+                let rotation = Vec2::from_angle(angle / steps as f32);
+                let mut radius = normal_in * delta;
+                for _ in 0..=steps {
+                    offset.push(current + radius);
+                    radius = rotation.rotate(radius);
+                }
+            }
+        }
+    }
+
+    offset
+}
+
+/// Combines two sets of polygons, keeping the points for which `inside` holds.
+///
+/// # Remarks
+///
+/// - The plane is cut into horizontal **slabs** at every vertex and every edge crossing, so inside a
+///   slab no two edges cross and the polygons reduce to a list of trapezoids. The trapezoids whose
+///   winding numbers satisfy `inside` are then stitched back into contours.
+/// - `inside` receives the **winding numbers** of a point with respect to `subject` and `clip`,
+///   counter-clockwise loops counting positively. This makes it possible to apply any fill rule and
+///   any boolean operation, and to resolve self-intersecting input.
+/// - The computation runs in double precision and points closer than a tolerance relative to the
+///   size of the input are merged, so shared edges and touching vertices are handled.
+/// - Output contours keep the region on their left: outer contours are counter-clockwise and holes
+///   are clockwise.
+///
+/// # Arguments
+///
+/// * `subject` - The first set of closed polygons, without repeating their first points.
+/// * `clip` - The second set of closed polygons, without repeating their first points.
+/// * `inside` - Decides from the winding numbers of a point whether it belongs to the result.
+///
+/// # Returns
+///
+/// The contours of the resulting region.
+pub(crate) fn combine(
+    subject: &[Vec<Vec2>],
+    clip: &[Vec<Vec2>],
+    inside: impl Fn(i32, i32) -> bool,
+) -> Vec<Vec<Vec2>> {
+    let mut edges = Vec::new();
+    for (set, polygons) in [subject, clip].into_iter().enumerate() {
+        for polygon in polygons {
+            for (i, &a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+                if let Some(edge) = Edge::new(a.as_dvec2(), b.as_dvec2(), set) {
+                    edges.push(edge);
+                }
+            }
+        }
+    }
+    if edges.is_empty() {
+        return Vec::new();
+    }
+
+    let magnitude = edges
+        .iter()
+        .flat_map(|e| [e.low.abs().max_element(), e.high.abs().max_element()])
+        .fold(1.0, f64::max);
+    let tolerance = magnitude * 1e-9;
+
+    edges.sort_by(|a, b| a.low.y.total_cmp(&b.low.y));
+    let levels = slab_levels(&edges, tolerance);
+    let mut slabs = decompose(&edges, &levels, tolerance, &inside);
+    snap_levels(&mut slabs, levels.len(), tolerance);
+
+    let boundary = boundary_edges(&slabs, &levels);
+    link_loops(&boundary, tolerance)
+        .into_iter()
+        .map(|contour| contour.into_iter().map(|p| p.as_vec2()).collect())
+        .collect()
+}
+
+/// A non-horizontal edge of the input, stored bottom to top.
+struct Edge {
+    low: DVec2,
+    high: DVec2,
+    /// Which input the edge comes from, 0 for the subject and 1 for the clip polygons.
+    set: usize,
+    /// The change of winding number when crossing the edge from left to right.
+    winding: i32,
+}
+
+impl Edge {
+    fn new(a: DVec2, b: DVec2, set: usize) -> Option<Self> {
+        if a.y == b.y {
+            // Horizontal edges never separate two points of a slab.
+            return None;
+        }
+        let (low, high, winding) = if a.y < b.y { (a, b, -1) } else { (b, a, 1) };
+        Some(Edge {
+            low,
+            high,
+            set,
+            winding,
+        })
+    }
+
+    fn x_at(&self, y: f64) -> f64 {
+        if y <= self.low.y {
+            self.low.x
+        } else if y >= self.high.y {
+            self.high.x
+        } else {
+            self.low.x + (y - self.low.y) * (self.high.x - self.low.x) / (self.high.y - self.low.y)
+        }
+    }
+}
+
+/// A piece of the result between two consecutive levels, bounded on the left and right by edges.
+#[derive(Clone, Copy)]
+struct Trapezoid {
+    bottom_left: f64,
+    bottom_right: f64,
+    top_left: f64,
+    top_right: f64,
+}
+
+/// Collects the heights of every vertex and every crossing between two edges, sorted and merged.
+fn slab_levels(edges: &[Edge], tolerance: f64) -> Vec<f64> {
+    let mut levels: Vec<f64> = edges.iter().flat_map(|e| [e.low.y, e.high.y]).collect();
+
+    // The edges are sorted by their lowest point, so only the following edges starting below the
+    // top of an edge can cross it.
+    for (i, a) in edges.iter().enumerate() {
+        for b in edges[i + 1..].iter().take_while(|b| b.low.y < a.high.y) {
+            let r = a.high - a.low;
+            let s = b.high - b.low;
+            let denominator = r.perp_dot(s);
+            if denominator.abs() <= f64::EPSILON * r.length() * s.length() {
+                continue;
+            }
+            let t = (b.low - a.low).perp_dot(s) / denominator;
+            let u = (b.low - a.low).perp_dot(r) / denominator;
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                levels.push(a.low.y + r.y * t);
+            }
+        }
+    }
+
+    levels.sort_by(f64::total_cmp);
+    levels.dedup_by(|a, b| (*a - *b).abs() <= tolerance);
+    levels
+}
+
+/// Computes the trapezoids of the result in every slab between two consecutive levels.
+fn decompose(
+    edges: &[Edge],
+    levels: &[f64],
+    tolerance: f64,
+    inside: &impl Fn(i32, i32) -> bool,
+) -> Vec<Vec<Trapezoid>> {
+    let mut slabs = Vec::with_capacity(levels.len().saturating_sub(1));
+    let mut active: Vec<&Edge> = Vec::new();
+    let mut next = 0;
+
+    for pair in levels.windows(2) {
+        let (bottom, top) = (pair[0], pair[1]);
+        while next < edges.len() && edges[next].low.y <= bottom + tolerance {
+            active.push(&edges[next]);
+            next += 1;
+        }
+        active.retain(|e| e.high.y > bottom + tolerance);
+
+        let middle = (bottom + top) / 2.0;
+        let mut crossing: Vec<(f64, &Edge)> = active
+            .iter()
+            .filter(|e| e.high.y >= top - tolerance)
+            .map(|&e| (e.x_at(middle), e))
+            .collect();
+        crossing.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut trapezoids: Vec<Trapezoid> = Vec::new();
+        let mut windings = [0, 0];
+        let mut left: Option<&Edge> = None;
+        for (_, edge) in crossing {
+            let was_inside = inside(windings[0], windings[1]);
+            windings[edge.set] += edge.winding;
+            let is_inside = inside(windings[0], windings[1]);
+
+            if !was_inside && is_inside {
+                left = Some(edge);
+            } else if was_inside && !is_inside {
+                let left = left
+                    .take()
+                    .expect("an interval always opens before closing");
+                let trapezoid = Trapezoid {
+                    bottom_left: left.x_at(bottom),
+                    bottom_right: edge.x_at(bottom),
+                    top_left: left.x_at(top),
+                    top_right: edge.x_at(top),
+                };
+
+                // Merge with the previous trapezoid when they only touch along an edge.
+                match trapezoids.last_mut() {
+                    Some(previous)
+                        if (previous.bottom_right - trapezoid.bottom_left).abs() <= tolerance
+                            && (previous.top_right - trapezoid.top_left).abs() <= tolerance =>
+                    {
+                        previous.bottom_right = trapezoid.bottom_right;
+                        previous.top_right = trapezoid.top_right;
+                    }
+                    _ => trapezoids.push(trapezoid),
+                }
+            }
+        }
+
+        trapezoids.retain(|t| {
+            t.bottom_right - t.bottom_left > tolerance || t.top_right - t.top_left > tolerance
+        });
+        slabs.push(trapezoids);
+    }
+
+    slabs
+}
+
+/// Merges the abscissas closer than `tolerance` on each level, so the corners shared by the
+/// trapezoids above and below a level are bitwise identical.
+fn snap_levels(slabs: &mut [Vec<Trapezoid>], level_count: usize, tolerance: f64) {
+    for level in 0..level_count {
+        let mut xs: Vec<f64> = Vec::new();
+        if level > 0 {
+            xs.extend(
+                slabs[level - 1]
+                    .iter()
+                    .flat_map(|t| [t.top_left, t.top_right]),
+            );
+        }
+        if level < slabs.len() {
+            xs.extend(
+                slabs[level]
+                    .iter()
+                    .flat_map(|t| [t.bottom_left, t.bottom_right]),
+            );
+        }
+        xs.sort_by(f64::total_cmp);
+        xs.dedup_by(|a, b| (*a - *b).abs() <= tolerance);
+
+        let snap = |x: &mut f64| {
+            let i = xs.partition_point(|&v| v < *x - tolerance);
+            if i < xs.len() && (xs[i] - *x).abs() <= tolerance {
+                *x = xs[i];
+            }
+        };
+        if level > 0 {
+            for t in &mut slabs[level - 1] {
+                snap(&mut t.top_left);
+                snap(&mut t.top_right);
+            }
+        }
+        if level < slabs.len() {
+            for t in &mut slabs[level] {
+                snap(&mut t.bottom_left);
+                snap(&mut t.bottom_right);
+            }
+        }
+    }
+}
+
+/// Collects the directed edges bounding the union of all trapezoids, with the region on their left.
+fn boundary_edges(slabs: &[Vec<Trapezoid>], levels: &[f64]) -> Vec<[DVec2; 2]> {
+    let mut boundary = Vec::new();
+
+    for (k, slab) in slabs.iter().enumerate() {
+        let (bottom, top) = (levels[k], levels[k + 1]);
+        for t in slab {
+            boundary.push([
+                DVec2::new(t.bottom_right, bottom),
+                DVec2::new(t.top_right, top),
+            ]);
+            boundary.push([
+                DVec2::new(t.top_left, top),
+                DVec2::new(t.bottom_left, bottom),
+            ]);
+        }
+    }
+
+    // Along each level, the tops of the trapezoids below and the bottoms of those above cancel out
+    // where they overlap, the rest is boundary.
+    for (k, &y) in levels.iter().enumerate() {
+        let below: Vec<(f64, f64)> = match k.checked_sub(1) {
+            Some(slab) => slabs[slab]
+                .iter()
+                .map(|t| (t.top_left, t.top_right))
+                .collect(),
+            None => Vec::new(),
+        };
+        let above: Vec<(f64, f64)> = slabs
+            .get(k)
+            .map(|slab| {
+                slab.iter()
+                    .map(|t| (t.bottom_left, t.bottom_right))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (left, right) in subtract_intervals(&below, &above) {
+            boundary.push([DVec2::new(right, y), DVec2::new(left, y)]);
+        }
+        for (left, right) in subtract_intervals(&above, &below) {
+            boundary.push([DVec2::new(left, y), DVec2::new(right, y)]);
+        }
+    }
+
+    boundary.retain(|edge| edge[0] != edge[1]);
+    boundary
+}
+
+/// Removes the sorted, disjoint intervals `b` from the sorted, disjoint intervals `a`.
+fn subtract_intervals(a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut result = Vec::new();
+    for &(start, end) in a {
+        let mut cursor = start;
+        for &(cut_start, cut_end) in b {
+            if cut_end <= cursor || cut_start >= end {
+                continue;
+            }
+            if cut_start > cursor {
+                result.push((cursor, cut_start));
+            }
+            cursor = cursor.max(cut_end);
+        }
+        if cursor < end {
+            result.push((cursor, end));
+        }
+    }
+    result
+}
+
+/// Links directed boundary edges head to tail into closed loops, dropping redundant vertices.
+///
+/// Where several loops touch at a vertex, the walk takes the leftmost turn so touching regions
+/// come out as separate loops.
+fn link_loops(boundary: &[[DVec2; 2]], tolerance: f64) -> Vec<Vec<DVec2>> {
+    let key = |p: DVec2| (p.x.to_bits(), p.y.to_bits());
+    let mut outgoing: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, edge) in boundary.iter().enumerate() {
+        outgoing.entry(key(edge[0])).or_default().push(i);
+    }
+
+    let mut used = vec![false; boundary.len()];
+    let mut loops = Vec::new();
+    for first in 0..boundary.len() {
+        if used[first] {
+            continue;
+        }
+
+        let mut contour = Vec::new();
+        let mut current = first;
+        let closed = loop {
+            used[current] = true;
+            let [start, end] = boundary[current];
+            contour.push(start);
+            if end == boundary[first][0] {
+                break true;
+            }
+
+            let direction = end - start;
+            let candidates = outgoing.get(&key(end)).into_iter().flatten();
+            let next = candidates.filter(|&&i| !used[i]).max_by(|&&a, &&b| {
+                let turn = |i: usize| {
+                    let out = boundary[i][1] - boundary[i][0];
+                    direction.perp_dot(out).atan2(direction.dot(out))
+                };
+                turn(a).total_cmp(&turn(b))
+            });
+            match next {
+                Some(&next) => current = next,
+                None => break false,
+            }
+        };
+
+        if closed {
+            let contour = simplify(contour, tolerance);
+            if contour.len() >= 3 {
+                loops.push(contour);
+            }
+        }
+    }
+
+    loops
+}
+
+/// Removes the vertices lying on the straight line between their neighbours.
+fn simplify(mut contour: Vec<DVec2>, tolerance: f64) -> Vec<DVec2> {
+    loop {
+        let n = contour.len();
+        if n < 3 {
+            return contour;
+        }
+        let redundant = (0..n).find(|&i| {
+            let previous = contour[(i + n - 1) % n];
+            let next = contour[(i + 1) % n];
+            let along = next - previous;
+            let offset = contour[i] - previous;
+            along.perp_dot(offset).abs() <= tolerance * along.length().max(tolerance)
+                && offset.dot(along) >= 0.0
+                && offset.length_squared() <= along.length_squared()
+        });
+        match redundant {
+            Some(i) => {
+                contour.remove(i);
+            }
+            None => return contour,
+        }
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Computes the signed area of a polygon, positive when counter-clockwise.
+    #[allow(dead_code)]
+    fn area(polygon: &[Vec2]) -> f32 {
+        let n = polygon.len();
+        (0..n)
+            .map(|i| polygon[i].perp_dot(polygon[(i + 1) % n]))
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Test for the `combine` function resolving a self-intersecting bow tie.
+    #[test]
+    fn test_combine_bow_tie() {
+        let bow_tie = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        // The two lobes are wound in opposite directions.
+        let non_zero = combine(std::slice::from_ref(&bow_tie), &[], |w, _| w != 0);
+        assert_eq!(non_zero.len(), 2);
+        for lobe in &non_zero {
+            assert_eq!(lobe.len(), 3);
+            assert!((area(lobe) - 1.0).abs() < 1e-5);
+        }
+
+        let positive = combine(&[bow_tie], &[], |w, _| w > 0);
+        assert_eq!(positive.len(), 1);
+        assert!(positive[0].contains(&Vec2::new(1.0, 1.0)));
+    }
+
+    /// Test for the `combine` function producing a counter-clockwise outer loop and a clockwise hole.
+    #[test]
+    fn test_combine_hole() {
+        let outer = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(4.0, 4.0),
+            Vec2::new(0.0, 4.0),
+        ];
+        let inner = vec![
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(1.0, 3.0),
+        ];
+        let mut loops = combine(&[outer, inner], &[], |w, _| w % 2 != 0);
+        loops.sort_by(|a, b| area(b).total_cmp(&area(a)));
+        assert_eq!(loops.len(), 2);
+        assert!((area(&loops[0]) - 16.0).abs() < 1e-5);
+        assert!((area(&loops[1]) + 4.0).abs() < 1e-5);
+        assert_eq!(loops[0].len(), 4);
+        assert_eq!(loops[1].len(), 4);
+    }
+
+    /// Test for the `union` function on two overlapping squares.
+    #[test]
+    fn test_union() {
+        let a = crate::fixtures::square(Vec2::ZERO, 2.0);
+        let b = crate::fixtures::square(Vec2::ONE, 2.0);
+        let loops = union(&[a], &[b]);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 8);
+        assert!((area(&loops[0]) - 7.0).abs() < 1e-5);
+    }
+
+    /// Test for the `intersection` function on two overlapping squares.
+    #[test]
+    fn test_intersection() {
+        let a = crate::fixtures::square(Vec2::ZERO, 2.0);
+        let b = crate::fixtures::square(Vec2::ONE, 2.0);
+        let loops = intersection(std::slice::from_ref(&a), &[b]);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 4);
+        assert!((area(&loops[0]) - 1.0).abs() < 1e-5);
+        for corner in [
+            Vec2::ONE,
+            Vec2::new(2.0, 1.0),
+            Vec2::splat(2.0),
+            Vec2::new(1.0, 2.0),
+        ] {
+            assert!(loops[0].contains(&corner));
+        }
+
+        // Clockwise input is read the same way.
+        let mut clockwise = crate::fixtures::square(Vec2::ONE, 2.0);
+        clockwise.reverse();
+        let loops = intersection(&[a], &[clockwise]);
+        assert!((area(&loops[0]) - 1.0).abs() < 1e-5);
+    }
+
+    /// Test for the `difference` function on two overlapping squares and on a square with a hole.
+    #[test]
+    fn test_difference() {
+        let a = crate::fixtures::square(Vec2::ZERO, 2.0);
+        let b = crate::fixtures::square(Vec2::ONE, 2.0);
+        let loops = difference(&[a], std::slice::from_ref(&b));
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 6);
+        assert!((area(&loops[0]) - 3.0).abs() < 1e-5);
+
+        // Cutting a square out of the middle of a bigger one leaves a clockwise hole.
+        let mut loops = difference(&[crate::fixtures::square(Vec2::ZERO, 4.0)], &[b]);
+        loops.sort_by(|a, b| area(b).total_cmp(&area(a)));
+        assert_eq!(loops.len(), 2);
+        assert!((area(&loops[0]) - 16.0).abs() < 1e-5);
+        assert!((area(&loops[1]) + 4.0).abs() < 1e-5);
+    }
+
+    /// Builds a counter-clockwise L shape, two units wide and three tall.
+    #[allow(dead_code)]
+    fn l_shape() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ]
+    }
+
+    /// Test for the `offset` function growing an L shape with mitered and rounded joins.
+    #[test]
+    fn test_offset_miter_round() {
+        let l = [l_shape()];
+        let mitered = offset(&l, 0.5, JoinType::Miter(2.0));
+        let rounded = offset(&l, 0.5, JoinType::Round);
+        let squared = offset(&l, 0.5, JoinType::Square);
+        assert_eq!(mitered.len(), 1);
+        assert_eq!(rounded.len(), 1);
+        assert_eq!(squared.len(), 1);
+
+        // The miters extend the sharp corners: the L grows by half a unit on every side.
+        assert_eq!(mitered[0].len(), 6);
+        assert!(mitered[0]
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec2::new(-0.5, -0.5), 1e-5)));
+        assert!((area(&mitered[0]) - 10.0).abs() < 1e-4);
+
+        // The five convex corners are rounded by quarter circles, slightly cut by the tessellation,
+        // while the moved edges overlap by a square at the concave one. Every point stays half a
+        // unit away from the L.
+        let exact = 4.0 + 10.0 * 0.5 + 5.0 * std::f32::consts::PI / 16.0 - 0.25;
+        assert!(area(&rounded[0]) < exact);
+        assert!(area(&rounded[0]) > exact - 0.05);
+        assert!(rounded[0].len() > 20);
+        for &p in &rounded[0] {
+            let distance = (0..6)
+                .map(|i| crate::geometry::distance_to_segment(p, l[0][i], l[0][(i + 1) % 6]))
+                .fold(f32::INFINITY, f32::min);
+            assert!((distance - 0.5).abs() < 1e-4);
+        }
+
+        // Squared corners lie in between, and a miter over the limit is squared off.
+        assert!(area(&squared[0]) > area(&rounded[0]));
+        assert!(area(&squared[0]) < area(&mitered[0]));
+        let limited = offset(&l, 0.5, JoinType::Miter(1.2));
+        assert!((area(&limited[0]) - area(&squared[0])).abs() < 1e-4);
+
+        // Shrinking rounds the concave corner instead, leaving two arms half a unit wide.
+        let shrunk = offset(&l, -0.25, JoinType::Round);
+        assert_eq!(shrunk.len(), 1);
+        let exact = 1.75 + 0.0625 - std::f32::consts::PI / 64.0;
+        assert!((area(&shrunk[0]) - exact).abs() < 0.01);
+    }
+}
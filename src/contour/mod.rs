@@ -0,0 +1,688 @@
+use glam::f32::{Vec2, Vec3};
+
+use crate::geometry::{
+    distance_to_segment, point_in_polygon, segment_intersection, signed_area, to_planar,
+};
+use crate::slicer::EPSILON;
+
+/// The polylines assembled from the segments of a layer, sorted by how they were closed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildReport {
+    /// Loops whose segments all join within `epsilon`.
+    pub closed: Vec<Vec<Vec3>>,
+    /// Loops that could only be closed by bridging one or more gaps up to `gap_tolerance` wide.
+    pub bridged: Vec<Vec<Vec3>>,
+    /// Chains that could not be closed, even by bridging.
+    pub dangling: Vec<Vec<Vec3>>,
+}
+
+impl BuildReport {
+    /// Returns an iterator over every closed loop, the `closed` ones first and then the `bridged` ones.
+    pub fn loops(&self) -> impl Iterator<Item = &Vec<Vec3>> + '_ {
+        self.closed.iter().chain(&self.bridged)
+    }
+}
+
+/// Stitches the intersection segments of a layer into polylines by matching their endpoints.
+///
+/// # Remarks
+///
+/// - Segments are **unoriented**, either endpoint may connect to either endpoint of another segment.
+/// - A **closed loop** is returned with its first point repeated at the end, see `is_closed`.
+/// - Chains that cannot be closed, e.g. because the mesh has missing triangles, are then **bridged**:
+///   the end of a chain is joined to the nearest chain end, including its own start, that is at
+///   most `gap_tolerance` away. Chains left open are reported as **dangling** polylines.
+/// - The output is **deterministic**: loops are ordered by their lowest segment index, and ties between
+///   candidate continuations are broken in favour of the lowest segment index.
+/// - Loops are **rewound** in the (x, z) plane: outer contours counter-clockwise and holes, the loops
+///   nested inside an odd number of others, clockwise. Chains left dangling keep their direction.
+///
+/// # Arguments
+///
+/// * `segments` - The intersection segments of a single layer.
+/// * `epsilon` - The maximum absolute difference per coordinate for two endpoints to be joined.
+/// * `gap_tolerance` - The maximum distance between two chain ends for the gap to be bridged.
+///
+/// # Returns
+///
+/// A `BuildReport` listing the closed loops, bridged loops and dangling chains formed by the segments.
+pub fn build_contours(segments: &[[Vec3; 2]], epsilon: f32, gap_tolerance: f32) -> BuildReport {
+    let index = EndpointIndex::new(segments);
+    let mut used: Vec<bool> = segments
+        .iter()
+        .map(|s| s[0].abs_diff_eq(s[1], epsilon))
+        .collect();
+    let mut report = BuildReport::default();
+    let mut chains = Vec::new();
+
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+
+        let start = segments[first][0];
+        let mut contour = vec![start, segments[first][1]];
+        let mut closed = false;
+
+        while let Some(next) = index.take_next(*contour.last().unwrap(), epsilon, &mut used) {
+            contour.push(next);
+            if next.abs_diff_eq(start, epsilon) {
+                *contour.last_mut().unwrap() = start;
+                closed = true;
+                break;
+            }
+        }
+
+        if closed {
+            report.closed.push(contour);
+        } else {
+            // The chain may have started in the middle, so grow it backwards as well.
+            let mut head = Vec::new();
+            let mut tail = start;
+            while let Some(previous) = index.take_next(tail, epsilon, &mut used) {
+                head.push(previous);
+                tail = previous;
+            }
+            head.reverse();
+            head.extend(contour);
+            chains.push(Some(head));
+        }
+    }
+
+    bridge_chains(&mut chains, gap_tolerance, &mut report);
+    normalize_winding(&mut report);
+    report
+}
+
+/// Winds the outer loops of a report counter-clockwise and its holes clockwise, see `build_contours`.
+fn normalize_winding(report: &mut BuildReport) {
+    let planar: Vec<Vec<Vec2>> = report
+        .loops()
+        .map(|contour| {
+            contour[..contour.len() - 1]
+                .iter()
+                .map(|&p| to_planar(p))
+                .collect()
+        })
+        .collect();
+    let is_hole: Vec<bool> = (0..planar.len())
+        .map(|i| {
+            let depth = (0..planar.len())
+                .filter(|&j| j != i && point_in_polygon(planar[i][0], &planar[j]))
+                .count();
+            depth % 2 == 1
+        })
+        .collect();
+
+    for ((contour, polygon), hole) in report
+        .closed
+        .iter_mut()
+        .chain(&mut report.bridged)
+        .zip(&planar)
+        .zip(is_hole)
+    {
+        let area = signed_area(polygon);
+        if area != 0.0 && (area > 0.0) == hole {
+            contour.reverse();
+        }
+    }
+}
+
+/// Joins open chains whose ends are at most `gap_tolerance` apart, moving the loops this closes to
+/// `report.bridged` and the chains left open to `report.dangling`.
+fn bridge_chains(chains: &mut [Option<Vec<Vec3>>], gap_tolerance: f32, report: &mut BuildReport) {
+    for i in 0..chains.len() {
+        let Some(mut chain) = chains[i].take() else {
+            continue;
+        };
+        let mut flipped = false;
+
+        loop {
+            let tail = *chain.last().unwrap();
+            // The best candidate so far, `None` standing for the start of the chain itself.
+            let mut best: Option<(f32, Option<(usize, bool)>)> = None;
+            if chain.len() > 2 {
+                let gap = tail.distance(chain[0]);
+                if gap <= gap_tolerance {
+                    best = Some((gap, None));
+                }
+            }
+            for (j, other) in chains.iter().enumerate().skip(i + 1) {
+                let Some(other) = other else {
+                    continue;
+                };
+                for (reversed, end) in [(false, other[0]), (true, other[other.len() - 1])] {
+                    let gap = tail.distance(end);
+                    if gap <= gap_tolerance && best.is_none_or(|(nearest, _)| gap < nearest) {
+                        best = Some((gap, Some((j, reversed))));
+                    }
+                }
+            }
+
+            match best {
+                Some((_, None)) => {
+                    chain.push(chain[0]);
+                    report.bridged.push(chain);
+                    break;
+                }
+                Some((_, Some((j, reversed)))) => {
+                    let mut other = chains[j].take().unwrap();
+                    if reversed {
+                        other.reverse();
+                    }
+                    chain.extend(other);
+                }
+                None if !flipped => {
+                    // Nothing continues the end of the chain, try from its start instead.
+                    chain.reverse();
+                    flipped = true;
+                }
+                None => {
+                    chain.reverse();
+                    report.dangling.push(chain);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Removes the loops too small to be printed, such as the slivers left by tiny triangles.
+///
+/// # Remarks
+///
+/// - A loop is removed when its enclosed area, ignoring its winding, is below `min_area`, **or** its
+///   length, closing edge included, is below `min_perimeter`. Holes are pruned the same way.
+/// - The remaining loops keep their order.
+///
+/// # Arguments
+///
+/// * `contours` - The loops to prune, without repeating their first points.
+/// * `min_area` - The smallest area of a loop to keep, in square millimeters.
+/// * `min_perimeter` - The smallest length of a loop to keep, in millimeters.
+pub fn prune_small(contours: &mut Vec<Vec<Vec2>>, min_area: f32, min_perimeter: f32) {
+    contours.retain(|contour| {
+        let n = contour.len();
+        let perimeter: f32 = (0..n)
+            .map(|i| contour[i].distance(contour[(i + 1) % n]))
+            .sum();
+        signed_area(contour).abs() >= min_area && perimeter >= min_perimeter
+    });
+}
+
+/// Replaces the points of a closed contour with points evenly spaced along its length.
+///
+/// # Remarks
+///
+/// - The spacing is adjusted so the points also divide the **closing** edge evenly: it is the length
+///   of the loop divided by the number of points nearest to `spacing` apart, **at least 3**.
+/// - The points are measured along the loop, so an edge shorter than the spacing may get no point
+///   and a longer one several. Corners falling between two points are cut.
+/// - Contours of fewer than 3 points and non-positive spacings are returned unchanged.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `spacing` - The target distance between two consecutive points along the loop.
+///
+/// # Returns
+///
+/// The resampled loop, starting at the first vertex and without repeating it.
+pub fn resample(contour: &[Vec2], spacing: f32) -> Vec<Vec2> {
+    let n = contour.len();
+    if n < 3 || spacing <= 0.0 {
+        return contour.to_vec();
+    }
+    let edge_length = |i: usize| contour[i].distance(contour[(i + 1) % n]);
+    let perimeter: f32 = (0..n).map(edge_length).sum();
+    let count = ((perimeter / spacing).round() as usize).max(3);
+    let step = perimeter / count as f32;
+
+    let (mut edge, mut edge_start) = (0, 0.0);
+    (0..count)
+        .map(|k| {
+            let along = k as f32 * step;
+            while edge < n - 1 && along > edge_start + edge_length(edge) {
+                edge_start += edge_length(edge);
+                edge += 1;
+            }
+            let a = contour[edge];
+            a + (contour[(edge + 1) % n] - a).normalize_or_zero() * (along - edge_start)
+        })
+        .collect()
+}
+
+/// Removes the points of a closed contour that barely change its shape, with the Ramer-Douglas-Peucker
+/// algorithm.
+///
+/// # Remarks
+///
+/// - The loop is split in two at its first vertex and the vertex **furthest** from it, which are
+///   always kept. Each half is then simplified as a polyline: the point furthest from the chord of
+///   a run is kept if it lies more than `tolerance` away, and both sides of it are simplified in turn.
+/// - Every removed point lies within `tolerance` of the simplified loop, and the kept points keep
+///   their order, so the loop stays closed with the same winding.
+/// - Contours of fewer than 4 points are returned unchanged.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `tolerance` - The largest distance between a removed point and the simplified loop.
+///
+/// # Returns
+///
+/// The simplified loop, starting at the first vertex and without repeating it.
+pub fn simplify(contour: &[Vec2], tolerance: f32) -> Vec<Vec2> {
+    let n = contour.len();
+    if n < 4 {
+        return contour.to_vec();
+    }
+    let far = (1..n).fold(1, |best, i| {
+        if contour[i].distance_squared(contour[0]) > contour[best].distance_squared(contour[0]) {
+            i
+        } else {
+            best
+        }
+    });
+
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[far] = true;
+    // Runs of the loop between two kept points, the second half wrapping back to the first vertex.
+    let mut runs = vec![(0, far), (far, n)];
+    while let Some((start, end)) = runs.pop() {
+        let (a, b) = (contour[start], contour[end % n]);
+        let furthest = (start + 1..end)
+            .map(|i| (i, distance_to_segment(contour[i], a, b)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, distance)) = furthest {
+            if distance > tolerance {
+                keep[i] = true;
+                runs.push((start, i));
+                runs.push((i, end));
+            }
+        }
+    }
+
+    (0..n).filter(|&i| keep[i]).map(|i| contour[i]).collect()
+}
+
+/// Finds the points where a closed contour crosses or touches itself.
+///
+/// # Remarks
+///
+/// - The edges are swept along the X axis: each edge is only tested against the edges whose extent
+///   along X overlaps its own, which keeps the search close to linear on regular contours.
+/// - Consecutive edges always share a vertex and are not tested against each other. Any other pair
+///   of edges meeting, see `segment_intersection`, is reported, including a vertex visited twice.
+/// - Points closer than `epsilon` are reported **once**, in the order the sweep finds them.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `epsilon` - The distance under which points are considered to touch.
+///
+/// # Returns
+///
+/// The self-intersection points, empty for a simple contour.
+pub fn find_self_intersections(contour: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    let n = contour.len();
+    if n < 4 {
+        return Vec::new();
+    }
+    let edge = |i: usize| [contour[i], contour[(i + 1) % n]];
+    let adjacent = |i: usize, j: usize| (i + 1) % n == j || (j + 1) % n == i;
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| {
+        let (a, b) = (edge(i), edge(j));
+        a[0].x.min(a[1].x).total_cmp(&b[0].x.min(b[1].x))
+    });
+
+    let mut points: Vec<Vec2> = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    for i in order {
+        let [a, b] = edge(i);
+        active.retain(|&j| {
+            let [c, d] = edge(j);
+            c.x.max(d.x) >= a.x.min(b.x) - epsilon
+        });
+        for &j in &active {
+            if adjacent(i, j) {
+                continue;
+            }
+            if let Some(point) = segment_intersection(edge(j), [a, b], epsilon) {
+                if !points.iter().any(|p| p.distance(point) <= epsilon) {
+                    points.push(point);
+                }
+            }
+        }
+        active.push(i);
+    }
+
+    points
+}
+
+/// Returns whether a closed contour is simple, i.e. does not cross or touch itself, see
+/// `find_self_intersections`.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `epsilon` - The distance under which points are considered to touch.
+pub fn is_simple(contour: &[Vec2], epsilon: f32) -> bool {
+    find_self_intersections(contour, epsilon).is_empty()
+}
+
+/// Joins the consecutive segments of a layer that continue each other in nearly the same direction,
+/// so dense meshes give `build_contours` fewer segments to stitch.
+///
+/// # Remarks
+///
+/// - Only segments **next to each other** in `segments` are joined, as the slices of neighbouring
+///   triangles usually are. A segment is joined to the previous one when they share an endpoint
+///   within `EPSILON`, in either orientation, and turn by less than `angle_tol_deg` there.
+/// - The shared endpoint disappears, so this assumes no third segment ends there, as in the slice
+///   of a manifold mesh. Degenerate segments are never joined.
+/// - The merged segments keep their order, and the corners of the layer are left untouched.
+///
+/// # Arguments
+///
+/// * `segments` - The intersection segments of a single layer.
+/// * `angle_tol_deg` - The largest turn between two segments for them to be joined, in degrees.
+pub fn merge_collinear_segments(segments: &mut Vec<[Vec3; 2]>, angle_tol_deg: f32) {
+    let max_cos = angle_tol_deg.to_radians().cos();
+    let mut merged: Vec<[Vec3; 2]> = Vec::with_capacity(segments.len());
+    for &[c, d] in segments.iter() {
+        let Some(&[a, b]) = merged.last() else {
+            merged.push([c, d]);
+            continue;
+        };
+        // The outer ends of the pair around the joint, if the segments share an endpoint.
+        let joined = if b.abs_diff_eq(c, EPSILON) {
+            Some((a, b, d))
+        } else if b.abs_diff_eq(d, EPSILON) {
+            Some((a, b, c))
+        } else if a.abs_diff_eq(c, EPSILON) {
+            Some((d, a, b))
+        } else if a.abs_diff_eq(d, EPSILON) {
+            Some((c, a, b))
+        } else {
+            None
+        };
+        match joined {
+            Some((start, joint, end))
+                if (joint - start)
+                    .normalize_or_zero()
+                    .dot((end - joint).normalize_or_zero())
+                    > max_cos =>
+            {
+                *merged.last_mut().unwrap() = [start, end];
+            }
+            _ => merged.push([c, d]),
+        }
+    }
+    *segments = merged;
+}
+
+/// Returns whether a polyline produced by `build_contours` is a closed loop.
+///
+/// # Arguments
+///
+/// * `contour` - The polyline to check.
+/// * `epsilon` - The maximum absolute difference per coordinate for the ends to be considered joined.
+pub fn is_closed(contour: &[Vec3], epsilon: f32) -> bool {
+    contour.len() > 3 && contour[0].abs_diff_eq(contour[contour.len() - 1], epsilon)
+}
+
+/// Segment endpoints sorted along the x axis so the neighbours of a point can be found by binary search.
+struct EndpointIndex<'a> {
+    segments: &'a [[Vec3; 2]],
+    /// Pairs of (x coordinate, segment index * 2 + endpoint index), sorted by x.
+    by_x: Vec<(f32, usize)>,
+}
+
+impl<'a> EndpointIndex<'a> {
+    fn new(segments: &'a [[Vec3; 2]]) -> Self {
+        let mut by_x: Vec<(f32, usize)> = segments
+            .iter()
+            .enumerate()
+            .flat_map(|(i, s)| [(s[0].x, i * 2), (s[1].x, i * 2 + 1)])
+            .collect();
+        by_x.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        EndpointIndex { segments, by_x }
+    }
+
+    /// Finds the unused segment with the lowest index touching `point`, marks it as used, and returns
+    /// its opposite endpoint.
+    fn take_next(&self, point: Vec3, epsilon: f32, used: &mut [bool]) -> Option<Vec3> {
+        let lower = self.by_x.partition_point(|&(x, _)| x < point.x - epsilon);
+        let found = self.by_x[lower..]
+            .iter()
+            .take_while(|&&(x, _)| x <= point.x + epsilon)
+            .map(|&(_, endpoint)| endpoint)
+            .filter(|&endpoint| {
+                !used[endpoint / 2]
+                    && self.segments[endpoint / 2][endpoint % 2].abs_diff_eq(point, epsilon)
+            })
+            .min()?;
+
+        let segment = found / 2;
+        used[segment] = true;
+        Some(self.segments[segment][1 - found % 2])
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::geometry::to_planar;
+    #[allow(unused_imports)]
+    use crate::slicer::{slice_triangle, SliceConfig, EPSILON};
+
+    /// Test for the `build_contours` function on a hollow cylinder sliced at mid-height.
+    #[test]
+    fn test_build_contours_hollow_cylinder() {
+        let triangles = crate::fixtures::tube_triangles(1.0, 2.0, 2.0, 24);
+        let segments: Vec<[Vec3; 2]> = triangles
+            .iter()
+            .map(|t| slice_triangle(t, 1.0, &SliceConfig::default()))
+            .filter(|points| points.len() == 2)
+            .map(|points| [points[0], points[1]])
+            .collect();
+        assert_eq!(segments.len(), 2 * 2 * 24);
+
+        let report = build_contours(&segments, EPSILON, EPSILON);
+        assert!(report.bridged.is_empty() && report.dangling.is_empty());
+        let contours = report.closed;
+        assert_eq!(contours.len(), 2);
+        for contour in &contours {
+            assert!(is_closed(contour, EPSILON));
+            assert_eq!(contour.len(), 24 * 2 + 1);
+        }
+
+        // Points on the quad edges lie on the circle, those on the diagonals fall slightly inside.
+        let radius = |contour: &Vec<Vec3>| {
+            contour
+                .iter()
+                .map(|&p| to_planar(p).length())
+                .fold(0.0, f32::max)
+        };
+        let mut radii: Vec<f32> = contours.iter().map(radius).collect();
+        radii.sort_by(f32::total_cmp);
+        assert!((radii[0] - 1.0).abs() < 1e-4);
+        assert!((radii[1] - 2.0).abs() < 1e-4);
+
+        // The outer wall turns counter-clockwise and the hole clockwise, whatever the segment order.
+        for contour in &contours {
+            let planar: Vec<Vec2> = contour.iter().map(|&p| to_planar(p)).collect();
+            let area = crate::geometry::signed_area(&planar);
+            assert_eq!(area > 0.0, radius(contour) > 1.5);
+        }
+
+        assert_eq!(build_contours(&segments, EPSILON, EPSILON).closed, contours);
+    }
+
+    /// Test for the `build_contours` function when a chain cannot be closed.
+    #[test]
+    fn test_build_contours_open_chain() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(1.0, 0.0, 1.0);
+        let d = Vec3::new(0.0, 0.0, 1.0);
+        // Starts in the middle of the chain, with the segments flipped arbitrarily.
+        let segments = [[c, b], [a, b], [d, c]];
+
+        let contours = build_contours(&segments, EPSILON, EPSILON).dangling;
+        assert_eq!(contours, vec![vec![d, c, b, a]]);
+        assert!(!is_closed(&contours[0], EPSILON));
+    }
+
+    /// Test for the `build_contours` function bridging the gap left by a missing triangle of a cube.
+    #[test]
+    fn test_build_contours_bridge_gap() {
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::ONE);
+        let config = SliceConfig::default();
+        let crossing = |t: &[Vec3; 3]| slice_triangle(t, 0.5, &config).len() == 2;
+        let missing = triangles.iter().position(crossing).unwrap();
+        triangles.remove(missing);
+
+        let segments: Vec<[Vec3; 2]> = triangles
+            .iter()
+            .map(|t| slice_triangle(t, 0.5, &config))
+            .filter(|points| points.len() == 2)
+            .map(|points| [points[0], points[1]])
+            .collect();
+        assert_eq!(segments.len(), 7);
+
+        let report = build_contours(&segments, EPSILON, 0.1);
+        assert!(report.closed.is_empty() && report.bridged.is_empty());
+        assert_eq!(report.dangling.len(), 1);
+        assert_eq!(report.dangling[0].len(), 8);
+
+        let report = build_contours(&segments, EPSILON, 1.0);
+        assert!(report.closed.is_empty() && report.dangling.is_empty());
+        assert_eq!(report.bridged.len(), 1);
+        assert!(is_closed(&report.bridged[0], EPSILON));
+        assert_eq!(report.loops().count(), 1);
+    }
+
+    /// Test for the `prune_small` function removing a tiny island next to the main contour.
+    #[test]
+    fn test_prune_small() {
+        let main = crate::fixtures::square(Vec2::ZERO, 10.0);
+        let mut contours = vec![
+            crate::fixtures::square(Vec2::new(20.0, 0.0), 0.1),
+            main.clone(),
+        ];
+
+        prune_small(&mut contours, 0.05, 0.0);
+        assert_eq!(contours, vec![main.clone()]);
+
+        // A long and thin sliver has a large enough perimeter but no area.
+        let mut sliver: Vec<Vec2> = crate::fixtures::square(Vec2::new(20.0, 0.0), 1.0);
+        sliver[2].y = 1e-3;
+        sliver[3].y = 1e-3;
+        contours.push(sliver);
+        prune_small(&mut contours, 0.05, 1.0);
+        assert_eq!(contours, vec![main]);
+    }
+
+    /// Test for the `find_self_intersections` function on a figure-eight contour.
+    #[test]
+    fn test_find_self_intersections_figure_eight() {
+        let figure_eight = [
+            Vec2::ZERO,
+            Vec2::splat(2.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        let points = find_self_intersections(&figure_eight, EPSILON);
+        assert_eq!(points.len(), 1);
+        assert!(points[0].abs_diff_eq(Vec2::ONE, 1e-5));
+        assert!(!is_simple(&figure_eight, EPSILON));
+
+        let square = [
+            Vec2::ZERO,
+            Vec2::new(2.0, 0.0),
+            Vec2::splat(2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert!(is_simple(&square, EPSILON));
+    }
+
+    /// Test for the `resample` function spacing points evenly around a square.
+    #[test]
+    fn test_resample_square() {
+        let square = [
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::splat(10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let points = resample(&square, 1.0);
+        assert_eq!(points.len(), 40);
+        assert_eq!(points[0], Vec2::ZERO);
+        for i in 0..points.len() {
+            let gap = points[i].distance(points[(i + 1) % points.len()]);
+            assert!((gap - 1.0).abs() < 1e-4);
+        }
+
+        // Short edges get no point of their own, the corners are cut.
+        let coarse = resample(&square, 15.0);
+        assert_eq!(coarse.len(), 3);
+        assert!(coarse[1].abs_diff_eq(Vec2::new(10.0, 10.0 / 3.0), 1e-4));
+        assert_eq!(resample(&square, 0.0), square.to_vec());
+    }
+
+    /// Test for the `simplify` function on a finely sampled circle.
+    #[test]
+    fn test_simplify_circle() {
+        let circle: Vec<Vec2> = (0..360)
+            .map(|i| Vec2::from_angle((i as f32).to_radians()) * 10.0)
+            .collect();
+        let simplified = simplify(&circle, 0.05);
+        assert!(simplified.len() < 60);
+        assert!(simplified.len() >= 20);
+        assert_eq!(simplified[0], circle[0]);
+        assert!(signed_area(&simplified) > 0.0);
+
+        let n = simplified.len();
+        for &p in &circle {
+            let distance = (0..n)
+                .map(|i| distance_to_segment(p, simplified[i], simplified[(i + 1) % n]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= 0.05 + 1e-4);
+        }
+    }
+
+    /// Test for the `merge_collinear_segments` function joining a line of unit segments.
+    #[test]
+    fn test_merge_collinear_segments() {
+        // Every other segment is reversed, as the slices of triangles are unoriented.
+        let mut segments: Vec<[Vec3; 2]> = (0..10)
+            .map(|i| {
+                let (a, b) = (Vec3::X * i as f32, Vec3::X * (i + 1) as f32);
+                if i % 2 == 0 {
+                    [a, b]
+                } else {
+                    [b, a]
+                }
+            })
+            .collect();
+        merge_collinear_segments(&mut segments, 1.0);
+        assert_eq!(segments, vec![[Vec3::ZERO, Vec3::X * 10.0]]);
+
+        // A corner is kept, and so are segments that do not touch.
+        let mut segments = vec![
+            [Vec3::ZERO, Vec3::X],
+            [Vec3::X, Vec3::new(1.0, 0.0, 1.0)],
+            [Vec3::new(5.0, 0.0, 5.0), Vec3::new(6.0, 0.0, 5.0)],
+        ];
+        let original = segments.clone();
+        merge_collinear_segments(&mut segments, 1.0);
+        assert_eq!(segments, original);
+    }
+}
@@ -0,0 +1,286 @@
+use std::f32::consts::PI;
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::gcode::{arc_length, Move};
+use crate::machine::MachineConfig;
+use crate::slicer::EPSILON;
+
+/// The totals of a single layer of a print, see `PrintEstimate`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayerStats {
+    /// The height of the nozzle at the first extrusion of the layer.
+    pub z: f32,
+    /// The length of filament pushed into the nozzle, in millimeters.
+    pub filament_length: f32,
+    /// The distance covered while extruding, in millimeters.
+    pub print_distance: f32,
+    /// The distance covered by travel moves, in millimeters.
+    pub travel_distance: f32,
+    /// The estimated duration of the layer, in seconds.
+    pub time: f32,
+}
+
+/// The estimated cost of a print: how long it takes and how much filament it uses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrintEstimate {
+    /// The length of filament pushed into the nozzle, in millimeters.
+    pub filament_length: f32,
+    /// The volume of plastic extruded, in cubic millimeters.
+    pub extruded_volume: f32,
+    /// The distance covered while extruding, in millimeters.
+    pub print_distance: f32,
+    /// The distance covered by travel moves, in millimeters.
+    pub travel_distance: f32,
+    /// The estimated duration of the print, in seconds.
+    pub time: f32,
+    /// The totals of every layer, in printing order.
+    pub layers: Vec<LayerStats>,
+}
+
+/// Estimates the duration and the filament usage of a print from its moves.
+///
+/// # Remarks
+///
+/// - Each move follows a **trapezoidal** speed profile: it starts and ends at rest, accelerates up to
+///   the feedrate of its kind and decelerates back, or never reaches the feedrate when it is too
+///   short. Moves do not blend into each other, so the estimate errs on the slow side.
+/// - Retractions are timed at the retraction feedrate without acceleration, and are not counted in
+///   the filament usage since they are pushed back before the next extrusion. The purge of a tool
+///   change is timed the same way but counted as used filament.
+/// - A `Move::SpeedFactor` scales the feedrates of the following travels and extrusions.
+/// - The nozzle starts at the origin. The moves are split into layers by `layer_starts`, so the
+///   travels of a Z hop and the climb of a spiral vase do not start layers of their own.
+///
+/// # Arguments
+///
+/// * `moves` - The moves of the print, as generated by `GcodeWriter::moves`.
+/// * `config` - The limits of the printer.
+///
+/// # Returns
+///
+/// A `PrintEstimate` with the totals of the print and of each of its layers.
+pub fn estimate_print(moves: &[Move], config: &MachineConfig) -> PrintEstimate {
+    let mut estimate = PrintEstimate::default();
+    let mut position = Vec3::ZERO;
+    let mut factor = 1.0;
+    let starts = layer_starts(moves);
+    let mut located = false;
+
+    for (i, m) in moves.iter().enumerate() {
+        if starts.contains(&i) {
+            estimate.layers.push(LayerStats::default());
+            located = false;
+        }
+        let Some(stats) = estimate.layers.last_mut() else {
+            continue;
+        };
+        match *m {
+            Move::Extrude { to, .. } | Move::Arc { to, .. } if !located => {
+                stats.z = to.z;
+                located = true;
+            }
+            _ => {}
+        }
+
+        match *m {
+            Move::Travel { to } => {
+                let distance = position.distance(to);
+                stats.travel_distance += distance;
+                let feedrate = config.travel_feedrate * factor;
+                stats.time += move_time(distance, feedrate, config.acceleration);
+                position = to;
+            }
+            Move::Extrude { to, e, .. } => {
+                let distance = position.distance(to);
+                stats.print_distance += distance;
+                stats.filament_length += e;
+                stats.time += move_time(
+                    distance,
+                    config.print_feedrate * factor,
+                    config.acceleration,
+                );
+                position = to;
+            }
+            Move::Arc {
+                to,
+                center,
+                clockwise,
+                e,
+                ..
+            } => {
+                let distance = arc_length(position.truncate(), to.truncate(), center, clockwise);
+                stats.print_distance += distance;
+                stats.filament_length += e;
+                stats.time += move_time(
+                    distance,
+                    config.print_feedrate * factor,
+                    config.acceleration,
+                );
+                position = to;
+            }
+            Move::Retract { length } | Move::Unretract { length } => {
+                stats.time += length / config.retraction_feedrate;
+            }
+            Move::ToolChange { purge, .. } => {
+                stats.filament_length += purge;
+                stats.time += purge / config.retraction_feedrate;
+            }
+            Move::Fan { .. } => {}
+            Move::SpeedFactor { factor: next } => factor = next,
+        }
+    }
+    for stats in &estimate.layers {
+        estimate.filament_length += stats.filament_length;
+        estimate.print_distance += stats.print_distance;
+        estimate.travel_distance += stats.travel_distance;
+        estimate.time += stats.time;
+    }
+    estimate.extruded_volume =
+        estimate.filament_length * PI * (config.filament_diameter / 2.0).powi(2);
+
+    estimate
+}
+
+/// Splits the moves of a print into layers, returning the index of the first move of each layer.
+///
+/// # Remarks
+///
+/// - Layers are told apart by the height of the **extrusions** only: travels, such as the lift of
+///   a Z hop, never start a layer. The moves between two layers, e.g. the retraction and the travel
+///   to the next layer, belong to the next one.
+/// - An extrusion higher than the previous one starts a new layer once the current layer has
+///   printed at a constant height. A layer rising from its first extrusion on is **climbing** like
+///   a loop of a spiral vase, and lasts until the nozzle comes back to where the loop started.
+/// - A print without extrusions is a single layer, and no moves make no layer.
+pub(crate) fn layer_starts(moves: &[Move]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut position = Vec3::ZERO;
+    let mut layer: Option<LayerShape> = None;
+    let mut next_start = 0;
+
+    for (i, m) in moves.iter().enumerate() {
+        let (Move::Travel { to } | Move::Extrude { to, .. } | Move::Arc { to, .. }) = *m else {
+            continue;
+        };
+        if let Move::Travel { .. } = m {
+            position = to;
+            continue;
+        }
+
+        let starts_layer = match &mut layer {
+            Some(shape) if to.z > shape.z + EPSILON => {
+                if shape.flat || shape.closed {
+                    true
+                } else {
+                    shape.z = to.z;
+                    false
+                }
+            }
+            Some(shape) => {
+                shape.flat = true;
+                false
+            }
+            None => true,
+        };
+        if starts_layer {
+            starts.push(next_start);
+            layer = Some(LayerShape {
+                z: to.z,
+                // A spiral loop may start with a hop to its first point, so it closes on either.
+                starts: [position.truncate(), to.truncate()],
+                flat: false,
+                closed: false,
+            });
+        } else if let Some(shape) = &mut layer {
+            let back = shape
+                .starts
+                .iter()
+                .any(|start| to.truncate().abs_diff_eq(*start, EPSILON));
+            shape.closed |= !shape.flat && back;
+        }
+        position = to;
+        next_start = i + 1;
+    }
+
+    if starts.is_empty() && !moves.is_empty() {
+        starts.push(0);
+    }
+    starts
+}
+
+/// The state of the layer being split off by `layer_starts`.
+struct LayerShape {
+    /// The height of the last extrusion.
+    z: f32,
+    /// Where the layer started: the nozzle before its first extrusion, and the end of it.
+    starts: [Vec2; 2],
+    /// Whether two consecutive extrusions were printed at the same height.
+    flat: bool,
+    /// Whether a climbing layer came back to where it started.
+    closed: bool,
+}
+
+/// Computes the duration of a move starting and ending at rest, limited by a feedrate and an
+/// acceleration.
+fn move_time(distance: f32, feedrate: f32, acceleration: f32) -> f32 {
+    // Reaching the feedrate and stopping again takes `feedrate / acceleration` seconds and covers
+    // `feedrate² / acceleration` millimeters.
+    let ramps = feedrate * feedrate / acceleration;
+    if distance >= ramps {
+        distance / feedrate + feedrate / acceleration
+    } else {
+        2.0 * (distance / acceleration).sqrt()
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::gcode::{GcodeLayer, GcodeWriter};
+
+    /// Test for the `move_time` function with and without reaching the feedrate.
+    #[test]
+    fn test_move_time() {
+        // 1s to reach 100mm/s and 1s to stop cover 100mm, the remaining 100mm take another 1s.
+        assert!((move_time(200.0, 100.0, 100.0) - 3.0).abs() < 1e-5);
+        // 25mm are covered in 0.5s up to 50mm/s and 0.5s back down.
+        assert!((move_time(25.0, 100.0, 100.0) - 1.0).abs() < 1e-5);
+    }
+
+    /// Test for the `estimate_print` function on a sliced cube.
+    #[test]
+    fn test_estimate_print_cube() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(10.0));
+        let layers: Vec<GcodeLayer> = crate::slicer::slice_mesh(&mesh, 0.2)
+            .unwrap()
+            .iter()
+            .map(GcodeLayer::from_layer)
+            .collect();
+        let writer = GcodeWriter::default();
+        let moves = writer.moves(&layers);
+
+        let config = MachineConfig::default();
+        let estimate = estimate_print(&moves, &config);
+        assert!(estimate.filament_length > 0.0);
+        assert!(estimate.extruded_volume > 0.0);
+        assert_eq!(estimate.layers.len(), 50);
+        // Every layer prints the 40mm outline of the cube.
+        assert!((estimate.print_distance - 50.0 * 40.0).abs() < 1e-1);
+        let layer_time: f32 = estimate.layers.iter().map(|l| l.time).sum();
+        assert!((estimate.time - layer_time).abs() < 1e-3);
+
+        // Doubling every speed roughly halves the time, acceleration keeping it a bit longer.
+        let fast = MachineConfig {
+            print_feedrate: config.print_feedrate * 2.0,
+            travel_feedrate: config.travel_feedrate * 2.0,
+            retraction_feedrate: config.retraction_feedrate * 2.0,
+            ..config.clone()
+        };
+        let fast_estimate = estimate_print(&moves, &fast);
+        assert!(fast_estimate.time < estimate.time);
+        assert!(fast_estimate.time > estimate.time / 2.0);
+        assert_eq!(fast_estimate.filament_length, estimate.filament_length);
+    }
+}
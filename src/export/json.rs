@@ -0,0 +1,82 @@
+use glam::f32::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::slicer::SlicedLayer;
+
+/// The JSON document of a sliced layer, as written by `layer_to_json`.
+///
+/// # Remarks
+///
+/// - Points are written as `[x, z]` arrays in the plane of the layer, and lines as pairs of points.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonLayer {
+    /// The height of the layer along the build axis.
+    pub z: f32,
+    /// The thickness of the layer.
+    pub thickness: f32,
+    /// The index of the object the layer belongs to.
+    pub object: usize,
+    /// The outlines of the layer.
+    pub contours: Vec<Vec<Vec2>>,
+    /// The centerlines of the walls, outermost first.
+    pub perimeters: Vec<Vec<Vec2>>,
+    /// The lines filling the solid regions.
+    pub solid_infill: Vec<[Vec2; 2]>,
+    /// The lines filling the sparse regions.
+    pub sparse_infill: Vec<[Vec2; 2]>,
+}
+
+impl From<&SlicedLayer> for JsonLayer {
+    fn from(layer: &SlicedLayer) -> Self {
+        JsonLayer {
+            z: layer.height,
+            thickness: layer.thickness,
+            object: 0,
+            contours: layer.contours.clone(),
+            perimeters: layer.perimeters.clone(),
+            solid_infill: layer.solid_infill.clone(),
+            sparse_infill: layer.sparse_infill.clone(),
+        }
+    }
+}
+
+/// Serializes a sliced layer to JSON for external viewers.
+///
+/// # Remarks
+///
+/// - Only available with the `json` feature.
+/// - A `SlicedModel` holds a **single object**, so the layer is tagged as object 0, like the first
+///   mesh of a plate. Change `JsonLayer::object` and serialize it directly for other objects.
+///
+/// # Arguments
+///
+/// * `layer` - The layer to serialize.
+///
+/// # Returns
+///
+/// The `JsonLayer` of the layer as a compact JSON string.
+pub fn layer_to_json(layer: &SlicedLayer) -> String {
+    serde_json::to_string(&JsonLayer::from(layer)).expect("a layer always serializes to JSON")
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `layer_to_json` function round-tripping a layer of a sliced cube.
+    #[test]
+    fn test_layer_to_json_round_trip() {
+        let mesh = crate::fixtures::box_mesh(glam::Vec3::ZERO, glam::Vec3::splat(10.0));
+        let model = crate::slicer::Slicer::new().slice(&mesh).unwrap();
+        let layer = &model.layers[10];
+
+        let json = layer_to_json(layer);
+        assert!(json.contains("\"object\":0"));
+        let parsed: JsonLayer = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, JsonLayer::from(layer));
+        assert_eq!(parsed.z, layer.height);
+        assert_eq!(parsed.contours.len(), 1);
+        assert!(!parsed.perimeters.is_empty());
+        assert!(!parsed.sparse_infill.is_empty());
+    }
+}
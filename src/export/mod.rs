@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::contour::build_contours;
+use crate::geometry::to_planar;
+use crate::slicer::{Layer, EPSILON};
+
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "image")]
+mod png;
+
+#[cfg(feature = "json")]
+pub use json::{layer_to_json, JsonLayer};
+#[cfg(feature = "image")]
+pub use png::layer_to_png;
+
+/// Stroke color of the segments belonging to a closed contour.
+const CLOSED_COLOR: &str = "#2e7d32";
+
+/// Stroke color of the segments belonging to an open polyline.
+const OPEN_COLOR: &str = "#c62828";
+
+/// Renders the segments of a layer as an SVG document for visual debugging.
+///
+/// # Remarks
+///
+/// - The layer is seen **from above**: X maps to the horizontal axis and Z to the vertical axis,
+///   growing upwards like a regular 2D frame.
+/// - The drawing is **uniformly scaled** to fit the viewport, keeping a small margin on every side.
+/// - Each segment is drawn as its own `<line>`, green when it belongs to a closed contour and red
+///   when it belongs to an open polyline.
+///
+/// # Arguments
+///
+/// * `layer` - The layer to render.
+/// * `width` - The width of the SVG viewport.
+/// * `height` - The height of the SVG viewport.
+///
+/// # Returns
+///
+/// The SVG document as a string.
+pub fn layer_to_svg(layer: &Layer, width: f32, height: f32) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    let _ = writeln!(svg, "<!-- layer at y = {} -->", layer.height);
+
+    let points = layer.segments.iter().flatten().map(|&p| to_planar(p));
+    let Some((min, max)) = points.fold(None, |bounds: Option<(Vec2, Vec2)>, p| {
+        Some(bounds.map_or((p, p), |(min, max)| (min.min(p), max.max(p))))
+    }) else {
+        svg.push_str("</svg>\n");
+        return svg;
+    };
+
+    let margin = 0.05 * width.min(height);
+    let extent = (max - min).max(Vec2::splat(EPSILON));
+    let scale = ((width - 2.0 * margin) / extent.x).min((height - 2.0 * margin) / extent.y);
+    let project = |p: Vec3| {
+        let offset = (to_planar(p) - min) * scale;
+        Vec2::new(margin + offset.x, height - margin - offset.y)
+    };
+
+    let report = build_contours(&layer.segments, EPSILON, EPSILON);
+    let closed = report.loops().map(|contour| (contour, CLOSED_COLOR));
+    let open = report.dangling.iter().map(|contour| (contour, OPEN_COLOR));
+    for (contour, color) in closed.chain(open) {
+        for pair in contour.windows(2) {
+            let (a, b) = (project(pair[0]), project(pair[1]));
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.3}" y1="{:.3}" x2="{:.3}" y2="{:.3}" stroke="{color}" stroke-width="1"/>"#,
+                a.x, a.y, b.x, b.y
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `layer_to_svg` function drawing one line per segment of a cube layer.
+    #[test]
+    fn test_layer_to_svg_cube() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        let layer = &crate::slicer::slice_mesh(&mesh, 0.5).unwrap()[0];
+
+        let svg = layer_to_svg(layer, 200.0, 100.0);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line").count(), layer.segments.len());
+        assert_eq!(svg.matches(CLOSED_COLOR).count(), 8);
+        assert_eq!(svg.matches(OPEN_COLOR).count(), 0);
+    }
+
+    /// Test for the `layer_to_svg` function coloring open polylines differently.
+    #[test]
+    fn test_layer_to_svg_open() {
+        let layer = Layer {
+            height: 0.0,
+            thickness: 0.2,
+            segments: vec![[Vec3::ZERO, Vec3::X], [Vec3::X, Vec3::new(1.0, 0.0, 1.0)]],
+        };
+
+        let svg = layer_to_svg(&layer, 100.0, 100.0);
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert_eq!(svg.matches(OPEN_COLOR).count(), 2);
+
+        let empty = Layer {
+            segments: Vec::new(),
+            ..layer
+        };
+        assert_eq!(
+            layer_to_svg(&empty, 100.0, 100.0).matches("<line").count(),
+            0
+        );
+    }
+}
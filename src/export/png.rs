@@ -0,0 +1,135 @@
+use glam::f32::Vec2;
+use image::{Rgba, RgbaImage};
+
+use crate::slicer::SlicedLayer;
+
+/// Color of the area enclosed by the contours of the layer.
+const FILL_COLOR: Rgba<u8> = Rgba([200, 200, 200, 255]);
+
+/// Color of the centerlines of the walls.
+const PERIMETER_COLOR: Rgba<u8> = Rgba([46, 125, 50, 255]);
+
+/// Color of the infill lines, solid and sparse.
+const INFILL_COLOR: Rgba<u8> = Rgba([21, 101, 192, 255]);
+
+/// Rasterizes a sliced layer into an image, e.g. for a thumbnail of the print.
+///
+/// # Remarks
+///
+/// - Only available with the `image` feature.
+/// - The layer is seen **from above** like in `layer_to_svg`: X maps to the columns and Z to the
+///   rows, growing upwards. The image covers the bounding box of the contours, one pixel per
+///   `resolution` millimeters.
+/// - A pixel is filled when its center lies inside the contours by the **even-odd rule**, so holes
+///   stay transparent like everything outside. Perimeters and infill are drawn over the fill in
+///   their own colors, one pixel wide.
+///
+/// # Arguments
+///
+/// * `layer` - The layer to render.
+/// * `resolution` - The size of a pixel, in millimeters.
+///
+/// # Returns
+///
+/// The image of the layer, empty if the layer has no contours or the resolution is not positive.
+pub fn layer_to_png(layer: &SlicedLayer, resolution: f32) -> RgbaImage {
+    let mut points = layer.contours.iter().flatten().copied();
+    let Some(first) = points.next() else {
+        return RgbaImage::new(0, 0);
+    };
+    if resolution <= 0.0 {
+        return RgbaImage::new(0, 0);
+    }
+    let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+    let size = ((max - min) / resolution).ceil().max(Vec2::ONE);
+    let (width, height) = (size.x as u32, size.y as u32);
+    let mut image = RgbaImage::new(width, height);
+
+    for row in 0..height {
+        let y = max.y - (row as f32 + 0.5) * resolution;
+        let mut crossings: Vec<f32> = layer
+            .contours
+            .iter()
+            .flat_map(|contour| {
+                (0..contour.len()).filter_map(move |i| {
+                    let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+                    // Half-open on y so a vertex shared by two edges is counted once.
+                    ((a.y <= y) != (b.y <= y)).then(|| a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x))
+                })
+            })
+            .collect();
+        crossings.sort_by(f32::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            let start = ((span[0] - min.x) / resolution - 0.5).ceil().max(0.0) as u32;
+            let end = ((span[1] - min.x) / resolution - 0.5).floor();
+            if end < 0.0 {
+                continue;
+            }
+            for column in start..=(end as u32).min(width - 1) {
+                image.put_pixel(column, row, FILL_COLOR);
+            }
+        }
+    }
+
+    let mut draw = |a: Vec2, b: Vec2, color: Rgba<u8>| {
+        let steps = (a.distance(b) / (resolution / 2.0)).ceil().max(1.0) as usize;
+        for k in 0..=steps {
+            let p = (a.lerp(b, k as f32 / steps as f32) - min) / resolution;
+            let (column, row) = (p.x.floor(), (size.y - p.y).floor());
+            if column >= 0.0 && row >= 0.0 && column < size.x && row < size.y {
+                image.put_pixel(column as u32, row as u32, color);
+            }
+        }
+    };
+    for perimeter in &layer.perimeters {
+        for i in 0..perimeter.len() {
+            draw(
+                perimeter[i],
+                perimeter[(i + 1) % perimeter.len()],
+                PERIMETER_COLOR,
+            );
+        }
+    }
+    for [a, b] in layer.solid_infill.iter().chain(&layer.sparse_infill) {
+        draw(*a, *b, INFILL_COLOR);
+    }
+
+    image
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `layer_to_png` function filling a square with a hole.
+    #[test]
+    fn test_layer_to_png_square() {
+        let mut hole = crate::fixtures::square(Vec2::splat(4.0), 2.0);
+        hole.reverse();
+        let layer = SlicedLayer {
+            height: 0.1,
+            thickness: 0.2,
+            contours: vec![crate::fixtures::square(Vec2::ZERO, 10.0), hole],
+            perimeters: vec![crate::fixtures::square(Vec2::splat(0.2), 9.6)],
+            solid_infill: vec![[Vec2::new(1.0, 2.05), Vec2::new(9.0, 2.05)]],
+            sparse_infill: Vec::new(),
+        };
+
+        let image = layer_to_png(&layer, 0.1);
+        assert_eq!(image.dimensions(), (100, 100));
+        // Rows grow downwards, the pixel at (x, z) is in column 10x and row 100 - 10z.
+        let at = |x: f32, z: f32| *image.get_pixel((x * 10.0) as u32, (100.0 - z * 10.0) as u32);
+        assert_eq!(at(1.0, 1.0), FILL_COLOR);
+        assert_eq!(at(5.0, 5.0), Rgba([0, 0, 0, 0]));
+        assert_eq!(at(0.25, 5.0), PERIMETER_COLOR);
+        assert_eq!(at(5.0, 2.05), INFILL_COLOR);
+        assert_eq!(at(8.0, 8.0), FILL_COLOR);
+
+        let empty = SlicedLayer {
+            contours: Vec::new(),
+            ..layer
+        };
+        assert_eq!(layer_to_png(&empty, 0.1).dimensions(), (0, 0));
+    }
+}
@@ -0,0 +1,124 @@
+//! Shared geometry for the unit tests of the crate.
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::mesh::Mesh;
+
+/// Corner indices of each face of a box, counter-clockwise when seen from outside.
+/// Corner `i` takes its x, y and z from bits 0, 1 and 2 of `i` respectively.
+const BOX_FACES: [[usize; 4]; 6] = [
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+];
+
+/// Builds a counter-clockwise square from its lower left corner and size.
+pub fn square(corner: Vec2, size: f32) -> Vec<Vec2> {
+    vec![
+        corner,
+        corner + Vec2::new(size, 0.0),
+        corner + Vec2::splat(size),
+        corner + Vec2::new(0.0, size),
+    ]
+}
+
+/// Builds the 12 outward-facing triangles of an axis-aligned box.
+pub fn box_triangles(min: Vec3, max: Vec3) -> Vec<[Vec3; 3]> {
+    let corner = |i: usize| {
+        Vec3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    };
+
+    BOX_FACES
+        .iter()
+        .flat_map(|quad| {
+            [
+                [corner(quad[0]), corner(quad[1]), corner(quad[2])],
+                [corner(quad[0]), corner(quad[2]), corner(quad[3])],
+            ]
+        })
+        .collect()
+}
+
+/// Builds an indexed mesh of an axis-aligned box.
+pub fn box_mesh(min: Vec3, max: Vec3) -> Mesh {
+    Mesh::from_triangles(&box_triangles(min, max))
+}
+
+/// Builds the triangles of a hollow cylinder standing on the XZ plane, centered on the Y axis.
+///
+/// Each wall is approximated by `sides` quads and every triangle is wound to face out of the solid.
+pub fn tube_triangles(
+    inner_radius: f32,
+    outer_radius: f32,
+    height: f32,
+    sides: usize,
+) -> Vec<[Vec3; 3]> {
+    let point = |radius: f32, side: usize, y: f32| {
+        let angle = std::f32::consts::TAU * (side % sides) as f32 / sides as f32;
+        Vec3::new(radius * angle.cos(), y, radius * angle.sin())
+    };
+
+    let mut triangles = Vec::new();
+    for side in 0..sides {
+        let quads = [
+            // Outer wall, facing away from the axis.
+            [
+                point(outer_radius, side, 0.0),
+                point(outer_radius, side, height),
+                point(outer_radius, side + 1, height),
+                point(outer_radius, side + 1, 0.0),
+            ],
+            // Inner wall, facing the axis.
+            [
+                point(inner_radius, side, 0.0),
+                point(inner_radius, side + 1, 0.0),
+                point(inner_radius, side + 1, height),
+                point(inner_radius, side, height),
+            ],
+            // Top ring, facing up.
+            [
+                point(inner_radius, side, height),
+                point(inner_radius, side + 1, height),
+                point(outer_radius, side + 1, height),
+                point(outer_radius, side, height),
+            ],
+            // Bottom ring, facing down.
+            [
+                point(inner_radius, side, 0.0),
+                point(outer_radius, side, 0.0),
+                point(outer_radius, side + 1, 0.0),
+                point(inner_radius, side + 1, 0.0),
+            ],
+        ];
+        for quad in quads {
+            triangles.push([quad[0], quad[1], quad[2]]);
+            triangles.push([quad[0], quad[2], quad[3]]);
+        }
+    }
+    triangles
+}
+
+/// Builds the triangles of a closed cone standing on the XZ plane, centered on the Y axis.
+pub fn cone_triangles(radius: f32, height: f32, sides: usize) -> Vec<[Vec3; 3]> {
+    let point = |side: usize| {
+        let angle = std::f32::consts::TAU * (side % sides) as f32 / sides as f32;
+        Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin())
+    };
+    let apex = Vec3::new(0.0, height, 0.0);
+
+    (0..sides)
+        .flat_map(|side| {
+            [
+                [point(side), apex, point(side + 1)],
+                [point(side), point(side + 1), Vec3::ZERO],
+            ]
+        })
+        .collect()
+}
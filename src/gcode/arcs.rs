@@ -0,0 +1,188 @@
+use std::f32::consts::PI;
+
+use glam::f32::Vec2;
+
+use crate::slicer::EPSILON;
+
+/// The fewest points, including both ends, worth replacing by an arc.
+const MIN_ARC_POINTS: usize = 4;
+
+/// A move along a 2D path, either straight or along a circular arc.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathMove {
+    /// Moves in a straight line to `to`.
+    Line { to: Vec2 },
+    /// Moves along the circle of center `center` to `to`, clockwise or counter-clockwise.
+    Arc {
+        to: Vec2,
+        center: Vec2,
+        clockwise: bool,
+    },
+}
+
+/// Replaces runs of points lying on a circle with arcs, the "arc welder" of the slicer.
+///
+/// # Remarks
+///
+/// - A run is turned into an arc when it has **at least 4 points**, every point and every segment
+///   midpoint is within `tolerance` of the circle, and the run turns around the center in a single
+///   direction. The arc is **greedily** extended for as long as the run fits.
+/// - Arcs are limited to **half a turn**, so full circles are split and every arc has a well
+///   conditioned center.
+/// - Runs that are straight within `tolerance` stay lines.
+///
+/// # Arguments
+///
+/// * `contour` - The points of the path, starting at the current position. To follow a closed loop,
+///   its first point must be repeated at the end.
+/// * `tolerance` - The largest distance allowed between the path and an arc replacing it.
+///
+/// # Returns
+///
+/// The moves following the path from its first point, which is not part of the moves.
+pub fn fit_arcs(contour: &[Vec2], tolerance: f32) -> Vec<PathMove> {
+    let mut moves = Vec::new();
+    let mut start = 0;
+    while start + 1 < contour.len() {
+        let mut fitted = None;
+        let mut end = start + MIN_ARC_POINTS - 1;
+        while end < contour.len() {
+            match fit_arc(&contour[start..=end], tolerance) {
+                Some(arc) => fitted = Some((end, arc)),
+                None => break,
+            }
+            end += 1;
+        }
+
+        match fitted.filter(|&(end, _)| !is_straight(&contour[start..=end], tolerance)) {
+            Some((end, (center, clockwise))) => {
+                moves.push(PathMove::Arc {
+                    to: contour[end],
+                    center,
+                    clockwise,
+                });
+                start = end;
+            }
+            None => {
+                moves.push(PathMove::Line {
+                    to: contour[start + 1],
+                });
+                start += 1;
+            }
+        }
+    }
+
+    moves
+}
+
+/// Fits a single arc through all the points of a run, returning its center and direction.
+fn fit_arc(points: &[Vec2], tolerance: f32) -> Option<(Vec2, bool)> {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let center = circumcenter(first, points[points.len() / 2], last)?;
+    let radius = first.distance(center);
+    let clockwise = (first - center).perp_dot(points[1] - center) < 0.0;
+
+    let mut sweep = 0.0;
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0] - center, pair[1] - center);
+        let turn = from.perp_dot(to).atan2(from.dot(to));
+        if turn == 0.0 || (turn < 0.0) != clockwise {
+            return None;
+        }
+        sweep += turn.abs();
+
+        let midpoint = (pair[0] + pair[1]) / 2.0;
+        if (to.length() - radius).abs() > tolerance
+            || (midpoint.distance(center) - radius).abs() > tolerance
+        {
+            return None;
+        }
+    }
+
+    (sweep <= PI + EPSILON).then_some((center, clockwise))
+}
+
+/// Returns whether all the points of a run are within `tolerance` of the chord joining its ends.
+fn is_straight(points: &[Vec2], tolerance: f32) -> bool {
+    let first = points[0];
+    let chord = points[points.len() - 1] - first;
+    chord.length() > EPSILON
+        && points
+            .iter()
+            .all(|&p| chord.perp_dot(p - first).abs() <= tolerance * chord.length())
+}
+
+/// Computes the center of the circle passing through three points, if they are not collinear.
+fn circumcenter(a: Vec2, b: Vec2, c: Vec2) -> Option<Vec2> {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * ab.perp_dot(ac);
+    if d.abs() <= EPSILON {
+        return None;
+    }
+    let offset = Vec2::new(
+        ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+        ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+    );
+    Some(a + offset / d)
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Samples a circle of radius 10 centered at (5, 5), repeating the first point at the end.
+    #[allow(dead_code)]
+    fn circle(sides: usize, clockwise: bool) -> Vec<Vec2> {
+        let sign = if clockwise { -1.0 } else { 1.0 };
+        (0..=sides)
+            .map(|i| {
+                let angle = sign * 2.0 * PI * i as f32 / sides as f32;
+                Vec2::new(5.0, 5.0) + 10.0 * Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect()
+    }
+
+    /// Test for the `fit_arcs` function collapsing a sampled circle into a few arcs.
+    #[test]
+    fn test_fit_arcs_circle() {
+        for clockwise in [false, true] {
+            let points = circle(64, clockwise);
+            let moves = fit_arcs(&points, 0.05);
+            assert!(moves.len() <= 3);
+
+            for m in &moves {
+                let PathMove::Arc {
+                    center,
+                    clockwise: direction,
+                    ..
+                } = *m
+                else {
+                    panic!("expected an arc, got {m:?}");
+                };
+                assert!(center.abs_diff_eq(Vec2::splat(5.0), 1e-3));
+                assert_eq!(direction, clockwise);
+            }
+            let PathMove::Arc { to, .. } = moves[moves.len() - 1] else {
+                unreachable!();
+            };
+            assert!(to.abs_diff_eq(points[0], 1e-4));
+        }
+    }
+
+    /// Test for the `fit_arcs` function keeping the corners of a polygon as lines.
+    #[test]
+    fn test_fit_arcs_square() {
+        let square = [
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::ZERO,
+        ];
+        let moves = fit_arcs(&square, 0.05);
+        assert_eq!(moves.len(), 5);
+        assert!(moves.iter().all(|m| matches!(m, PathMove::Line { .. })));
+        assert_eq!(moves[4], PathMove::Line { to: Vec2::ZERO });
+    }
+}
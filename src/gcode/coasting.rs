@@ -0,0 +1,157 @@
+use glam::f32::Vec3;
+
+use super::Move;
+use crate::slicer::EPSILON;
+
+/// Stops extruding for the last stretch of every closed perimeter, letting the pressure left in the
+/// nozzle finish the loop instead of leaving a blob at the seam.
+///
+/// # Remarks
+///
+/// - A perimeter is a travel followed by extrusions, ending within `EPSILON` of where the travel
+///   arrived. Its last `coast_distance` millimeters are turned into extrusions of **zero** filament,
+///   the move crossing the boundary being split in two.
+/// - Perimeters not longer than `coast_distance` are **skipped**, as are those whose last stretch
+///   contains an arc, which cannot be split. Open paths are left untouched.
+///
+/// # Arguments
+///
+/// * `moves` - The moves of the print, as generated by `GcodeWriter::moves`.
+/// * `coast_distance` - The length of the end of each perimeter printed without extruding.
+pub fn apply_coasting(moves: &mut Vec<Move>, coast_distance: f32) {
+    if coast_distance <= 0.0 {
+        return;
+    }
+
+    let mut coasted = Vec::with_capacity(moves.len());
+    let mut start: Option<Vec3> = None;
+    let mut i = 0;
+    while i < moves.len() {
+        let end = (i..moves.len())
+            .find(|&j| !matches!(moves[j], Move::Extrude { .. } | Move::Arc { .. }))
+            .unwrap_or(moves.len());
+        if end == i {
+            if let Move::Travel { to } = moves[i] {
+                start = Some(to);
+            }
+            coasted.push(moves[i]);
+            i += 1;
+            continue;
+        }
+
+        let run = &moves[i..end];
+        match start.take() {
+            Some(from) => coasted.extend(coast_run(from, run, coast_distance)),
+            None => coasted.extend_from_slice(run),
+        }
+        i = end;
+    }
+
+    *moves = coasted;
+}
+
+/// Coasts the end of a run of extrusions starting at `from`, or returns it unchanged when it is not a
+/// closed perimeter long enough to coast.
+fn coast_run(from: Vec3, run: &[Move], coast_distance: f32) -> Vec<Move> {
+    let target = |m: &Move| match *m {
+        Move::Extrude { to, .. } | Move::Arc { to, .. } => to,
+        _ => unreachable!("a run only holds extrusions"),
+    };
+    let last = target(&run[run.len() - 1]);
+    if !from.abs_diff_eq(last, EPSILON) {
+        return run.to_vec();
+    }
+
+    // Walks back from the end until the coasted stretch is covered.
+    let mut left = coast_distance;
+    for k in (0..run.len()).rev() {
+        let Move::Extrude { to, e, feature } = run[k] else {
+            return run.to_vec();
+        };
+        let previous = if k == 0 { from } else { target(&run[k - 1]) };
+        let length = previous.distance(to);
+        if length < left {
+            left -= length;
+            continue;
+        }
+
+        let mut coasted = run[..k].to_vec();
+        let split = previous + (to - previous) * ((length - left) / length);
+        coasted.push(Move::Extrude {
+            to: split,
+            e: e * (length - left) / length,
+            feature,
+        });
+        coasted.push(Move::Extrude {
+            to,
+            e: 0.0,
+            feature,
+        });
+        // The moves after the split are plain extrusions, an arc would have stopped the walk.
+        coasted.extend(run[k + 1..].iter().map(|&m| match m {
+            Move::Extrude { to, feature, .. } => Move::Extrude {
+                to,
+                e: 0.0,
+                feature,
+            },
+            _ => m,
+        }));
+        return coasted;
+    }
+
+    // The whole perimeter is shorter than the coasted stretch.
+    run.to_vec()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::gcode::{Feature, GcodeLayer, GcodeWriter};
+    #[allow(unused_imports)]
+    use glam::f32::Vec2;
+
+    /// Test for the `apply_coasting` function on a square perimeter and a tiny one.
+    #[test]
+    fn test_apply_coasting_square() {
+        let layer = GcodeLayer::from_shells(
+            0.2,
+            0.2,
+            vec![(0, crate::fixtures::square(Vec2::ZERO, 10.0))],
+        );
+        let writer = GcodeWriter::default();
+        let original = writer.moves(&[layer]);
+        let mut moves = original.clone();
+        apply_coasting(&mut moves, 3.0);
+
+        // The last side is split 3mm before the end, which is reached without extruding.
+        assert_eq!(moves.len(), original.len() + 1);
+        let [.., split, last] = moves[..] else {
+            panic!("the perimeter has no moves");
+        };
+        assert_eq!(
+            last,
+            Move::Extrude {
+                to: Vec3::new(0.0, 0.0, 0.2),
+                e: 0.0,
+                feature: Feature::OuterPerimeter,
+            }
+        );
+        let Move::Extrude { to, e, .. } = split else {
+            panic!("the split is an extrusion");
+        };
+        assert!(to.abs_diff_eq(Vec3::new(0.0, 3.0, 0.2), 1e-5));
+        assert!((e - writer.extrusion_length(7.0, 0.2)).abs() < 1e-6);
+
+        // A perimeter shorter than the coasted stretch is left alone.
+        let tiny = GcodeLayer::from_shells(
+            0.2,
+            0.2,
+            vec![(0, crate::fixtures::square(Vec2::ZERO, 0.5))],
+        );
+        let original = writer.moves(&[tiny]);
+        let mut moves = original.clone();
+        apply_coasting(&mut moves, 3.0);
+        assert_eq!(moves, original);
+    }
+}
@@ -0,0 +1,278 @@
+use super::Move;
+use crate::estimate::{estimate_print, layer_starts};
+use crate::machine::MachineConfig;
+
+/// The settings of `plan_cooling`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoolingConfig {
+    /// The fan speed of the layers taking at least `fan_threshold`, from 0 (off) to 1 (full speed).
+    pub min_fan_speed: f32,
+    /// The fan speed the ramp reaches for a layer taking no time at all, from 0 to 1.
+    pub max_fan_speed: f32,
+    /// The layer time below which the fan speeds up, in seconds.
+    pub fan_threshold: f32,
+    /// The shortest time a layer may take, slower layers being slowed down to it, in seconds. 0
+    /// disables the slowdown.
+    pub min_layer_time: f32,
+    /// Whether the fan runs during the first layer. A warm first layer sticks better to the bed.
+    pub first_layer_fan: bool,
+}
+
+impl Default for CoolingConfig {
+    fn default() -> Self {
+        CoolingConfig {
+            min_fan_speed: 0.35,
+            max_fan_speed: 1.0,
+            fan_threshold: 60.0,
+            min_layer_time: 5.0,
+            first_layer_fan: false,
+        }
+    }
+}
+
+impl CoolingConfig {
+    /// Returns the fan speed of a layer printed in `layer_time` seconds.
+    ///
+    /// # Remarks
+    ///
+    /// - The speed ramps **linearly** from `max_fan_speed` for an instant layer to `min_fan_speed` at
+    ///   `fan_threshold`, and stays at `min_fan_speed` for longer layers.
+    pub fn fan_speed(&self, layer_time: f32) -> f32 {
+        if self.fan_threshold <= 0.0 || layer_time >= self.fan_threshold {
+            return self.min_fan_speed;
+        }
+        let t = layer_time.max(0.0) / self.fan_threshold;
+        self.max_fan_speed + (self.min_fan_speed - self.max_fan_speed) * t
+    }
+}
+
+/// Inserts the fan and speed commands cooling each layer according to how long it takes to print.
+///
+/// # Remarks
+///
+/// - The time of each layer is estimated with `estimate_print`. Layers are told apart by the
+///   height of their extrusions, so the travels of a Z hop and the climb of a spiral vase do not
+///   split them.
+/// - Each layer starts with a `Move::Fan` setting the speed of `CoolingConfig::fan_speed`, only
+///   when it differs from the previous one. The fan starts **off**, and stays off during the first
+///   layer unless `first_layer_fan`.
+/// - A layer faster than `min_layer_time` also starts with a `Move::SpeedFactor` slowing it down
+///   to that time, and the next layer restores the full speed.
+/// - Any `Fan` and `SpeedFactor` already in `moves` is **replaced** by the plan.
+///
+/// # Arguments
+///
+/// * `moves` - The moves of the print, as generated by `GcodeWriter::moves`.
+/// * `config` - The cooling settings.
+/// * `machine` - The limits of the printer, used to estimate the layer times.
+pub fn plan_cooling(moves: &mut Vec<Move>, config: &CoolingConfig, machine: &MachineConfig) {
+    moves.retain(|m| !matches!(m, Move::Fan { .. } | Move::SpeedFactor { .. }));
+    let starts = layer_starts(moves);
+    let times: Vec<f32> = estimate_print(moves, machine)
+        .layers
+        .iter()
+        .map(|layer| layer.time)
+        .collect();
+
+    let mut planned = Vec::with_capacity(moves.len() + 2 * times.len());
+    let (mut fan, mut factor) = (0.0, 1.0);
+    let mut layer = 0;
+    for (i, &m) in moves.iter().enumerate() {
+        if starts.get(layer) == Some(&i) {
+            let time = times.get(layer).copied().unwrap_or(0.0);
+            let speed = if layer == 0 && !config.first_layer_fan {
+                0.0
+            } else {
+                config.fan_speed(time)
+            };
+            if speed != fan {
+                planned.push(Move::Fan { speed });
+                fan = speed;
+            }
+            let slowdown = if time > 0.0 && time < config.min_layer_time {
+                time / config.min_layer_time
+            } else {
+                1.0
+            };
+            if slowdown != factor {
+                planned.push(Move::SpeedFactor { factor: slowdown });
+                factor = slowdown;
+            }
+            layer += 1;
+        }
+        planned.push(m);
+    }
+
+    *moves = planned;
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::gcode::{plan_retractions, GcodeLayer, GcodeWriter, RetractionConfig};
+    #[allow(unused_imports)]
+    use glam::f32::Vec2;
+
+    /// Test for the `plan_cooling` function speeding up the fan on a short layer.
+    #[test]
+    fn test_plan_cooling_short_layer() {
+        let layers: Vec<GcodeLayer> = [100.0, 100.0, 100.0, 5.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| {
+                GcodeLayer::from_shells(
+                    0.2 * (i + 1) as f32,
+                    0.2,
+                    vec![(0, crate::fixtures::square(Vec2::ZERO, size))],
+                )
+            })
+            .collect();
+        let mut moves = GcodeWriter::default().moves(&layers);
+        let config = CoolingConfig {
+            fan_threshold: 8.0,
+            ..CoolingConfig::default()
+        };
+        plan_cooling(&mut moves, &config, &MachineConfig::default());
+
+        let fans: Vec<f32> = moves
+            .iter()
+            .filter_map(|m| match m {
+                Move::Fan { speed } => Some(*speed),
+                _ => None,
+            })
+            .collect();
+        // Off on the first layer, the minimum on the long layers and faster on the short one.
+        assert_eq!(fans.len(), 2);
+        assert_eq!(fans[0], config.min_fan_speed);
+        assert!(fans[1] > fans[0]);
+        assert!(fans[1] <= config.max_fan_speed);
+        let first_fan = moves
+            .iter()
+            .position(|m| matches!(m, Move::Fan { .. }))
+            .unwrap();
+        assert!(moves[..first_fan].iter().all(|m| match m {
+            Move::Travel { to } | Move::Extrude { to, .. } => to.z == 0.2,
+            _ => true,
+        }));
+
+        // The short layer is slowed down to the minimum layer time.
+        let factors: Vec<f32> = moves
+            .iter()
+            .filter_map(|m| match m {
+                Move::SpeedFactor { factor } => Some(*factor),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(factors.len(), 1);
+        assert!(factors[0] > 0.0 && factors[0] < 1.0);
+
+        // Planning again replaces the previous plan.
+        let planned = moves.clone();
+        plan_cooling(&mut moves, &config, &MachineConfig::default());
+        assert_eq!(moves, planned);
+    }
+
+    /// Test for the `plan_cooling` function ignoring the travels of a Z hop.
+    #[test]
+    fn test_plan_cooling_z_hop() {
+        // Two parts far apart, so every layer has a retracted travel between them.
+        let layers: Vec<GcodeLayer> = (1..=4)
+            .map(|i| {
+                let mut layer = GcodeLayer::from_shells(
+                    0.2 * i as f32,
+                    0.2,
+                    vec![
+                        (0, crate::fixtures::square(Vec2::ZERO, 5.0)),
+                        (0, crate::fixtures::square(Vec2::new(50.0, 0.0), 5.0)),
+                    ],
+                );
+                layer.tools = Vec::new();
+                layer
+            })
+            .collect();
+        let config = CoolingConfig {
+            fan_threshold: 30.0,
+            ..CoolingConfig::default()
+        };
+        let plan = |z_hop: f32| {
+            let mut moves = GcodeWriter::default().moves(&layers);
+            let retraction = RetractionConfig {
+                z_hop,
+                ..RetractionConfig::default()
+            };
+            plan_retractions(&mut moves, &retraction);
+            plan_cooling(&mut moves, &config, &MachineConfig::default());
+            moves
+        };
+        let commands = |moves: &[Move]| -> Vec<Move> {
+            moves
+                .iter()
+                .filter(|m| matches!(m, Move::Fan { .. } | Move::SpeedFactor { .. }))
+                .copied()
+                .collect()
+        };
+
+        // The hops change the height of the nozzle, but only add their own time to the plan.
+        let flat = plan(0.0);
+        let hopped = plan(0.4);
+        assert!(hopped
+            .iter()
+            .any(|m| matches!(m, Move::Travel { to } if to.z > 1.0)));
+        assert_eq!(commands(&flat).len(), 3);
+        assert_eq!(commands(&hopped).len(), commands(&flat).len());
+        for (hop, no_hop) in commands(&hopped).iter().zip(&commands(&flat)) {
+            match (*hop, *no_hop) {
+                (Move::Fan { speed: a }, Move::Fan { speed: b })
+                | (Move::SpeedFactor { factor: a }, Move::SpeedFactor { factor: b }) => {
+                    assert!((a - b).abs() < 0.05)
+                }
+                _ => panic!("the plans differ: {hop:?} and {no_hop:?}"),
+            }
+        }
+        // The fan stays off until the last extrusion of the first layer.
+        let first_fan = hopped
+            .iter()
+            .position(|m| matches!(m, Move::Fan { .. }))
+            .unwrap();
+        let last_first_layer = hopped
+            .iter()
+            .rposition(|m| matches!(m, Move::Extrude { to, .. } if to.z == 0.2))
+            .unwrap();
+        assert!(first_fan > last_first_layer);
+    }
+
+    /// Test for the `plan_cooling` function on a spiral vase, one layer per loop.
+    #[test]
+    fn test_plan_cooling_spiral() {
+        let square = vec![
+            Vec2::ZERO,
+            Vec2::new(100.0, 0.0),
+            Vec2::splat(100.0),
+            Vec2::new(0.0, 100.0),
+        ];
+        let layers: Vec<GcodeLayer> = (1..=5)
+            .map(|i| GcodeLayer::from_shells(0.2 * i as f32, 0.2, vec![(0, square.clone())]))
+            .collect();
+        let mut moves = GcodeWriter::default().spiralize(&layers).unwrap();
+        assert_eq!(layer_starts(&moves).len(), 5);
+
+        // Each 400mm loop takes 10s, nothing is slowed down and the fan runs at its minimum.
+        let config = CoolingConfig {
+            fan_threshold: 8.0,
+            ..CoolingConfig::default()
+        };
+        plan_cooling(&mut moves, &config, &MachineConfig::default());
+        let commands: Vec<Move> = moves
+            .iter()
+            .filter(|m| matches!(m, Move::Fan { .. } | Move::SpeedFactor { .. }))
+            .copied()
+            .collect();
+        assert_eq!(
+            commands,
+            vec![Move::Fan {
+                speed: config.min_fan_speed
+            }]
+        );
+    }
+}
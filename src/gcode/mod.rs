@@ -0,0 +1,1502 @@
+use std::f32::consts::PI;
+use std::fmt::{self, Write};
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::bridge::is_supported;
+use crate::contour::build_contours;
+use crate::geometry::to_planar;
+use crate::perimeter::{apply_fuzzy_skin, FuzzySkin, WidePath};
+use crate::seam::{aligned_reference, place_seam, SeamPlacement};
+use crate::slicer::{Layer, PlateLayer, EPSILON};
+
+mod arcs;
+mod coasting;
+mod cooling;
+mod retraction;
+
+pub use arcs::{fit_arcs, PathMove};
+pub use coasting::apply_coasting;
+pub use cooling::{plan_cooling, CoolingConfig};
+pub use retraction::{plan_retractions, RetractionConfig};
+
+/// The extruder position, in millimeters of filament, beyond which absolute extrusion is reset to
+/// zero at the next layer change, before the `E` values lose precision.
+const ABSOLUTE_E_RESET: f32 = 1000.0;
+
+/// Errors that can occur while generating the moves of a print.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GcodeError {
+    /// Spiral vase mode needs exactly one closed loop per layer, this layer has `count`.
+    ContourCount { layer: usize, count: usize },
+}
+
+impl fmt::Display for GcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcodeError::ContourCount { layer, count } => write!(
+                f,
+                "layer {layer} has {count} contours, spiral vase mode needs exactly one"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GcodeError {}
+
+/// How the `E` values of the G-code are expressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtrusionMode {
+    /// `M82`: each `E` is the position of the extruder, accumulating the filament of every move.
+    #[default]
+    Absolute,
+    /// `M83`: each `E` is the filament pushed by its own move.
+    Relative,
+}
+
+impl ExtrusionMode {
+    /// Pushes `amount` millimeters of filament from the extruder position `e`, and returns the `E`
+    /// value of the move.
+    fn advance(self, e: &mut f32, amount: f32) -> f32 {
+        *e += amount;
+        match self {
+            ExtrusionMode::Absolute => *e,
+            ExtrusionMode::Relative => amount,
+        }
+    }
+}
+
+/// The G-code dialect of the firmware running the printer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GcodeFlavor {
+    /// Marlin and its forks, waiting for the nozzle with `M109`.
+    #[default]
+    Marlin,
+    /// The Prusa firmware, a Marlin fork speaking the same dialect. The header checks the nozzle
+    /// diameter of the printer with `M862.1`, so a print sliced for another nozzle is flagged.
+    Prusa,
+    /// Klipper, heating with its own extended commands. Arcs need the optional `[gcode_arcs]`
+    /// section of its configuration, so they are not emitted.
+    Klipper,
+}
+
+impl GcodeFlavor {
+    /// Returns the name of the flavor, as written in the header of the G-code.
+    pub fn name(self) -> &'static str {
+        match self {
+            GcodeFlavor::Marlin => "marlin",
+            GcodeFlavor::Prusa => "prusa",
+            GcodeFlavor::Klipper => "klipper",
+        }
+    }
+
+    /// Returns whether the firmware accepts `G2` and `G3` arcs out of the box.
+    pub fn supports_arcs(self) -> bool {
+        !matches!(self, GcodeFlavor::Klipper)
+    }
+}
+
+/// The kind of line an extruding move prints, deciding its speed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Feature {
+    /// The outer wall, visible on the surface of the print.
+    #[default]
+    OuterPerimeter,
+    /// A wall hidden inside the part.
+    InnerPerimeter,
+    /// The lines filling the inside of the part.
+    Infill,
+    /// A stretch of wall printed over the air, not supported by the layer below.
+    Overhang,
+}
+
+/// The speeds of the moves of a print, in millimeters per second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedConfig {
+    /// The speed of the outer walls, slower for a clean surface.
+    pub outer_perimeter: f32,
+    /// The speed of the inner walls.
+    pub inner_perimeter: f32,
+    /// The speed of the infill.
+    pub infill: f32,
+    /// The speed of the walls over overhangs, slower so they have time to cool and set.
+    pub overhang: f32,
+    /// The speed of travel moves.
+    pub travel: f32,
+    /// The speed of every extruding move of the first layer, whatever its feature, so it sticks to
+    /// the bed.
+    pub first_layer: f32,
+}
+
+impl Default for SpeedConfig {
+    fn default() -> Self {
+        SpeedConfig {
+            outer_perimeter: 30.0,
+            inner_perimeter: 40.0,
+            infill: 60.0,
+            overhang: 15.0,
+            travel: 120.0,
+            first_layer: 20.0,
+        }
+    }
+}
+
+impl SpeedConfig {
+    /// Returns the speed of an extruding move of `feature`, on the first layer or above.
+    pub fn extrusion(&self, feature: Feature, first_layer: bool) -> f32 {
+        if first_layer {
+            return self.first_layer;
+        }
+        match feature {
+            Feature::OuterPerimeter => self.outer_perimeter,
+            Feature::InnerPerimeter => self.inner_perimeter,
+            Feature::Infill => self.infill,
+            Feature::Overhang => self.overhang,
+        }
+    }
+}
+
+/// A single machine move, in machine coordinates: X and Y on the bed and Z pointing up.
+///
+/// The slicer works with Y as the build axis, so a point `(x, y, z)` of a layer maps to the machine
+/// position `(x, z, y)`: the 2D frame of a layer is the XY plane of the bed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Move {
+    /// Moves the nozzle without extruding.
+    Travel { to: Vec3 },
+    /// Moves the nozzle in a straight line while pushing `e` millimeters of filament.
+    Extrude { to: Vec3, e: f32, feature: Feature },
+    /// Moves the nozzle along a circular arc around `center`, on the XY plane, while pushing `e`
+    /// millimeters of filament.
+    Arc {
+        to: Vec3,
+        center: Vec2,
+        clockwise: bool,
+        e: f32,
+        feature: Feature,
+    },
+    /// Pulls `length` millimeters of filament back to relieve the nozzle pressure before a travel.
+    Retract { length: f32 },
+    /// Pushes back `length` millimeters of filament after a travel.
+    Unretract { length: f32 },
+    /// Switches to the extruder `tool` and pushes `purge` millimeters of its filament to prime it.
+    ToolChange { tool: usize, purge: f32 },
+    /// Sets the speed of the part cooling fan, from 0 (off) to 1 (full speed).
+    Fan { speed: f32 },
+    /// Scales the speed of every following move by `factor`, 1 being the configured speeds.
+    SpeedFactor { factor: f32 },
+}
+
+/// The closed perimeters of a layer, ready to be turned into moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeLayer {
+    /// The height of the nozzle while printing the layer, i.e. the top of the layer.
+    pub z: f32,
+    /// The thickness of the layer.
+    pub thickness: f32,
+    /// The closed loops to print, in printing order, without repeating their first point.
+    pub perimeters: Vec<Vec<Vec2>>,
+    /// The index of the wall of each perimeter, 0 for an outer wall, see `generate_ordered_shells`.
+    /// Perimeters without an index are outer walls.
+    pub walls: Vec<usize>,
+    /// The tool printing each perimeter. Perimeters without a tool are printed by tool 0.
+    pub tools: Vec<usize>,
+    /// The regions of the layer below, see `Layer::regions`, to find the walls printed over the air.
+    /// Without them every wall is considered supported.
+    pub below: Option<Vec<Vec<Vec2>>>,
+    /// The open lines of varying width to print after the perimeters, see `variable_width`.
+    pub wide_paths: Vec<WidePath>,
+}
+
+impl GcodeLayer {
+    /// Assembles the segments of a sliced layer into the loops to print.
+    ///
+    /// # Remarks
+    ///
+    /// - Open polylines cannot be printed as perimeters and are **dropped**.
+    /// - The nozzle height is the **top** of the layer, half a thickness above its slicing plane.
+    pub fn from_layer(layer: &Layer) -> Self {
+        let perimeters: Vec<Vec<Vec2>> = build_contours(&layer.segments, EPSILON, EPSILON)
+            .loops()
+            .map(|contour| {
+                contour[..contour.len() - 1]
+                    .iter()
+                    .map(|&p| to_planar(p))
+                    .collect()
+            })
+            .collect();
+
+        GcodeLayer {
+            z: layer.height + layer.thickness / 2.0,
+            thickness: layer.thickness,
+            walls: vec![0; perimeters.len()],
+            tools: vec![0; perimeters.len()],
+            perimeters,
+            below: None,
+            wide_paths: Vec::new(),
+        }
+    }
+
+    /// Assembles the contours of every object of a plate layer into the loops to print.
+    ///
+    /// # Remarks
+    ///
+    /// - Each loop is printed by the tool of its object, tool 0 for objects without one.
+    /// - The nozzle height is the **top** of the layer, half a thickness above its slicing plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The layer of the plate, see `slice_plate`.
+    /// * `object_tools` - The tool of each object, in the order of the meshes of the plate.
+    pub fn from_plate_layer(layer: &PlateLayer, object_tools: &[usize]) -> Self {
+        let perimeters: Vec<Vec<Vec2>> = layer
+            .contours
+            .iter()
+            .map(|contour| {
+                contour.points[..contour.points.len() - 1]
+                    .iter()
+                    .map(|&p| to_planar(p))
+                    .collect()
+            })
+            .collect();
+        let tools = layer
+            .contours
+            .iter()
+            .map(|contour| object_tools.get(contour.object).copied().unwrap_or(0))
+            .collect();
+
+        GcodeLayer {
+            z: layer.height + layer.thickness / 2.0,
+            thickness: layer.thickness,
+            walls: vec![0; perimeters.len()],
+            tools,
+            perimeters,
+            below: None,
+            wide_paths: Vec::new(),
+        }
+    }
+
+    /// Builds a layer printing the walls of `generate_ordered_shells` in their order.
+    ///
+    /// # Arguments
+    ///
+    /// * `z` - The height of the nozzle while printing the layer.
+    /// * `thickness` - The thickness of the layer.
+    /// * `shells` - The index of the wall and the loop of every wall loop, in printing order.
+    pub fn from_shells(z: f32, thickness: f32, shells: Vec<(usize, Vec<Vec2>)>) -> Self {
+        let (walls, perimeters): (Vec<usize>, Vec<Vec<Vec2>>) = shells.into_iter().unzip();
+        GcodeLayer {
+            z,
+            thickness,
+            tools: vec![0; perimeters.len()],
+            perimeters,
+            walls,
+            below: None,
+            wide_paths: Vec::new(),
+        }
+    }
+}
+
+/// Generates the moves and G-code text of a print.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeWriter {
+    /// The diameter of the nozzle in millimeters, used as the width of the extruded lines.
+    pub nozzle_diameter: f32,
+    /// The diameter of the filament in millimeters.
+    pub filament_diameter: f32,
+    /// The speed of each kind of move.
+    pub speeds: SpeedConfig,
+    /// The factor applied to the theoretical amount of filament, to tune the flow of a material.
+    pub extrusion_multiplier: f32,
+    /// The length of filament pulled back before each travel, in millimeters.
+    pub retraction_length: f32,
+    /// The speed of retractions in millimeters per second.
+    pub retraction_feedrate: f32,
+    /// Where each perimeter loop starts, see `place_seam`.
+    pub seam: SeamPlacement,
+    /// The fuzzy skin applied to every perimeter, if any, see `apply_fuzzy_skin`.
+    pub fuzzy_skin: Option<FuzzySkin>,
+    /// The tolerance of the arcs fitted to the perimeters, see `fit_arcs`. Without it perimeters are
+    /// printed as straight lines only.
+    pub arc_tolerance: Option<f32>,
+    /// The length of filament pushed to prime an extruder after a tool change, in millimeters.
+    pub purge_length: f32,
+    /// Whether the `E` values are absolute or relative to each move.
+    pub extrusion_mode: ExtrusionMode,
+    /// The dialect of the firmware, deciding the header, the temperature commands and the arcs.
+    pub flavor: GcodeFlavor,
+    /// The temperature the nozzle is heated to before the print, in degrees Celsius. Without it the
+    /// G-code leaves the temperature to the start script of the printer.
+    pub nozzle_temperature: Option<f32>,
+}
+
+impl Default for GcodeWriter {
+    fn default() -> Self {
+        GcodeWriter {
+            nozzle_diameter: 0.4,
+            filament_diameter: 1.75,
+            speeds: SpeedConfig::default(),
+            extrusion_multiplier: 1.0,
+            retraction_length: 1.0,
+            retraction_feedrate: 35.0,
+            seam: SeamPlacement::Nearest,
+            fuzzy_skin: None,
+            arc_tolerance: None,
+            purge_length: 5.0,
+            extrusion_mode: ExtrusionMode::Absolute,
+            flavor: GcodeFlavor::Marlin,
+            nozzle_temperature: None,
+        }
+    }
+}
+
+impl GcodeWriter {
+    /// Computes the length of filament needed to extrude a line.
+    ///
+    /// # Remarks
+    ///
+    /// - The extruded line is modelled as a **rectangle** as wide as the nozzle and as high as the
+    ///   layer, and the filament as a cylinder: the filament length is the line volume divided by the
+    ///   filament cross-section, scaled by the extrusion multiplier.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the line in millimeters.
+    /// * `thickness` - The thickness of the layer in millimeters.
+    pub fn extrusion_length(&self, length: f32, thickness: f32) -> f32 {
+        self.extrusion_length_at_width(length, thickness, self.nozzle_diameter)
+    }
+
+    /// Computes the length of filament needed to extrude a line of a given width, like
+    /// `extrusion_length` for a line narrower or wider than the nozzle.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the line in millimeters.
+    /// * `thickness` - The thickness of the layer in millimeters.
+    /// * `width` - The width of the line in millimeters.
+    pub fn extrusion_length_at_width(&self, length: f32, thickness: f32, width: f32) -> f32 {
+        let volume = length * width * thickness;
+        let filament_area = PI * (self.filament_diameter / 2.0).powi(2);
+        volume / filament_area * self.extrusion_multiplier
+    }
+
+    /// Turns the perimeters of each layer into machine moves.
+    ///
+    /// # Remarks
+    ///
+    /// - Each loop is reached with a **travel**, wrapped in a retraction when the nozzle has already
+    ///   extruded, then printed point by point back to its start.
+    /// - Each outer wall starts at its seam, chosen by `seam` from the position of the nozzle after
+    ///   the previous loop. `SeamPlacement::Random` is seeded with the index of the layer. Inner
+    ///   walls, hidden inside the part, start at the vertex **nearest** to the nozzle instead.
+    /// - With `SeamPlacement::Aligned`, each outer wall lines up with the closest seam of the layer
+    ///   below, see `aligned_reference`. Walls with nothing to line up with, on the first layer or
+    ///   when no outer wall was printed below, fall back to `SeamPlacement::Rear`.
+    /// - With `fuzzy_skin`, each outer wall is fuzzed before its seam is placed, seeded with the
+    ///   indices of the layer and of the loop. The first layer is skipped unless
+    ///   `FuzzySkin::first_layer`.
+    /// - With `arc_tolerance`, curved runs of each loop are printed as **arcs**, see `fit_arcs`.
+    ///   Flavors without arcs, see `GcodeFlavor::supports_arcs`, ignore it.
+    /// - With `GcodeLayer::below`, each line and arc whose middle is not supported by the layer
+    ///   below, see `is_supported`, is tagged as `Feature::Overhang` to be printed slower.
+    /// - The print starts with tool 0. The loops of a layer are **grouped by tool**, the active tool
+    ///   first and then the others by index, keeping their order within a tool. Before the first
+    ///   loop of another tool the old one retracts and a `Move::ToolChange` primes the new one.
+    /// - The `wide_paths` of a layer are printed after its loops by the active tool, as inner walls,
+    ///   each from its end nearest to the nozzle. The filament of each line follows the **average
+    ///   width** of its ends, see `extrusion_length_at_width`.
+    /// - Layers are expected in printing order, from bottom to top.
+    ///
+    /// # Arguments
+    ///
+    /// * `layers` - The layers to print.
+    ///
+    /// # Returns
+    ///
+    /// The moves of the whole print.
+    pub fn moves(&self, layers: &[GcodeLayer]) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut extruded = false;
+        let mut position = Vec2::ZERO;
+        let mut active_tool = 0;
+        let mut seams: Vec<Vec2> = Vec::new();
+
+        for (index, layer) in layers.iter().enumerate() {
+            let seams_below = std::mem::take(&mut seams);
+            let fuzzy_skin = self
+                .fuzzy_skin
+                .filter(|fuzzy| index > 0 || fuzzy.first_layer);
+            let tool_of = |number: usize| layer.tools.get(number).copied().unwrap_or(0);
+            let mut order: Vec<usize> = (0..layer.perimeters.len()).collect();
+            order.sort_by_key(|&number| (tool_of(number) != active_tool, tool_of(number)));
+
+            for number in order {
+                let perimeter = &layer.perimeters[number];
+                if perimeter.len() <= 2 {
+                    continue;
+                }
+                let to_machine = |p: Vec2| Vec3::new(p.x, p.y, layer.z);
+                let outer = layer.walls.get(number).is_none_or(|&wall| wall == 0);
+                let mut perimeter = perimeter.clone();
+                if let Some(fuzzy) = fuzzy_skin.filter(|_| outer) {
+                    let seed = (index as u64) << 32 | number as u64;
+                    apply_fuzzy_skin(&mut perimeter, fuzzy.thickness, fuzzy.point_distance, seed);
+                }
+                let (placement, feature) = if outer {
+                    (self.seam, Feature::OuterPerimeter)
+                } else {
+                    (SeamPlacement::Nearest, Feature::InnerPerimeter)
+                };
+                match placement {
+                    SeamPlacement::Aligned => match aligned_reference(&perimeter, &seams_below) {
+                        Some(seam) => place_seam(&mut perimeter, placement, seam, index as u64),
+                        None => place_seam(&mut perimeter, SeamPlacement::Rear, position, 0),
+                    },
+                    _ => place_seam(&mut perimeter, placement, position, index as u64),
+                }
+                position = perimeter[0];
+                if outer {
+                    seams.push(position);
+                }
+
+                let tool = tool_of(number);
+                let change = tool != active_tool;
+                if extruded {
+                    moves.push(Move::Retract {
+                        length: self.retraction_length,
+                    });
+                }
+                if change {
+                    // The purge primes the new extruder, there is nothing to push back.
+                    moves.push(Move::ToolChange {
+                        tool,
+                        purge: self.purge_length,
+                    });
+                    active_tool = tool;
+                }
+                moves.push(Move::Travel {
+                    to: to_machine(perimeter[0]),
+                });
+                if extruded && !change {
+                    moves.push(Move::Unretract {
+                        length: self.retraction_length,
+                    });
+                }
+
+                let mut path = perimeter.clone();
+                path.push(perimeter[0]);
+                let arc_tolerance = self.arc_tolerance.filter(|_| self.flavor.supports_arcs());
+                let path_moves = match arc_tolerance {
+                    Some(tolerance) => fit_arcs(&path, tolerance),
+                    None => path[1..].iter().map(|&to| PathMove::Line { to }).collect(),
+                };
+                let overhang = |from: Vec2, to: Vec2| match &layer.below {
+                    Some(below) if !is_supported((from + to) / 2.0, below) => Feature::Overhang,
+                    _ => feature,
+                };
+                let mut from = path[0];
+                for path_move in path_moves {
+                    match path_move {
+                        PathMove::Line { to } => {
+                            let e = self.extrusion_length(from.distance(to), layer.thickness);
+                            moves.push(Move::Extrude {
+                                to: to_machine(to),
+                                e,
+                                feature: overhang(from, to),
+                            });
+                            from = to;
+                        }
+                        PathMove::Arc {
+                            to,
+                            center,
+                            clockwise,
+                        } => {
+                            let length = arc_length(from, to, center, clockwise);
+                            moves.push(Move::Arc {
+                                to: to_machine(to),
+                                center,
+                                clockwise,
+                                e: self.extrusion_length(length, layer.thickness),
+                                feature: overhang(from, to),
+                            });
+                            from = to;
+                        }
+                    }
+                }
+                extruded = true;
+            }
+
+            for wide_path in &layer.wide_paths {
+                if wide_path.points.len() < 2 {
+                    continue;
+                }
+                let to_machine = |p: Vec2| Vec3::new(p.x, p.y, layer.z);
+                // Start from the end nearest to the nozzle.
+                let mut path: Vec<(Vec2, f32)> = wide_path
+                    .points
+                    .iter()
+                    .copied()
+                    .zip(wide_path.widths.iter().copied())
+                    .collect();
+                if path[path.len() - 1].0.distance(position) < path[0].0.distance(position) {
+                    path.reverse();
+                }
+
+                if extruded {
+                    moves.push(Move::Retract {
+                        length: self.retraction_length,
+                    });
+                }
+                moves.push(Move::Travel {
+                    to: to_machine(path[0].0),
+                });
+                if extruded {
+                    moves.push(Move::Unretract {
+                        length: self.retraction_length,
+                    });
+                }
+                for pair in path.windows(2) {
+                    let [(from, from_width), (to, to_width)] = [pair[0], pair[1]];
+                    let width = (from_width + to_width) / 2.0;
+                    moves.push(Move::Extrude {
+                        to: to_machine(to),
+                        e: self.extrusion_length_at_width(
+                            from.distance(to),
+                            layer.thickness,
+                            width,
+                        ),
+                        feature: Feature::InnerPerimeter,
+                    });
+                }
+                position = path[path.len() - 1].0;
+                extruded = true;
+            }
+        }
+
+        moves
+    }
+
+    /// Turns the single perimeter of each layer into a continuous spiral, for spiral vase mode.
+    ///
+    /// # Remarks
+    ///
+    /// - The first layer is printed **flat** at its height. Every following loop rises continuously
+    ///   from the height of the previous layer to its own, the height growing in proportion to the
+    ///   distance printed, so the wall has no seam and no Z step.
+    /// - Each loop starts at the point nearest to the end of the previous one, and the hop to that
+    ///   point, if any, is extruded as part of the spiral. There is a single travel, to the start of the print.
+    /// - Each layer must have exactly **one** loop of at least 3 points, a layer with several islands,
+    ///   holes or none cannot be printed as a single wall.
+    ///
+    /// # Arguments
+    ///
+    /// * `layers` - The layers to print, from bottom to top.
+    ///
+    /// # Returns
+    ///
+    /// The moves of the whole print, or a `GcodeError` naming the first layer that is not a single
+    /// loop.
+    pub fn spiralize(&self, layers: &[GcodeLayer]) -> Result<Vec<Move>, GcodeError> {
+        let mut moves = Vec::new();
+        let mut position: Option<Vec3> = None;
+
+        for (index, layer) in layers.iter().enumerate() {
+            let perimeter = match layer.perimeters.as_slice() {
+                [perimeter] if perimeter.len() > 2 => perimeter,
+                perimeters => {
+                    return Err(GcodeError::ContourCount {
+                        layer: index,
+                        count: perimeters.iter().filter(|p| p.len() > 2).count(),
+                    })
+                }
+            };
+            let mut perimeter = perimeter.clone();
+            let start = position.map_or(Vec2::ZERO, |p| p.truncate());
+            place_seam(&mut perimeter, SeamPlacement::Nearest, start, index as u64);
+
+            let mut path = perimeter.clone();
+            path.push(perimeter[0]);
+            let from = match position {
+                Some(from) => from,
+                None => {
+                    let from = perimeter[0].extend(layer.z);
+                    moves.push(Move::Travel { to: from });
+                    path.remove(0);
+                    from
+                }
+            };
+
+            let total: f32 = std::iter::once(from.truncate())
+                .chain(path.iter().copied())
+                .collect::<Vec<_>>()
+                .windows(2)
+                .map(|pair| pair[0].distance(pair[1]))
+                .sum();
+            let mut printed = 0.0;
+            let mut previous = from.truncate();
+            for &p in &path {
+                let length = previous.distance(p);
+                if length <= EPSILON {
+                    continue;
+                }
+                printed += length;
+                let z = if total > EPSILON {
+                    from.z + (layer.z - from.z) * printed / total
+                } else {
+                    layer.z
+                };
+                moves.push(Move::Extrude {
+                    to: p.extend(z),
+                    e: self.extrusion_length(length, layer.thickness),
+                    feature: Feature::OuterPerimeter,
+                });
+                previous = p;
+            }
+            position = Some(previous.extend(layer.z));
+        }
+
+        Ok(moves)
+    }
+
+    /// Serializes machine moves into G-code.
+    ///
+    /// # Remarks
+    ///
+    /// - Positions are **absolute** (`G90`). Extrusion follows `extrusion_mode`: with `M82` the
+    ///   extruder position accumulates the filament pushed by every move, with `M83` each move
+    ///   gives its own amount.
+    /// - When the height of a travel changes, a dedicated `G1 Z` line is emitted before the move.
+    ///   An extrusion or an arc changing the height carries its `Z` on its own line instead, so a
+    ///   spiral vase climbs continuously rather than in steps. With absolute extrusion, the
+    ///   extruder position is also reset with `G92 E0` at each change of height once it grows large.
+    /// - The header names the `flavor`. For Prusa, it checks the nozzle diameter with `M862.1`. With
+    ///   `nozzle_temperature`, it heats the nozzle and waits for it: `M109` for Marlin and Prusa,
+    ///   `SET_HEATER_TEMPERATURE` and `TEMPERATURE_WAIT` for Klipper.
+    /// - Arcs are emitted as `G2` when clockwise and `G3` otherwise, their center given by `I` and `J`
+    ///   relative to the start of the arc. Flavors without arcs get straight `G1` lines along the
+    ///   arc instead, sharing its filament.
+    /// - Tool changes are emitted as `T<n>`, followed by a reset of the extruder position and the
+    ///   purge of the new extruder.
+    /// - Each extruding move is printed at the speed of its `Feature`, or at the first layer speed at
+    ///   the height of the first positioned move, see `SpeedConfig`.
+    /// - Fan speeds are emitted as `M106` from 0 to 255, and speed factors as `M220` percentages.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - The moves to serialize.
+    ///
+    /// # Returns
+    ///
+    /// The G-code program as a string.
+    pub fn write(&self, moves: &[Move]) -> String {
+        let mut gcode = String::new();
+        let _ = writeln!(gcode, "; generated by katana");
+        let _ = writeln!(gcode, "; flavor: {}", self.flavor.name());
+        let _ = writeln!(gcode, "G21 ; millimeters");
+        let _ = writeln!(gcode, "G90 ; absolute positioning");
+        let _ = match self.extrusion_mode {
+            ExtrusionMode::Absolute => writeln!(gcode, "M82 ; absolute extrusion"),
+            ExtrusionMode::Relative => writeln!(gcode, "M83 ; relative extrusion"),
+        };
+        let _ = writeln!(gcode, "G92 E0");
+        if self.flavor == GcodeFlavor::Prusa {
+            let _ = writeln!(
+                gcode,
+                "M862.1 P{:.2} ; check the nozzle diameter",
+                self.nozzle_diameter
+            );
+        }
+        if let Some(temperature) = self.nozzle_temperature {
+            match self.flavor {
+                GcodeFlavor::Marlin | GcodeFlavor::Prusa => {
+                    let _ = writeln!(gcode, "M109 S{temperature:.0} ; wait for the nozzle");
+                }
+                GcodeFlavor::Klipper => {
+                    let _ = writeln!(
+                        gcode,
+                        "SET_HEATER_TEMPERATURE HEATER=extruder TARGET={temperature:.0}"
+                    );
+                    let _ = writeln!(
+                        gcode,
+                        "TEMPERATURE_WAIT SENSOR=extruder MINIMUM={temperature:.0}"
+                    );
+                }
+            }
+        }
+
+        let mode = self.extrusion_mode;
+        let mut e: f32 = 0.0;
+        let mut z = None;
+        let mut first_z = None;
+        let mut first_layer = false;
+        let mut position = Vec2::ZERO;
+        for m in moves {
+            // The height an extrusion climbs to, on the line of the move itself.
+            let mut climb = String::new();
+            let from_z = z;
+            if let Move::Travel { to } | Move::Extrude { to, .. } | Move::Arc { to, .. } = m {
+                first_layer = to.z == *first_z.get_or_insert(to.z);
+                if z != Some(to.z) {
+                    if mode == ExtrusionMode::Absolute && e.abs() > ABSOLUTE_E_RESET {
+                        let _ = writeln!(gcode, "G92 E0");
+                        e = 0.0;
+                    }
+                    match m {
+                        Move::Travel { .. } => {
+                            let _ = writeln!(
+                                gcode,
+                                "G1 Z{:.3} F{:.0}",
+                                to.z,
+                                self.speeds.travel * 60.0
+                            );
+                        }
+                        _ => climb = format!(" Z{:.3}", to.z),
+                    }
+                    z = Some(to.z);
+                }
+            }
+            let from = position;
+            if let Move::Travel { to } | Move::Extrude { to, .. } | Move::Arc { to, .. } = m {
+                position = to.truncate();
+            }
+
+            match *m {
+                Move::Travel { to } => {
+                    let _ = writeln!(
+                        gcode,
+                        "G0 X{:.3} Y{:.3} F{:.0}",
+                        to.x,
+                        to.y,
+                        self.speeds.travel * 60.0
+                    );
+                }
+                Move::Extrude {
+                    to,
+                    e: amount,
+                    feature,
+                } => {
+                    let _ = writeln!(
+                        gcode,
+                        "G1 X{:.3} Y{:.3}{climb} E{:.5} F{:.0}",
+                        to.x,
+                        to.y,
+                        mode.advance(&mut e, amount),
+                        self.speeds.extrusion(feature, first_layer) * 60.0
+                    );
+                }
+                Move::Arc {
+                    to,
+                    center,
+                    clockwise,
+                    e: amount,
+                    feature,
+                } if !self.flavor.supports_arcs() => {
+                    // The points are evenly spread, each line takes the same share of filament.
+                    let points = flatten_arc(from, to.truncate(), center, clockwise);
+                    let share = amount / points.len() as f32;
+                    for (k, p) in points.iter().enumerate() {
+                        // A climbing arc spreads its rise over its lines.
+                        let climb = match from_z.filter(|_| !climb.is_empty()) {
+                            Some(from_z) => {
+                                let t = (k + 1) as f32 / points.len() as f32;
+                                format!(" Z{:.3}", from_z + (to.z - from_z) * t)
+                            }
+                            None => climb.clone(),
+                        };
+                        let _ = writeln!(
+                            gcode,
+                            "G1 X{:.3} Y{:.3}{climb} E{:.5} F{:.0}",
+                            p.x,
+                            p.y,
+                            mode.advance(&mut e, share),
+                            self.speeds.extrusion(feature, first_layer) * 60.0
+                        );
+                    }
+                }
+                Move::Arc {
+                    to,
+                    center,
+                    clockwise,
+                    e: amount,
+                    feature,
+                } => {
+                    let offset = center - from;
+                    let _ = writeln!(
+                        gcode,
+                        "{} X{:.3} Y{:.3}{climb} I{:.3} J{:.3} E{:.5} F{:.0}",
+                        if clockwise { "G2" } else { "G3" },
+                        to.x,
+                        to.y,
+                        offset.x,
+                        offset.y,
+                        mode.advance(&mut e, amount),
+                        self.speeds.extrusion(feature, first_layer) * 60.0
+                    );
+                }
+                Move::Retract { length } => {
+                    let _ = writeln!(
+                        gcode,
+                        "G1 E{:.5} F{:.0}",
+                        mode.advance(&mut e, -length),
+                        self.retraction_feedrate * 60.0
+                    );
+                }
+                Move::Unretract { length } => {
+                    let _ = writeln!(
+                        gcode,
+                        "G1 E{:.5} F{:.0}",
+                        mode.advance(&mut e, length),
+                        self.retraction_feedrate * 60.0
+                    );
+                }
+                Move::ToolChange { tool, purge } => {
+                    let _ = writeln!(gcode, "T{tool}");
+                    let _ = writeln!(gcode, "G92 E0");
+                    e = 0.0;
+                    if purge > 0.0 {
+                        let _ = writeln!(
+                            gcode,
+                            "G1 E{:.5} F{:.0} ; purge",
+                            mode.advance(&mut e, purge),
+                            self.retraction_feedrate * 60.0
+                        );
+                    }
+                }
+                Move::Fan { speed } => {
+                    let _ = writeln!(gcode, "M106 S{:.0}", speed.clamp(0.0, 1.0) * 255.0);
+                }
+                Move::SpeedFactor { factor } => {
+                    let _ = writeln!(gcode, "M220 S{:.0}", factor * 100.0);
+                }
+            }
+        }
+
+        gcode
+    }
+}
+
+/// Returns the points of the arc around `center` from `from` to `to` every few degrees, ending
+/// with `to` and not repeating `from`.
+fn flatten_arc(from: Vec2, to: Vec2, center: Vec2, clockwise: bool) -> Vec<Vec2> {
+    let radius = from.distance(center);
+    let sweep = if radius > EPSILON {
+        arc_length(from, to, center, clockwise) / radius
+    } else {
+        0.0
+    };
+    let steps = (sweep / (PI / 32.0)).ceil().max(1.0) as usize;
+    let step = if clockwise { -sweep } else { sweep } / steps as f32;
+    let mut points: Vec<Vec2> = (1..steps)
+        .map(|k| center + Vec2::from_angle(step * k as f32).rotate(from - center))
+        .collect();
+    points.push(to);
+    points
+}
+
+/// Computes the length of the arc around `center` from `from` to `to` in the given direction.
+pub(crate) fn arc_length(from: Vec2, to: Vec2, center: Vec2, clockwise: bool) -> f32 {
+    let (start, end) = (from - center, to - center);
+    let mut sweep = start.perp_dot(end).atan2(start.dot(end));
+    if clockwise {
+        sweep = -sweep;
+    }
+    if sweep <= 0.0 {
+        sweep += 2.0 * PI;
+    }
+    start.length() * sweep
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::mesh::Mesh;
+
+    /// Extracts the value following `axis` in a G-code line, if present.
+    #[allow(dead_code)]
+    fn word(line: &str, axis: char) -> Option<f32> {
+        line.split_whitespace()
+            .find_map(|w| w.strip_prefix(axis))
+            .and_then(|v| v.parse().ok())
+    }
+
+    /// Test for the `GcodeWriter` on a sliced cube.
+    #[test]
+    fn test_gcode_writer_cube() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(10.0));
+        let layers: Vec<GcodeLayer> = crate::slicer::slice_mesh(&mesh, 0.2)
+            .unwrap()
+            .iter()
+            .map(GcodeLayer::from_layer)
+            .collect();
+        assert_eq!(layers.len(), 50);
+        assert!(layers.iter().all(|l| l.perimeters.len() == 1));
+
+        let writer = GcodeWriter::default();
+        let gcode = writer.write(&writer.moves(&layers));
+
+        let zs: Vec<f32> = gcode
+            .lines()
+            .filter(|l| l.starts_with("G1 Z"))
+            .filter_map(|l| word(l, 'Z'))
+            .collect();
+        assert_eq!(zs.len(), 50);
+        assert!(zs.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!((zs[0] - 0.2).abs() < 1e-4);
+
+        let mut previous_e = 0.0;
+        let mut extrusions = 0;
+        for line in gcode.lines().filter(|l| l.starts_with("G1 X")) {
+            let e = word(line, 'E').unwrap();
+            assert!(e > previous_e);
+            previous_e = e;
+            extrusions += 1;
+        }
+        assert!(extrusions >= 50 * 4);
+        assert_eq!(gcode.matches("G0 ").count(), 50);
+    }
+
+    /// Test for the `GcodeWriter::moves` function visiting the walls in the requested order.
+    #[test]
+    fn test_gcode_writer_wall_order() {
+        let square = vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::splat(10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let writer = GcodeWriter {
+            seam: SeamPlacement::Rear,
+            ..GcodeWriter::default()
+        };
+        // The inset of the loop each travel reaches, i.e. the distance to the edge of the square.
+        let insets = |order| -> Vec<f32> {
+            let shells = crate::perimeter::generate_ordered_shells(&square, 0.4, 3, order);
+            let layer = GcodeLayer::from_shells(0.2, 0.2, shells);
+            writer
+                .moves(&[layer])
+                .iter()
+                .filter_map(|m| match m {
+                    Move::Travel { to } => Some(to.x.min(to.y).min(10.0 - to.x).min(10.0 - to.y)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let outer_first = insets(crate::perimeter::WallOrder::OuterFirst);
+        let inner_first = insets(crate::perimeter::WallOrder::InnerFirst);
+        for (insets, expected) in [
+            (outer_first, [0.2, 0.6, 1.0]),
+            (inner_first, [1.0, 0.6, 0.2]),
+        ] {
+            assert_eq!(insets.len(), 3);
+            for (inset, expected) in insets.iter().zip(expected) {
+                assert!((inset - expected).abs() < 1e-4);
+            }
+        }
+
+        // The seam placement still targets the outer wall, printed last here.
+        let shells = crate::perimeter::generate_ordered_shells(
+            &square,
+            0.4,
+            3,
+            crate::perimeter::WallOrder::InnerFirst,
+        );
+        let moves = writer.moves(&[GcodeLayer::from_shells(0.2, 0.2, shells)]);
+        let last_travel = moves
+            .iter()
+            .rev()
+            .find_map(|m| match m {
+                Move::Travel { to } => Some(*to),
+                _ => None,
+            })
+            .unwrap();
+        assert!((last_travel.y - 9.8).abs() < 1e-4);
+    }
+
+    /// Test for the `GcodeWriter` switching tools between two objects of a plate.
+    #[test]
+    fn test_gcode_writer_tool_changes() {
+        let meshes = [
+            crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE),
+            crate::fixtures::box_mesh(Vec3::new(3.0, 0.0, 0.0), Vec3::new(4.0, 1.0, 1.0)),
+        ]
+        .map(|mesh| (mesh, crate::slicer::SliceConfig::default()));
+        let plate = crate::slicer::slice_plate(&meshes, 0.25).unwrap();
+        let layers: Vec<GcodeLayer> = plate
+            .layers
+            .iter()
+            .map(|layer| GcodeLayer::from_plate_layer(layer, &[0, 1]))
+            .collect();
+        assert_eq!(layers[0].tools, vec![0, 1]);
+
+        let writer = GcodeWriter::default();
+        let moves = writer.moves(&layers);
+        let changes: Vec<usize> = moves
+            .iter()
+            .filter_map(|m| match m {
+                Move::ToolChange { tool, purge } => {
+                    assert_eq!(*purge, writer.purge_length);
+                    Some(*tool)
+                }
+                _ => None,
+            })
+            .collect();
+        // Each layer starts with the tool the previous one ended with, a single change per layer.
+        assert_eq!(changes, vec![1, 0, 1, 0]);
+
+        // Every loop is printed by the tool of its object.
+        let mut tool = 0;
+        for m in &moves {
+            match m {
+                Move::ToolChange { tool: next, .. } => tool = *next,
+                Move::Extrude { to, .. } => assert_eq!(to.x >= 3.0 - 1e-4, tool == 1),
+                _ => {}
+            }
+        }
+
+        let gcode = writer.write(&moves);
+        assert_eq!(gcode.lines().filter(|l| *l == "T1").count(), 2);
+        assert_eq!(gcode.lines().filter(|l| *l == "T0").count(), 2);
+        assert_eq!(gcode.matches("; purge").count(), 4);
+    }
+
+    /// Test for the `GcodeWriter::write` function in both extrusion modes on the same path.
+    #[test]
+    fn test_gcode_writer_extrusion_mode() {
+        let moves = [
+            Move::Travel { to: Vec3::ZERO },
+            Move::Extrude {
+                to: Vec3::X,
+                e: 0.5,
+                feature: Feature::OuterPerimeter,
+            },
+            Move::Extrude {
+                to: Vec3::new(1.0, 1.0, 0.0),
+                e: 0.25,
+                feature: Feature::OuterPerimeter,
+            },
+            Move::Retract { length: 1.0 },
+            Move::Travel {
+                to: Vec3::new(5.0, 5.0, 0.0),
+            },
+            Move::Unretract { length: 1.0 },
+            Move::Extrude {
+                to: Vec3::new(6.0, 5.0, 0.0),
+                e: 0.5,
+                feature: Feature::OuterPerimeter,
+            },
+        ];
+        let e_values = |mode| -> Vec<f32> {
+            let writer = GcodeWriter {
+                extrusion_mode: mode,
+                ..GcodeWriter::default()
+            };
+            writer
+                .write(&moves)
+                .lines()
+                .filter(|l| l.starts_with("G1 X") || l.starts_with("G1 E"))
+                .filter_map(|l| word(l, 'E'))
+                .collect()
+        };
+
+        let absolute = e_values(ExtrusionMode::Absolute);
+        let relative = e_values(ExtrusionMode::Relative);
+        let expected_absolute = [0.5, 0.75, -0.25, 0.75, 1.25];
+        let expected_relative = [0.5, 0.25, -1.0, 1.0, 0.5];
+        assert_eq!(absolute.len(), 5);
+        assert_eq!(relative.len(), 5);
+        for (e, expected) in absolute.iter().zip(expected_absolute) {
+            assert!((e - expected).abs() < 1e-5);
+        }
+        for (e, expected) in relative.iter().zip(expected_relative) {
+            assert!((e - expected).abs() < 1e-5);
+        }
+
+        let header = |mode| {
+            GcodeWriter {
+                extrusion_mode: mode,
+                ..GcodeWriter::default()
+            }
+            .write(&[])
+        };
+        assert!(header(ExtrusionMode::Absolute).contains("M82"));
+        assert!(header(ExtrusionMode::Relative).contains("M83"));
+    }
+
+    /// Test for the `GcodeWriter::write` function resetting a large absolute extruder position.
+    #[test]
+    fn test_gcode_writer_absolute_e_reset() {
+        let moves: Vec<Move> = (1..=4)
+            .map(|layer| Move::Extrude {
+                to: Vec3::new(0.0, 0.0, layer as f32),
+                e: 600.0,
+                feature: Feature::OuterPerimeter,
+            })
+            .collect();
+        let gcode = GcodeWriter::default().write(&moves);
+        let e_values: Vec<f32> = gcode
+            .lines()
+            .filter(|l| l.starts_with("G1 X"))
+            .filter_map(|l| word(l, 'E'))
+            .collect();
+        assert_eq!(e_values, vec![600.0, 1200.0, 600.0, 1200.0]);
+        assert_eq!(gcode.matches("G92 E0").count(), 2);
+    }
+
+    /// Test for the `GcodeWriter::write` function printing each feature at its own speed.
+    #[test]
+    fn test_gcode_writer_feature_speeds() {
+        let extrude = |to: Vec3, feature| Move::Extrude {
+            to,
+            e: 0.1,
+            feature,
+        };
+        let moves = [
+            Move::Travel { to: Vec3::ZERO },
+            extrude(Vec3::X, Feature::Infill),
+            Move::Travel {
+                to: Vec3::new(0.0, 0.0, 0.4),
+            },
+            extrude(Vec3::new(1.0, 0.0, 0.4), Feature::Infill),
+            extrude(Vec3::new(1.0, 1.0, 0.4), Feature::OuterPerimeter),
+            extrude(Vec3::new(0.0, 1.0, 0.4), Feature::InnerPerimeter),
+        ];
+        let speeds = SpeedConfig::default();
+        let writer = GcodeWriter::default();
+        let feedrates: Vec<f32> = writer
+            .write(&moves)
+            .lines()
+            .filter(|l| l.starts_with("G1 X"))
+            .filter_map(|l| word(l, 'F'))
+            .collect();
+
+        // The infill of the first layer is slowed down like everything else on it.
+        let expected = [
+            speeds.first_layer,
+            speeds.infill,
+            speeds.outer_perimeter,
+            speeds.inner_perimeter,
+        ];
+        assert_eq!(feedrates, expected.map(|speed| speed * 60.0).to_vec());
+        assert_ne!(feedrates[1], feedrates[2]);
+    }
+
+    /// Test for the `GcodeWriter::extrusion_length` function following the volumetric model.
+    #[test]
+    fn test_gcode_writer_extrusion_length() {
+        let writer = GcodeWriter {
+            nozzle_diameter: 0.5,
+            filament_diameter: 2.0,
+            extrusion_multiplier: 2.0,
+            ..GcodeWriter::default()
+        };
+        // 10mm x 0.5mm x 0.2mm = 1mm³, over a filament section of pi mm², doubled.
+        let e = writer.extrusion_length(10.0, 0.2);
+        assert!((e - 2.0 / PI).abs() < 1e-6);
+    }
+
+    /// Test for the `GcodeWriter` printing the walls of a tube as arcs.
+    #[test]
+    fn test_gcode_writer_arcs() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::tube_triangles(5.0, 10.0, 1.0, 64));
+        let layers: Vec<GcodeLayer> = crate::slicer::slice_mesh(&mesh, 0.5)
+            .unwrap()
+            .iter()
+            .map(GcodeLayer::from_layer)
+            .collect();
+        let lines = GcodeWriter::default();
+        let arcs = GcodeWriter {
+            arc_tolerance: Some(0.05),
+            ..GcodeWriter::default()
+        };
+
+        let gcode = arcs.write(&arcs.moves(&layers));
+        assert!(gcode.lines().all(|l| !l.starts_with("G1 X")));
+        assert_eq!(
+            gcode.matches("G2 ").count() + gcode.matches("G3 ").count(),
+            8
+        );
+        for m in arcs.moves(&layers) {
+            if let Move::Arc { center, .. } = m {
+                assert!(center.length() < 0.05);
+            }
+        }
+
+        // The arcs extrude as much filament as the lines they replace, within the fitting error.
+        let total = |moves: Vec<Move>| -> f32 {
+            moves
+                .iter()
+                .map(|m| match *m {
+                    Move::Extrude { e, .. } | Move::Arc { e, .. } => e,
+                    _ => 0.0,
+                })
+                .sum()
+        };
+        let (straight, curved) = (total(lines.moves(&layers)), total(arcs.moves(&layers)));
+        assert!((curved - straight).abs() < straight * 0.01);
+    }
+
+    /// Test for the `GcodeWriter::spiralize` function raising Z continuously along the loops.
+    #[test]
+    fn test_gcode_writer_spiralize() {
+        let square = vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::splat(10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let mut layers: Vec<GcodeLayer> = (1..=4)
+            .map(|i| GcodeLayer {
+                z: i as f32 * 0.2,
+                thickness: 0.2,
+                perimeters: vec![square.clone()],
+                walls: Vec::new(),
+                tools: Vec::new(),
+                below: None,
+                wide_paths: Vec::new(),
+            })
+            .collect();
+        let moves = GcodeWriter::default().spiralize(&layers).unwrap();
+        assert_eq!(
+            moves[0],
+            Move::Travel {
+                to: Vec3::new(0.0, 0.0, 0.2)
+            }
+        );
+        assert_eq!(moves.len(), 1 + 4 * 4);
+        assert!(moves[1..].iter().all(|m| matches!(m, Move::Extrude { .. })));
+
+        // Z never decreases, and rises by the same amount per millimeter along each loop.
+        let points: Vec<Vec3> = moves
+            .iter()
+            .map(|m| match *m {
+                Move::Travel { to } | Move::Extrude { to, .. } => to,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(points.windows(2).all(|pair| pair[1].z >= pair[0].z));
+        for pair in points[4..].windows(2) {
+            let rise = (pair[1].z - pair[0].z) / pair[0].truncate().distance(pair[1].truncate());
+            assert!((rise - 0.2 / 40.0).abs() < 1e-5);
+        }
+        assert!((points[points.len() - 1].z - 0.8).abs() < 1e-6);
+
+        // The G-code climbs along the lines themselves, without separate Z steps.
+        let gcode = GcodeWriter::default().write(&moves);
+        assert_eq!(gcode.matches("G1 Z").count(), 1);
+        let extrusions: Vec<&str> = gcode.lines().filter(|l| l.starts_with("G1 X")).collect();
+        assert_eq!(extrusions.len(), 4 * 4);
+        for (line, point) in extrusions[4..].iter().zip(&points[5..]) {
+            assert!((word(line, 'Z').unwrap() - point.z).abs() < 1e-3);
+        }
+        assert!(extrusions[..4].iter().all(|l| word(l, 'Z').is_none()));
+
+        layers[2].perimeters.push(square.clone());
+        assert_eq!(
+            GcodeWriter::default().spiralize(&layers),
+            Err(GcodeError::ContourCount { layer: 2, count: 2 })
+        );
+    }
+
+    /// Test for the `GcodeWriter::moves` function lining up the seams of stacked squares.
+    #[test]
+    fn test_gcode_writer_aligned_seam() {
+        let writer = GcodeWriter {
+            seam: SeamPlacement::Aligned,
+            ..GcodeWriter::default()
+        };
+        // Each layer starts its loop elsewhere and drifts a little, and an island appears midway.
+        let layers: Vec<GcodeLayer> = (0..8)
+            .map(|i| {
+                let drift = Vec2::splat(0.01 * i as f32);
+                let mut outline =
+                    crate::contour::resample(&crate::fixtures::square(drift, 10.0), 0.5);
+                outline.rotate_left(7 * i);
+                let mut shells = vec![(0, outline)];
+                if i >= 4 {
+                    shells.push((0, crate::fixtures::square(Vec2::new(20.0, 0.0), 5.0)));
+                }
+                GcodeLayer::from_shells(0.2 * (i + 1) as f32, 0.2, shells)
+            })
+            .collect();
+        let seams: Vec<Vec3> = writer
+            .moves(&layers)
+            .iter()
+            .filter_map(|m| match m {
+                Move::Travel { to } if to.x < 15.0 => Some(*to),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(seams.len(), 8);
+        for seam in &seams {
+            assert!(seam.truncate().distance(seams[0].truncate()) < 0.1);
+        }
+        // The first layer falls back to the rear of the square.
+        assert!((seams[0].y - 10.0).abs() < 1e-4);
+    }
+
+    /// Test for the `GcodeWriter::moves` function slowing down the walls over a gap.
+    #[test]
+    fn test_gcode_writer_overhang() {
+        let rectangle =
+            |min: Vec2, max: Vec2| vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        // A 20mm long wall resting on two 5mm pillars, leaving a 10mm gap in the middle.
+        let mut layer = GcodeLayer::from_shells(
+            0.4,
+            0.2,
+            vec![(0, rectangle(Vec2::ZERO, Vec2::new(20.0, 2.0)))],
+        );
+        layer.perimeters[0] = crate::contour::resample(&layer.perimeters[0], 1.0);
+        layer.below = Some(vec![
+            rectangle(Vec2::splat(-1.0), Vec2::new(5.0, 3.0)),
+            rectangle(Vec2::new(15.0, -1.0), Vec2::new(21.0, 3.0)),
+        ]);
+        // The first layer is printed at its own speed, the wall is laid on top of another one.
+        let base = GcodeLayer::from_shells(0.2, 0.2, vec![(0, rectangle(Vec2::ZERO, Vec2::ONE))]);
+        let writer = GcodeWriter::default();
+        let moves = writer.moves(&[base, layer]);
+
+        let mut from = Vec3::ZERO;
+        let (mut supported, mut overhangs) = (0, 0);
+        for m in &moves {
+            match *m {
+                Move::Extrude { to, feature, .. } if to.z > 0.3 => {
+                    if (5.0..15.0).contains(&((from.x + to.x) / 2.0)) {
+                        assert_eq!(feature, Feature::Overhang);
+                        overhangs += 1;
+                    } else {
+                        assert_eq!(feature, Feature::OuterPerimeter);
+                        supported += 1;
+                    }
+                }
+                _ => {}
+            }
+            if let Move::Travel { to } | Move::Extrude { to, .. } = *m {
+                from = to;
+            }
+        }
+        assert!(overhangs >= 20);
+        assert!(supported >= 20);
+
+        // The overhanging lines are printed at the overhang speed, the others at their usual one.
+        let gcode = writer.write(&moves);
+        let feedrate = |speed: f32| format!("F{:.0}", speed * 60.0);
+        assert!(gcode.contains(&feedrate(writer.speeds.overhang)));
+        assert!(gcode.contains(&feedrate(writer.speeds.outer_perimeter)));
+    }
+
+    /// Test for the `moves` function modulating the filament along a wide path.
+    #[test]
+    fn test_gcode_writer_wide_path() {
+        let wedge = vec![Vec2::ZERO, Vec2::new(10.0, -0.4), Vec2::new(10.0, 0.4)];
+        let mut layer = GcodeLayer::from_shells(0.2, 0.2, Vec::new());
+        layer.wide_paths = crate::perimeter::variable_width(&[wedge], 0.4, 0.1, 1.0);
+        let writer = GcodeWriter::default();
+        let moves = writer.moves(&[layer]);
+
+        assert!(matches!(moves[0], Move::Travel { .. }));
+        let mut from = match moves[0] {
+            Move::Travel { to } => to,
+            _ => unreachable!(),
+        };
+        let mut rates = Vec::new();
+        for m in &moves[1..] {
+            let Move::Extrude { to, e, feature } = *m else {
+                panic!("a wide path is a single run of extrusions");
+            };
+            assert_eq!(feature, Feature::InnerPerimeter);
+            rates.push(e / from.distance(to));
+            from = to;
+        }
+        // The filament per millimeter grows with the width, from the tip to the base of the wedge.
+        let nominal = writer.extrusion_length(1.0, 0.2);
+        assert!(rates.first().unwrap() < &(nominal / 2.0));
+        assert!(rates.last().unwrap() > &(nominal * 1.5));
+        assert!(rates.windows(2).all(|pair| pair[1] >= pair[0] - 1e-6));
+    }
+
+    /// Test for the `GcodeWriter::write` function adapting the header and arcs to the flavor.
+    #[test]
+    fn test_gcode_writer_flavor() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::tube_triangles(5.0, 10.0, 1.0, 64));
+        let layers: Vec<GcodeLayer> = crate::slicer::slice_mesh(&mesh, 0.5)
+            .unwrap()
+            .iter()
+            .map(GcodeLayer::from_layer)
+            .collect();
+        let marlin = GcodeWriter {
+            arc_tolerance: Some(0.05),
+            nozzle_temperature: Some(210.0),
+            ..GcodeWriter::default()
+        };
+        let klipper = GcodeWriter {
+            flavor: GcodeFlavor::Klipper,
+            ..marlin.clone()
+        };
+        let header = |gcode: &str| -> Vec<String> {
+            gcode
+                .lines()
+                .take_while(|l| !l.starts_with("G0 ") && !l.starts_with("G1 "))
+                .map(String::from)
+                .collect()
+        };
+
+        let marlin_gcode = marlin.write(&marlin.moves(&layers));
+        let klipper_gcode = klipper.write(&klipper.moves(&layers));
+        assert_ne!(header(&marlin_gcode), header(&klipper_gcode));
+        assert!(header(&marlin_gcode).contains(&"; flavor: marlin".to_string()));
+        assert!(marlin_gcode.contains("M109 S210"));
+        assert!(header(&klipper_gcode).contains(&"; flavor: klipper".to_string()));
+        assert!(klipper_gcode.contains("TEMPERATURE_WAIT SENSOR=extruder MINIMUM=210"));
+        assert!(!klipper_gcode.contains("M109"));
+
+        // Klipper gets no arcs, neither from the moves nor from arcs written anyway.
+        assert!(marlin_gcode.contains("G2 ") || marlin_gcode.contains("G3 "));
+        assert!(!klipper_gcode.contains("G2 ") && !klipper_gcode.contains("G3 "));
+        let flattened = klipper.write(&marlin.moves(&layers));
+        assert!(!flattened.contains("G2 ") && !flattened.contains("G3 "));
+        let last_e = |gcode: &str| {
+            gcode
+                .lines()
+                .rev()
+                .find_map(|l| l.contains(" X").then(|| word(l, 'E')).flatten())
+                .unwrap()
+        };
+        assert!((last_e(&flattened) - last_e(&marlin_gcode)).abs() < 1e-3);
+    }
+
+    /// Test for the `GcodeWriter::write` function checking the nozzle in the Prusa header.
+    #[test]
+    fn test_gcode_writer_flavor_prusa() {
+        let prusa = GcodeWriter {
+            flavor: GcodeFlavor::Prusa,
+            nozzle_diameter: 0.6,
+            nozzle_temperature: Some(215.0),
+            ..GcodeWriter::default()
+        };
+        let gcode = prusa.write(&[]);
+        assert_eq!(
+            gcode.lines().collect::<Vec<_>>(),
+            [
+                "; generated by katana",
+                "; flavor: prusa",
+                "G21 ; millimeters",
+                "G90 ; absolute positioning",
+                "M82 ; absolute extrusion",
+                "G92 E0",
+                "M862.1 P0.60 ; check the nozzle diameter",
+                "M109 S215 ; wait for the nozzle",
+            ]
+        );
+
+        // Marlin shares the dialect but skips the check.
+        let marlin = GcodeWriter {
+            flavor: GcodeFlavor::Marlin,
+            ..prusa
+        };
+        assert!(!marlin.write(&[]).contains("M862"));
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use glam::f32::Vec2;
+
+use super::Move;
+use crate::geometry::point_in_polygon;
+use crate::slicer::EPSILON;
+
+/// The settings of `plan_retractions`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetractionConfig {
+    /// The length of filament pulled back before a travel, in millimeters.
+    pub length: f32,
+    /// The shortest travel worth retracting for, in millimeters.
+    pub min_travel: f32,
+    /// The distance the nozzle keeps following the last printed path before retracting, wiping off
+    /// the ooze, in millimeters. 0 disables wiping.
+    pub wipe_distance: f32,
+    /// The height the nozzle is lifted by during a retracted travel, so it does not drag across the
+    /// printed parts, in millimeters. 0 disables the lift.
+    pub z_hop: f32,
+}
+
+impl Default for RetractionConfig {
+    fn default() -> Self {
+        RetractionConfig {
+            length: 1.0,
+            min_travel: 2.0,
+            wipe_distance: 0.0,
+            z_hop: 0.0,
+        }
+    }
+}
+
+/// Decides which travel moves retract the filament, to avoid stringing without retracting needlessly.
+///
+/// # Remarks
+///
+/// - Any `Retract` and `Unretract` already in `moves` is **replaced** by the plan.
+/// - A travel is wrapped in a retraction when it is longer than `min_travel` and does **not** stay
+///   inside a single loop extruded on its layer, i.e. when it crosses a perimeter or goes through the
+///   air between parts. Strings left inside a part are hidden by the infill.
+/// - With `wipe_distance`, the nozzle first follows the path it just printed for that distance
+///   without extruding.
+/// - With `z_hop`, a retracted travel goes **up** by the hop once retracted, moves across at the
+///   lifted height of its destination, and comes back **down** before pushing the filament back.
+///   Travels that are not retracted stay low, they do not leave the part.
+/// - Travels before the first extrusion are never retracted.
+/// - A tool change always retracts the outgoing extruder once it has extruded.
+///
+/// # Arguments
+///
+/// * `moves` - The moves of the print, as generated by `GcodeWriter::moves`.
+/// * `config` - The retraction settings.
+pub fn plan_retractions(moves: &mut Vec<Move>, config: &RetractionConfig) {
+    let loops = extruded_loops(moves);
+
+    let mut planned = Vec::with_capacity(moves.len());
+    let mut position = Vec2::ZERO;
+    let mut path: Vec<Vec2> = Vec::new();
+    let mut extruded = false;
+    for &m in moves.iter() {
+        match m {
+            Move::Retract { .. } | Move::Unretract { .. } => continue,
+            Move::Fan { .. } | Move::SpeedFactor { .. } => planned.push(m),
+            Move::ToolChange { .. } => {
+                // The outgoing extruder would ooze while it sits idle.
+                if extruded {
+                    planned.push(Move::Retract {
+                        length: config.length,
+                    });
+                }
+                // The purge primes the new extruder, its first travel needs no retraction.
+                planned.push(m);
+                extruded = false;
+            }
+            Move::Travel { to } => {
+                let target = to.truncate();
+                let layer = loops.get(&to.z.to_bits()).map_or(&[][..], Vec::as_slice);
+                let retract = extruded
+                    && position.distance(target) > config.min_travel
+                    && !layer.iter().any(|l| stays_inside(position, target, l));
+
+                if retract {
+                    // The nozzle lifts where the wipe leaves it, not back over the printed path.
+                    let mut lift = position;
+                    if config.wipe_distance > 0.0 {
+                        for point in wipe(&path, config.wipe_distance) {
+                            planned.push(Move::Travel {
+                                to: point.extend(current_z(&planned, to.z)),
+                            });
+                            lift = point;
+                        }
+                    }
+                    planned.push(Move::Retract {
+                        length: config.length,
+                    });
+                    if config.z_hop > 0.0 {
+                        let z = current_z(&planned, to.z);
+                        planned.push(Move::Travel {
+                            to: lift.extend(z + config.z_hop),
+                        });
+                        planned.push(Move::Travel {
+                            to: target.extend(to.z + config.z_hop),
+                        });
+                    }
+                    planned.push(m);
+                    planned.push(Move::Unretract {
+                        length: config.length,
+                    });
+                } else {
+                    planned.push(m);
+                }
+                position = target;
+                path = vec![target];
+            }
+            Move::Extrude { to, .. } | Move::Arc { to, .. } => {
+                planned.push(m);
+                position = to.truncate();
+                path.push(position);
+                extruded = true;
+            }
+        }
+    }
+
+    *moves = planned;
+}
+
+/// Returns the height of the last positioned move, or `fallback` if there is none.
+fn current_z(moves: &[Move], fallback: f32) -> f32 {
+    moves
+        .iter()
+        .rev()
+        .find_map(|m| match m {
+            Move::Travel { to } | Move::Extrude { to, .. } | Move::Arc { to, .. } => Some(to.z),
+            _ => None,
+        })
+        .unwrap_or(fallback)
+}
+
+/// Collects the closed loops extruded on each layer, keyed by the bits of their height.
+///
+/// Arcs are approximated by their chords, which is enough to tell inside from outside.
+fn extruded_loops(moves: &[Move]) -> HashMap<u32, Vec<Vec<Vec2>>> {
+    let mut loops: HashMap<u32, Vec<Vec<Vec2>>> = HashMap::new();
+    let mut path: Vec<Vec2> = Vec::new();
+    let mut z = 0.0_f32;
+    let mut close = |path: &mut Vec<Vec2>, z: f32| {
+        if path.len() > 3 && path[0].abs_diff_eq(path[path.len() - 1], EPSILON) {
+            path.pop();
+            loops
+                .entry(z.to_bits())
+                .or_default()
+                .push(std::mem::take(path));
+        }
+        path.clear();
+    };
+
+    for m in moves {
+        match *m {
+            Move::Travel { to } => {
+                close(&mut path, z);
+                path.push(to.truncate());
+                z = to.z;
+            }
+            Move::Extrude { to, .. } | Move::Arc { to, .. } => path.push(to.truncate()),
+            _ => {}
+        }
+    }
+    close(&mut path, z);
+
+    loops
+}
+
+/// Returns whether the segment from `a` to `b` lies inside a loop without crossing its edges.
+fn stays_inside(a: Vec2, b: Vec2, polygon: &[Vec2]) -> bool {
+    if !point_in_polygon((a + b) / 2.0, polygon) {
+        return false;
+    }
+    let direction = b - a;
+    (0..polygon.len()).all(|i| {
+        let (p, q) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let side = q - p;
+        let denominator = direction.perp_dot(side);
+        if denominator.abs() <= EPSILON {
+            return true;
+        }
+        let t = (p - a).perp_dot(side) / denominator;
+        let u = (p - a).perp_dot(direction) / denominator;
+        // Touching the loop at either end of the travel is fine, e.g. leaving from a perimeter.
+        !(t > EPSILON && t < 1.0 - EPSILON && (0.0..=1.0).contains(&u))
+    })
+}
+
+/// Computes the points of a wipe: following a printed path again from its start, as a closed
+/// perimeter ends where it began, for `distance`.
+fn wipe(path: &[Vec2], distance: f32) -> Vec<Vec2> {
+    let mut points = Vec::new();
+    let mut left = distance;
+    for pair in path.windows(2) {
+        let length = pair[0].distance(pair[1]);
+        if length >= left {
+            points.push(pair[0] + (pair[1] - pair[0]) * (left / length));
+            break;
+        }
+        points.push(pair[1]);
+        left -= length;
+    }
+    points
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::gcode::{ExtrusionMode, GcodeLayer, GcodeWriter};
+    #[allow(unused_imports)]
+    use glam::f32::Vec3;
+
+    /// Test for the `plan_retractions` function on a hop inside a part and a travel to another part.
+    #[test]
+    fn test_plan_retractions_two_parts() {
+        let layer = GcodeLayer {
+            z: 0.2,
+            thickness: 0.2,
+            perimeters: vec![
+                crate::fixtures::square(Vec2::ZERO, 10.0),
+                crate::fixtures::square(Vec2::splat(4.0), 2.0),
+                crate::fixtures::square(Vec2::new(20.0, 0.0), 10.0),
+            ],
+            walls: Vec::new(),
+            tools: Vec::new(),
+            below: None,
+            wide_paths: Vec::new(),
+        };
+        let mut moves = GcodeWriter::default().moves(&[layer]);
+        let retractions = |moves: &[Move]| {
+            moves
+                .iter()
+                .filter(|m| matches!(m, Move::Retract { .. }))
+                .count()
+        };
+        assert_eq!(retractions(&moves), 2);
+
+        plan_retractions(&mut moves, &RetractionConfig::default());
+        assert_eq!(retractions(&moves), 1);
+        let index = moves
+            .iter()
+            .position(|m| matches!(m, Move::Retract { .. }))
+            .unwrap();
+        // The hop to the island inside the first part is not retracted, the travel to the second is.
+        assert!(matches!(moves[index - 1], Move::Extrude { .. }));
+        assert_eq!(
+            moves[index + 1],
+            Move::Travel {
+                to: Vec2::new(20.0, 0.0).extend(0.2)
+            }
+        );
+        assert_eq!(moves[index + 2], Move::Unretract { length: 1.0 });
+
+        // Wiping follows the island again before retracting.
+        let config = RetractionConfig {
+            wipe_distance: 3.0,
+            ..RetractionConfig::default()
+        };
+        plan_retractions(&mut moves, &config);
+        let index = moves
+            .iter()
+            .position(|m| matches!(m, Move::Retract { .. }))
+            .unwrap();
+        assert_eq!(retractions(&moves), 1);
+        assert_eq!(
+            &moves[index - 2..index],
+            &[
+                Move::Travel {
+                    to: Vec2::new(6.0, 4.0).extend(0.2)
+                },
+                Move::Travel {
+                    to: Vec2::new(6.0, 5.0).extend(0.2)
+                },
+            ]
+        );
+    }
+
+    /// Test for the `plan_retractions` function retracting the outgoing extruder before a tool change.
+    #[test]
+    fn test_plan_retractions_tool_change() {
+        let layer = GcodeLayer {
+            z: 0.2,
+            thickness: 0.2,
+            perimeters: vec![
+                crate::fixtures::square(Vec2::ZERO, 10.0),
+                crate::fixtures::square(Vec2::new(20.0, 0.0), 10.0),
+            ],
+            walls: Vec::new(),
+            tools: vec![0, 1],
+            below: None,
+            wide_paths: Vec::new(),
+        };
+        let above = GcodeLayer {
+            z: 0.4,
+            ..layer.clone()
+        };
+        let writer = GcodeWriter {
+            extrusion_mode: ExtrusionMode::Relative,
+            ..GcodeWriter::default()
+        };
+        let mut moves = writer.moves(&[layer, above]);
+        plan_retractions(&mut moves, &RetractionConfig::default());
+
+        let changes: Vec<usize> = moves
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| matches!(m, Move::ToolChange { .. }))
+            .map(|(i, _)| i)
+            .collect();
+        assert!(changes.len() >= 2);
+        for i in changes {
+            assert_eq!(moves[i - 1], Move::Retract { length: 1.0 });
+        }
+
+        // In the G-code, every `T<n>` follows the retraction of the previous tool.
+        let gcode = writer.write(&moves);
+        let lines: Vec<&str> = gcode.lines().collect();
+        for (i, _) in lines.iter().enumerate().filter(|(_, l)| l.starts_with('T')) {
+            assert!(lines[i - 1].starts_with("G1 E-1.00000"));
+        }
+    }
+
+    /// Test for the `plan_retractions` function lifting the nozzle over the gap between two parts.
+    #[test]
+    fn test_plan_retractions_z_hop() {
+        let layer = GcodeLayer {
+            z: 0.2,
+            thickness: 0.2,
+            perimeters: vec![
+                crate::fixtures::square(Vec2::ZERO, 10.0),
+                crate::fixtures::square(Vec2::new(20.0, 0.0), 10.0),
+            ],
+            walls: Vec::new(),
+            tools: Vec::new(),
+            below: None,
+            wide_paths: Vec::new(),
+        };
+        let printed = GcodeWriter::default().moves(&[layer]);
+        let mut moves = printed.clone();
+        let config = RetractionConfig {
+            z_hop: 0.4,
+            ..RetractionConfig::default()
+        };
+        plan_retractions(&mut moves, &config);
+
+        let index = moves
+            .iter()
+            .position(|m| matches!(m, Move::Retract { .. }))
+            .unwrap();
+        assert_eq!(
+            &moves[index + 1..index + 5],
+            &[
+                Move::Travel {
+                    to: Vec3::new(0.0, 0.0, 0.6)
+                },
+                Move::Travel {
+                    to: Vec3::new(20.0, 0.0, 0.6)
+                },
+                Move::Travel {
+                    to: Vec3::new(20.0, 0.0, 0.2)
+                },
+                Move::Unretract { length: 1.0 },
+            ]
+        );
+        // The first travel, before anything is printed, is neither retracted nor lifted.
+        assert!(moves[..index]
+            .iter()
+            .all(|m| !matches!(m, Move::Travel { to } if to.z > 0.2)));
+
+        // After a wipe, the nozzle lifts straight up from where the wipe ended.
+        let config = RetractionConfig {
+            wipe_distance: 3.0,
+            ..config
+        };
+        let mut moves = printed;
+        plan_retractions(&mut moves, &config);
+        let index = moves
+            .iter()
+            .position(|m| matches!(m, Move::Retract { .. }))
+            .unwrap();
+        assert_eq!(
+            &moves[index - 1..index + 3],
+            &[
+                Move::Travel {
+                    to: Vec3::new(3.0, 0.0, 0.2)
+                },
+                Move::Retract { length: 1.0 },
+                Move::Travel {
+                    to: Vec3::new(3.0, 0.0, 0.6)
+                },
+                Move::Travel {
+                    to: Vec3::new(20.0, 0.0, 0.6)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+
+use glam::f32::{Vec2, Vec3};
+use glam::IVec3;
+
+use crate::slicer::{compare_by_xyz, EPSILON};
+
+/// The number of points up to which `weld_indices` compares them all instead of building a grid.
+const SMALL_WELD: usize = 16;
+
+/// Projects a point on the plane of its layer, dropping its height along Y, the build axis.
+///
+/// # Returns
+///
+/// The `(x, z)` coordinates of the point.
+pub fn to_planar(p: Vec3) -> Vec2 {
+    Vec2::new(p.x, p.z)
+}
+
+/// Lifts a point of a layer back into space, the inverse of `to_planar`.
+///
+/// # Returns
+///
+/// The point `(p.x, height, p.y)`.
+pub fn from_planar(p: Vec2, height: f32) -> Vec3 {
+    Vec3::new(p.x, height, p.y)
+}
+
+/// Checks whether a 2D point lies inside a polygon, using the ray casting (crossing number) method.
+///
+/// # Remarks
+///
+/// - Points on the **boundary** of the polygon, within `EPSILON` of an edge or a vertex, are
+///   considered inside, so the answer does not flip on float noise.
+/// - The polygon may be convex or concave and wound either way, its first point must not be repeated.
+///
+/// # Arguments
+///
+/// * `point` - The point to test.
+/// * `polygon` - The vertices of the polygon, the last one connecting back to the first.
+///
+/// # Returns
+///
+/// `true` if the point is inside the polygon or on its boundary.
+pub fn point_in_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+
+    let edges = || {
+        polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    };
+
+    if edges().any(|(a, b)| distance_to_segment(point, a, b) <= EPSILON) {
+        return true;
+    }
+
+    // Count the edges crossed by a ray going from the point towards +X. Each edge is half-open
+    // along Y so a ray passing through a vertex counts it exactly once.
+    let crossings = edges()
+        .filter(|&(a, b)| (a.y > point.y) != (b.y > point.y))
+        .filter(|&(a, b)| {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            point.x < x
+        })
+        .count();
+
+    crossings % 2 == 1
+}
+
+/// Computes the signed area of a polygon with the shoelace formula.
+///
+/// # Returns
+///
+/// The area of the polygon, positive when it is wound counter-clockwise and negative when clockwise.
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| polygon[i].perp_dot(polygon[(i + 1) % n]))
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Reverses a polygon in place if it is not wound the requested way.
+///
+/// # Remarks
+///
+/// - Outer contours are expected counter-clockwise and holes clockwise, as produced by the `clip`
+///   module. A polygon with no area is left untouched.
+///
+/// # Arguments
+///
+/// * `polygon` - The vertices of the polygon, without repeating the first one.
+/// * `want_ccw` - Whether the polygon must be wound counter-clockwise, or clockwise.
+pub fn ensure_winding(polygon: &mut [Vec2], want_ccw: bool) {
+    let area = signed_area(polygon);
+    if area != 0.0 && (area > 0.0) != want_ccw {
+        polygon.reverse();
+    }
+}
+
+/// Computes the distance between a point and the segment going from `a` to `b`.
+pub fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 {
+        ((point - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(a + ab * t)
+}
+
+/// Computes the point where two segments meet.
+///
+/// # Remarks
+///
+/// - Segments **touching** within `epsilon`, at a shared endpoint or with an endpoint lying on the
+///   other segment, meet at that endpoint, which is returned exactly.
+/// - Segments closer to parallel than `epsilon` over their length are treated as **parallel**, so
+///   the point is never computed from a vanishing cross product. Collinear segments only meet if
+///   they touch at a single endpoint, an overlap of positive length has no single point and yields
+///   `None`.
+/// - Degenerate segments, shorter than `epsilon`, are treated as points.
+///
+/// # Arguments
+///
+/// * `a` - The endpoints of the first segment.
+/// * `b` - The endpoints of the second segment.
+/// * `epsilon` - The distance under which points are considered to touch.
+///
+/// # Returns
+///
+/// The point shared by both segments, or `None` if they do not meet or overlap along a segment.
+pub fn segment_intersection(a: [Vec2; 2], b: [Vec2; 2], epsilon: f32) -> Option<Vec2> {
+    let touches =
+        |p: Vec2, segment: [Vec2; 2]| distance_to_segment(p, segment[0], segment[1]) <= epsilon;
+    let contacts: Vec<Vec2> = a
+        .iter()
+        .filter(|&&p| touches(p, b))
+        .chain(b.iter().filter(|&&p| touches(p, a)))
+        .copied()
+        .collect();
+
+    let r = a[1] - a[0];
+    let s = b[1] - b[0];
+    let denominator = r.perp_dot(s);
+    let parallel = denominator.abs() <= epsilon * r.length().max(s.length());
+    if parallel || !contacts.is_empty() {
+        // Every contact point must be the same point, otherwise the segments overlap.
+        let first = *contacts.first()?;
+        return contacts
+            .iter()
+            .all(|p| p.distance(first) <= epsilon)
+            .then_some(first);
+    }
+
+    let offset = b[0] - a[0];
+    let t = offset.perp_dot(s) / denominator;
+    let u = offset.perp_dot(r) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| a[0] + r * t)
+}
+
+/// Sorts points and removes approximate duplicates in place.
+///
+/// # Remarks
+///
+/// - Duplicates are found with the grid of `weld_points_grid`, so points closer than `epsilon` on
+///   every axis are merged even when they fall on both sides of a cell of `compare_by_xyz`.
+/// - Of each group of duplicates, only the **first** one of the input is kept. The kept points are
+///   then sorted with `compare_by_xyz`, so the **order** of the input is not preserved.
+///
+/// # Arguments
+///
+/// * `points` - The points to deduplicate.
+/// * `epsilon` - The maximum absolute difference per coordinate for two points to be merged.
+pub fn dedup_points(points: &mut Vec<Vec3>, epsilon: f32) {
+    weld_points_grid(points, epsilon);
+    points.sort_by(|a, b| compare_by_xyz(a, b, epsilon));
+}
+
+/// Removes approximate duplicates in place by bucketing the points into a grid, in near-linear time.
+///
+/// # Remarks
+///
+/// - The grid has cells of size `epsilon`, so a point only needs to be compared with the points
+///   kept in its own cell and the **26 neighbouring** ones.
+/// - Unlike `dedup_points`, the **order** of the input is preserved: a point is kept unless an
+///   earlier kept point is closer than `epsilon` on every axis.
+///
+/// # Arguments
+///
+/// * `points` - The points to deduplicate.
+/// * `epsilon` - The maximum absolute difference per coordinate for two points to be merged.
+pub fn weld_points_grid(points: &mut Vec<Vec3>, epsilon: f32) {
+    let kept = weld_indices(points, epsilon);
+    let mut index = 0;
+    points.retain(|_| {
+        index += 1;
+        kept[index - 1] == index - 1
+    });
+}
+
+/// Maps each point to the index of the earlier kept point it is welded to, or to its own index when
+/// it is kept, see `weld_points_grid`.
+pub(crate) fn weld_indices(points: &[Vec3], epsilon: f32) -> Vec<usize> {
+    // Any kept point closer than `epsilon` lies in a neighbouring cell, so comparing with every kept
+    // point welds the same way, and spares the grid for the few points of a sliced triangle.
+    if points.len() <= SMALL_WELD {
+        let mut kept: Vec<usize> = Vec::with_capacity(points.len());
+        return (0..points.len())
+            .map(|i| {
+                let welded = kept
+                    .iter()
+                    .copied()
+                    .find(|&k| points[k].abs_diff_eq(points[i], epsilon));
+                welded.unwrap_or_else(|| {
+                    kept.push(i);
+                    i
+                })
+            })
+            .collect();
+    }
+
+    let cell_of = |p: Vec3| (p / epsilon).floor().as_ivec3();
+    // The kept points of each cell are chained: the map holds the last one, `previous` the others.
+    let mut cells: HashMap<IVec3, usize> = HashMap::with_capacity(points.len());
+    let mut previous = vec![usize::MAX; points.len()];
+
+    (0..points.len())
+        .map(|i| {
+            let p = points[i];
+            let cell = cell_of(p);
+            let welded = (-1..=1).find_map(|x| {
+                (-1..=1).find_map(|y| {
+                    (-1..=1).find_map(|z| {
+                        let mut k = *cells.get(&(cell + IVec3::new(x, y, z)))?;
+                        loop {
+                            if points[k].abs_diff_eq(p, epsilon) {
+                                return Some(k);
+                            }
+                            k = previous[k];
+                            if k == usize::MAX {
+                                return None;
+                            }
+                        }
+                    })
+                })
+            });
+            welded.unwrap_or_else(|| {
+                if let Some(last) = cells.insert(cell, i) {
+                    previous[i] = last;
+                }
+                i
+            })
+        })
+        .collect()
+}
+
+/// Computes the convex hull of a set of points with Andrew's monotone chain algorithm.
+///
+/// # Remarks
+///
+/// - Points closer than `EPSILON` on both axes are considered the same point.
+/// - Points lying on an edge of the hull, within `EPSILON`, are **not** hull vertices, so collinear
+///   input yields its two extreme points.
+///
+/// # Arguments
+///
+/// * `points` - The points to enclose, in any order.
+///
+/// # Returns
+///
+/// The vertices of the hull in counter-clockwise order, without repeating the first one.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup_by(|a, b| a.abs_diff_eq(*b, EPSILON));
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let chain = |points: &mut dyn Iterator<Item = &Vec2>| {
+        let mut chain: Vec<Vec2> = Vec::new();
+        for &p in points {
+            while chain.len() >= 2 {
+                let (a, b) = (chain[chain.len() - 2], chain[chain.len() - 1]);
+                if (b - a).perp_dot(p - b) > EPSILON {
+                    break;
+                }
+                chain.pop();
+            }
+            chain.push(p);
+        }
+        // The last point of each chain is the first point of the other one.
+        chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut sorted.iter());
+    hull.extend(chain(&mut sorted.iter().rev()));
+    hull
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `point_in_polygon` function on a convex polygon.
+    #[test]
+    fn test_point_in_polygon_convex() {
+        let square = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+        assert!(point_in_polygon(Vec2::splat(0.5), &square));
+        assert!(!point_in_polygon(Vec2::new(1.5, 0.5), &square));
+        assert!(!point_in_polygon(Vec2::new(-0.5, 0.5), &square));
+
+        // The ray from this point passes exactly through the vertex at (1, 1).
+        let diamond = [
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        assert!(point_in_polygon(Vec2::new(0.5, 1.0), &diamond));
+        assert!(!point_in_polygon(Vec2::new(-0.5, 1.0), &diamond));
+    }
+
+    /// Test for the `point_in_polygon` function on a concave polygon.
+    #[test]
+    fn test_point_in_polygon_concave() {
+        // A U shape opening towards +Y.
+        let u_shape = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(2.0, 3.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ];
+        assert!(point_in_polygon(Vec2::new(0.5, 2.0), &u_shape));
+        assert!(point_in_polygon(Vec2::new(2.5, 2.0), &u_shape));
+        assert!(point_in_polygon(Vec2::new(1.5, 0.5), &u_shape));
+        assert!(!point_in_polygon(Vec2::new(1.5, 2.0), &u_shape));
+    }
+
+    /// Test for the `point_in_polygon` function on the boundary of the polygon.
+    #[test]
+    fn test_point_in_polygon_boundary() {
+        let square = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+        assert!(point_in_polygon(Vec2::new(1.0, 0.5), &square));
+        assert!(point_in_polygon(Vec2::new(0.5, 0.0), &square));
+        assert!(point_in_polygon(Vec2::ONE, &square));
+        assert!(point_in_polygon(
+            Vec2::new(0.0, 0.5 + EPSILON / 2.0),
+            &square
+        ));
+        assert!(!point_in_polygon(Vec2::new(1.0 + 1e-3, 0.5), &square));
+    }
+
+    /// Test for the `dedup_points` function merging near-identical points.
+    #[test]
+    fn test_dedup_points() {
+        let mut points = vec![
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(1.0 + EPSILON / 2.0, 2.0, 3.0),
+            Vec3::new(1.0, 2.0 - EPSILON / 2.0, 3.0 + EPSILON / 4.0),
+        ];
+        dedup_points(&mut points, EPSILON);
+        assert_eq!(points.len(), 1);
+        assert!(points[0].abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), EPSILON));
+
+        let mut points = vec![Vec3::X, Vec3::ZERO, Vec3::X];
+        dedup_points(&mut points, EPSILON);
+        assert_eq!(points, vec![Vec3::ZERO, Vec3::X]);
+
+        // Points on both sides of a cell boundary of `compare_by_xyz` are still merged, even with a
+        // third point sorting between them.
+        let mut points = vec![
+            Vec3::X,
+            Vec3::new(1.0 - 4e-7, 0.0, 0.0),
+            Vec3::new(1.0 - 4e-7, 5.0, 0.0),
+        ];
+        dedup_points(&mut points, EPSILON);
+        assert_eq!(points, vec![Vec3::new(1.0 - 4e-7, 5.0, 0.0), Vec3::X]);
+    }
+
+    /// Test for the `to_planar` and `from_planar` functions round-tripping a point.
+    #[test]
+    fn test_planar_round_trip() {
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(to_planar(p), Vec2::new(1.0, 3.0));
+        assert_eq!(from_planar(to_planar(p), 7.0), Vec3::new(1.0, 7.0, 3.0));
+        assert_eq!(from_planar(to_planar(p), p.y), p);
+    }
+
+    /// Test for the `weld_points_grid` function matching a brute-force weld on random clusters.
+    #[test]
+    fn test_weld_points_grid() {
+        // Keeps a point unless an earlier kept point is within `epsilon` on every axis.
+        let brute_force = |points: &[Vec3], epsilon: f32| {
+            let mut kept: Vec<Vec3> = Vec::new();
+            for &p in points {
+                if !kept.iter().any(|k| k.abs_diff_eq(p, epsilon)) {
+                    kept.push(p);
+                }
+            }
+            kept
+        };
+
+        let mut rng = crate::random::SplitMix64::new(7);
+        let epsilon = 1e-3;
+        let mut points = Vec::new();
+        for _ in 0..500 {
+            let center = Vec3::new(rng.next_signed(), rng.next_signed(), rng.next_signed()) * 100.0;
+            for _ in 0..4 {
+                let jitter = Vec3::new(rng.next_signed(), rng.next_signed(), rng.next_signed());
+                points.push(center + jitter * epsilon / 4.0);
+            }
+        }
+
+        // The first point of each cluster is kept, in the order of the input.
+        let mut welded = points.clone();
+        weld_points_grid(&mut welded, epsilon);
+        assert_eq!(welded, brute_force(&points, epsilon));
+        assert_eq!(
+            welded,
+            points.iter().step_by(4).copied().collect::<Vec<_>>()
+        );
+
+        // Points packed a few epsilons apart chain across cells, the earliest kept ones win.
+        let packed: Vec<Vec3> = (0..2000)
+            .map(|_| Vec3::new(rng.next_signed(), rng.next_signed(), rng.next_signed()) * 0.01)
+            .collect();
+        let mut welded = packed.clone();
+        weld_points_grid(&mut welded, epsilon);
+        let expected = brute_force(&packed, epsilon);
+        assert!(expected.len() > 100 && expected.len() < packed.len());
+        assert_eq!(welded, expected);
+
+        // `dedup_points` keeps the same points, sorted.
+        let mut sorted = packed.clone();
+        dedup_points(&mut sorted, epsilon);
+        let mut expected = expected;
+        expected.sort_by(|a, b| compare_by_xyz(a, b, epsilon));
+        assert_eq!(sorted, expected);
+    }
+
+    /// Test for the `convex_hull` function on a square with interior points.
+    #[test]
+    fn test_convex_hull_square() {
+        let points = [
+            Vec2::new(0.5, 0.5),
+            Vec2::ONE,
+            Vec2::ZERO,
+            Vec2::new(0.25, 0.75),
+            Vec2::Y,
+            Vec2::new(0.5, 0.0),
+            Vec2::X,
+            Vec2::ONE,
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(hull, vec![Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y]);
+    }
+
+    /// Test for the `convex_hull` function on collinear points.
+    #[test]
+    fn test_convex_hull_collinear() {
+        let points = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 2.0 + EPSILON / 4.0),
+        ];
+        assert_eq!(convex_hull(&points), vec![Vec2::ZERO, Vec2::splat(3.0)]);
+        assert_eq!(convex_hull(&[Vec2::X]), vec![Vec2::X]);
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    /// Test for the `segment_intersection` function on crossing and disjoint segments.
+    #[test]
+    fn test_segment_intersection_crossing() {
+        let a = [Vec2::ZERO, Vec2::new(2.0, 2.0)];
+        let b = [Vec2::new(0.0, 2.0), Vec2::new(2.0, 0.0)];
+        assert_eq!(segment_intersection(a, b, EPSILON), Some(Vec2::ONE));
+
+        // The lines cross, but beyond the end of the second segment.
+        let short = [Vec2::new(0.0, 2.0), Vec2::new(0.5, 1.5)];
+        assert_eq!(segment_intersection(a, short, EPSILON), None);
+        // Parallel lines never meet.
+        let parallel = [Vec2::X, Vec2::new(3.0, 2.0)];
+        assert_eq!(segment_intersection(a, parallel, EPSILON), None);
+        // Nearly parallel segments crossing far away from both.
+        let nearly = [Vec2::new(0.0, 1e-3), Vec2::new(2.0, 2.0 + 2e-3)];
+        assert_eq!(segment_intersection(a, nearly, EPSILON), None);
+    }
+
+    /// Test for the `segment_intersection` function on touching and collinear segments.
+    #[test]
+    fn test_segment_intersection_touching() {
+        let a = [Vec2::ZERO, Vec2::new(2.0, 0.0)];
+
+        // A shared endpoint, and an endpoint lying on the other segment, within the tolerance.
+        let shared = [Vec2::new(2.0, EPSILON / 2.0), Vec2::new(3.0, 1.0)];
+        assert_eq!(
+            segment_intersection(a, shared, EPSILON),
+            Some(Vec2::new(2.0, 0.0))
+        );
+        let tee = [Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0)];
+        assert_eq!(segment_intersection(a, tee, EPSILON), Some(Vec2::X));
+        assert_eq!(segment_intersection(tee, a, EPSILON), Some(Vec2::X));
+
+        // Collinear segments meeting end to end touch at a single point.
+        let next = [Vec2::new(2.0, 0.0), Vec2::new(4.0, 0.0)];
+        assert_eq!(
+            segment_intersection(a, next, EPSILON),
+            Some(Vec2::new(2.0, 0.0))
+        );
+        // Collinear segments overlapping along a segment have no single intersection point.
+        let overlapping = [Vec2::X, Vec2::new(3.0, 0.0)];
+        assert_eq!(segment_intersection(a, overlapping, EPSILON), None);
+        // Collinear segments with a gap between them.
+        let apart = [Vec2::new(3.0, 0.0), Vec2::new(4.0, 0.0)];
+        assert_eq!(segment_intersection(a, apart, EPSILON), None);
+    }
+
+    /// Test for the `signed_area` and `ensure_winding` functions on a clockwise square.
+    #[test]
+    fn test_ensure_winding() {
+        let mut square = vec![
+            Vec2::ZERO,
+            Vec2::new(0.0, 2.0),
+            Vec2::splat(2.0),
+            Vec2::new(2.0, 0.0),
+        ];
+        assert_eq!(signed_area(&square), -4.0);
+
+        ensure_winding(&mut square, false);
+        assert_eq!(signed_area(&square), -4.0);
+        ensure_winding(&mut square, true);
+        assert_eq!(signed_area(&square), 4.0);
+        assert_eq!(square[0], Vec2::new(2.0, 0.0));
+
+        let mut flat = vec![Vec2::ZERO, Vec2::X, Vec2::new(2.0, 0.0)];
+        ensure_winding(&mut flat, true);
+        assert_eq!(flat, vec![Vec2::ZERO, Vec2::X, Vec2::new(2.0, 0.0)]);
+    }
+}
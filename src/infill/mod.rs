@@ -0,0 +1,894 @@
+use glam::f32::{Vec2, Vec3};
+
+use crate::clip;
+use crate::geometry::{distance_to_segment, from_planar, point_in_polygon, signed_area, to_planar};
+use crate::perimeter::offset_region;
+use crate::slicer::EPSILON;
+
+/// The largest distance a gap fill centerline may stray from the midpoints it is simplified from.
+const GAP_FILL_TOLERANCE: f32 = 0.01;
+
+/// The number of grid cells per `spacing` on which the gyroid cross-section is traced.
+const GYROID_RESOLUTION: f32 = 4.0;
+
+/// Generates rectilinear infill: parallel lines clipped to the inside of a layer's closed contours.
+///
+/// # Remarks
+///
+/// - The region is defined by the **even-odd rule**: a point is inside if it is enclosed by an odd
+///   number of contours, so holes are skipped without caring about the contours' winding.
+/// - The lines are spaced from the **origin** rather than from the region, so the infill of
+///   consecutive layers lines up.
+/// - Contours may or may not repeat their first point at the end.
+///
+/// # Arguments
+///
+/// * `contour` - The closed contours of the layer, all lying at the same height.
+/// * `spacing` - The distance between two neighbouring lines.
+/// * `angle_deg` - The direction of the lines in degrees, counter-clockwise from the X axis in the XZ plane.
+///
+/// # Returns
+///
+/// The extrusion segments of the infill, each fully inside the region.
+pub fn rectilinear(contour: &[Vec<Vec3>], spacing: f32, angle_deg: f32) -> Vec<[Vec3; 2]> {
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let Some(height) = contour.iter().flatten().map(|p| p.y).next() else {
+        return Vec::new();
+    };
+
+    // Rotate the region so the lines run along the u axis, one per value of v.
+    let direction = Vec2::from_angle(angle_deg.to_radians());
+    let inverse = Vec2::new(direction.x, -direction.y);
+    let polygons: Vec<Vec<Vec2>> = contour
+        .iter()
+        .map(|c| c.iter().map(|&p| inverse.rotate(to_planar(p))).collect())
+        .collect();
+
+    scanline_segments(&polygons, spacing)
+        .into_iter()
+        .map(|(k, u)| {
+            let v = k as f32 * spacing;
+            u.map(|u| from_planar(direction.rotate(Vec2::new(u, v)), height))
+        })
+        .collect()
+}
+
+/// Generates grid infill: lines along both the X and Z axes on the same layer, crossing into squares.
+///
+/// # Remarks
+///
+/// - The region is defined by the **even-odd rule**, like in `rectilinear`.
+/// - Both sets of lines are spaced from the **origin**, so they form a clean grid of squares that
+///   lines up across layers.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region to fill, without repeating their first points.
+/// * `spacing` - The distance between two neighbouring parallel lines.
+///
+/// # Returns
+///
+/// The extrusion segments of the infill, the lines along X first, each fully inside the region.
+pub fn grid(region: &[Vec<Vec2>], spacing: f32) -> Vec<[Vec2; 2]> {
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let along_x = scanline_segments(region, spacing)
+        .into_iter()
+        .map(|(k, u)| u.map(|u| Vec2::new(u, k as f32 * spacing)));
+    // Swapping the axes runs the scanlines along the second axis instead.
+    let swapped: Vec<Vec<Vec2>> = region
+        .iter()
+        .map(|polygon| polygon.iter().map(|p| Vec2::new(p.y, p.x)).collect())
+        .collect();
+    let along_y = scanline_segments(&swapped, spacing)
+        .into_iter()
+        .map(|(k, u)| u.map(|u| Vec2::new(k as f32 * spacing, u)));
+    along_x.chain(along_y).collect()
+}
+
+/// Generates monotonic infill: rectilinear lines all printed in the same direction, in an order
+/// sweeping across the region, for smooth top surfaces.
+///
+/// # Remarks
+///
+/// - The region is defined by the **even-odd rule**, like in `rectilinear`, and the lines are spaced
+///   from the origin the same way.
+/// - Every line is printed **in the direction of the lines**, i.e. along `angle_deg`, so neighbouring
+///   lines never overlap in opposite directions.
+/// - A line is only printed once all the lines of the previous scanline that it touches are, so the
+///   fill always advances as a single front along the perpendicular axis. Among the lines allowed,
+///   the one starting **nearest** to the end of the last line is printed next, so a region split
+///   in several branches is swept branch by branch rather than jumping between them.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region to fill, without repeating their first points.
+/// * `spacing` - The distance between two neighbouring lines.
+/// * `angle_deg` - The direction of the lines in degrees, counter-clockwise from the X axis.
+///
+/// # Returns
+///
+/// The extrusion segments of the infill in printing order, each from its start to its end.
+pub fn monotonic(region: &[Vec<Vec2>], spacing: f32, angle_deg: f32) -> Vec<[Vec2; 2]> {
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let direction = Vec2::from_angle(angle_deg.to_radians());
+    let inverse = Vec2::new(direction.x, -direction.y);
+    let polygons: Vec<Vec<Vec2>> = region
+        .iter()
+        .map(|polygon| polygon.iter().map(|&p| inverse.rotate(p)).collect())
+        .collect();
+
+    let segments = scanline_segments(&polygons, spacing);
+
+    // Count the lines of the previous scanline each line touches. The lines of a scanline are
+    // contiguous and sorted along it, so the first one touched is found by a binary search.
+    let mut blockers = vec![0_usize; segments.len()];
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); segments.len()];
+    let (mut row, mut previous) = (0, 0..0);
+    for i in 0..segments.len() {
+        let (k, u) = segments[i];
+        if i > 0 && segments[i - 1].0 != k {
+            previous = if segments[i - 1].0 == k - 1 {
+                row..i
+            } else {
+                i..i
+            };
+            row = i;
+        }
+        let first =
+            previous.start + segments[previous.clone()].partition_point(|&(_, w)| w[1] <= u[0]);
+        for j in (first..previous.end).take_while(|&j| segments[j].1[0] < u[1]) {
+            successors[j].push(i);
+            blockers[i] += 1;
+        }
+    }
+
+    let start = |i: usize| {
+        let (k, u) = segments[i];
+        Vec2::new(u[0], k as f32 * spacing)
+    };
+    let mut ready: Vec<usize> = (0..segments.len()).filter(|&i| blockers[i] == 0).collect();
+    let mut lines = Vec::with_capacity(segments.len());
+    let mut position = None;
+    while !ready.is_empty() {
+        let pick = match position {
+            None => (0..ready.len()).min_by_key(|&r| (segments[ready[r]].0, ready[r])),
+            Some(position) => (0..ready.len()).min_by(|&a, &b| {
+                let distance = |r: usize| start(ready[r]).distance(position);
+                distance(a)
+                    .total_cmp(&distance(b))
+                    .then(ready[a].cmp(&ready[b]))
+            }),
+        }
+        .unwrap();
+        let next = ready.swap_remove(pick);
+        for &successor in &successors[next] {
+            blockers[successor] -= 1;
+            if blockers[successor] == 0 {
+                ready.push(successor);
+            }
+        }
+
+        let (k, u) = segments[next];
+        let [a, b] = u.map(|u| Vec2::new(u, k as f32 * spacing));
+        position = Some(b);
+        lines.push([direction.rotate(a), direction.rotate(b)]);
+    }
+
+    lines
+}
+
+/// Generates concentric infill: nested rings following the boundary of a region towards its middle.
+///
+/// # Remarks
+///
+/// - The region is made of **outer contours** wound counter-clockwise and **holes** wound clockwise,
+///   as produced by the `clip` module.
+/// - The rings are centerlines: the first one is inset by half a `spacing` and each following one by
+///   a full `spacing` more, until nothing is left of the region.
+/// - A ring may be made of several loops when the region narrows, they are returned one after the
+///   other, outermost ring first.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region to fill, without repeating their first points.
+/// * `spacing` - The distance between two neighbouring rings.
+///
+/// # Returns
+///
+/// The loops of every ring, from the outermost to the innermost.
+pub fn concentric(region: &[Vec<Vec2>], spacing: f32) -> Vec<Vec<Vec2>> {
+    let mut rings = Vec::new();
+    if spacing <= 0.0 {
+        return rings;
+    }
+
+    for ring in 0.. {
+        let loops = offset_region(region, spacing * (ring as f32 + 0.5));
+        if loops.is_empty() {
+            break;
+        }
+        rings.extend(loops);
+    }
+    rings
+}
+
+/// Generates honeycomb infill: the edges of a hexagonal grid clipped to the inside of a region.
+///
+/// # Remarks
+///
+/// - The region is defined by the **even-odd rule**, like in `rectilinear`.
+/// - The hexagons have their points up and sides of length `cell_size`. The grid is anchored at the
+///   **origin** rather than at the region, so the infill of consecutive layers lines up.
+/// - Each edge shared by two cells is generated **once**, so no line is extruded twice.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region to fill, without repeating their first points.
+/// * `cell_size` - The length of a side of a hexagon.
+///
+/// # Returns
+///
+/// The extrusion segments of the infill, each fully inside the region.
+pub fn honeycomb(region: &[Vec<Vec2>], cell_size: f32) -> Vec<[Vec2; 2]> {
+    let Some(&first) = region.iter().flatten().next() else {
+        return Vec::new();
+    };
+    if cell_size <= 0.0 {
+        return Vec::new();
+    }
+    let (min, max) = region
+        .iter()
+        .flatten()
+        .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+
+    let column_spacing = 3.0_f32.sqrt() * cell_size;
+    let row_spacing = 1.5 * cell_size;
+    let corner = |center: Vec2, k: i32| {
+        center + Vec2::from_angle((30.0 + 60.0 * k as f32).to_radians()) * cell_size
+    };
+
+    let mut lines = Vec::new();
+    // One cell of margin on each side, so the edges owned by cells outside the box are generated too.
+    let rows = (min.y / row_spacing).floor() as i64 - 1..=(max.y / row_spacing).ceil() as i64 + 1;
+    for row in rows {
+        let shift = if row.rem_euclid(2) == 1 { 0.5 } else { 0.0 };
+        let columns = (min.x / column_spacing - shift).floor() as i64 - 1
+            ..=(max.x / column_spacing - shift).ceil() as i64 + 1;
+        for column in columns {
+            let center = Vec2::new(
+                (column as f32 + shift) * column_spacing,
+                row as f32 * row_spacing,
+            );
+            // Each cell owns its right, upper right and upper left edges, the other three belong to
+            // its neighbours.
+            for k in -1..2 {
+                clip_segment(corner(center, k), corner(center, k + 1), region, &mut lines);
+            }
+        }
+    }
+
+    lines
+}
+
+/// Generates gyroid infill: the cross-section of a gyroid surface at the height of the layer.
+///
+/// # Remarks
+///
+/// - The region is defined by the **even-odd rule**, like in `rectilinear`.
+/// - The gyroid is the surface `sin(ku) cos(kv) + sin(kv) cos(kz) + sin(kz) cos(ku) = 0`, scaled so
+///   that its walls are about `spacing` apart. Its cross-section is a set of wavy lines whose shape
+///   and direction **drift with `layer_z`**, so consecutive layers interlock into a 3D lattice.
+/// - The curves are traced with marching squares on a grid of `spacing / 4` cells anchored at the
+///   **origin**, so the infill of consecutive layers lines up. Each traced piece is a short straight
+///   segment, clipped to the region.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region to fill, without repeating their first points.
+/// * `spacing` - The distance between two neighbouring walls of the gyroid.
+/// * `layer_z` - The height of the layer along the build axis.
+///
+/// # Returns
+///
+/// The extrusion segments of the infill, each fully inside the region.
+pub fn gyroid(region: &[Vec<Vec2>], spacing: f32, layer_z: f32) -> Vec<[Vec2; 2]> {
+    let Some(&first) = region.iter().flatten().next() else {
+        return Vec::new();
+    };
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let (min, max) = region
+        .iter()
+        .flatten()
+        .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+
+    let k = std::f32::consts::PI / spacing;
+    let (sin_z, cos_z) = (k * layer_z).sin_cos();
+    let field = |p: Vec2| {
+        (k * p.x).sin() * (k * p.y).cos() + (k * p.y).sin() * cos_z + sin_z * (k * p.x).cos()
+    };
+
+    let step = spacing / GYROID_RESOLUTION;
+    let (i_min, i_max) = ((min.x / step).floor() as i64, (max.x / step).ceil() as i64);
+    let (j_min, j_max) = ((min.y / step).floor() as i64, (max.y / step).ceil() as i64);
+    let node = |i: i64, j: i64| Vec2::new(i as f32 * step, j as f32 * step);
+
+    let mut lines = Vec::new();
+    for i in i_min..i_max {
+        for j in j_min..j_max {
+            let corners = [
+                node(i, j),
+                node(i + 1, j),
+                node(i + 1, j + 1),
+                node(i, j + 1),
+            ];
+            let values = corners.map(field);
+            // The point where the surface crosses each edge of the cell, if it does.
+            let crossings: [Option<Vec2>; 4] = std::array::from_fn(|edge| {
+                let (a, b) = (edge, (edge + 1) % 4);
+                ((values[a] > 0.0) != (values[b] > 0.0)).then(|| {
+                    let t = values[a] / (values[a] - values[b]);
+                    corners[a].lerp(corners[b], t)
+                })
+            });
+
+            let pairs = match crossings.iter().flatten().count() {
+                2 => {
+                    let mut points = crossings.iter().flatten();
+                    vec![(*points.next().unwrap(), *points.next().unwrap())]
+                }
+                // A saddle: the value at the center tells which opposite corners are connected.
+                4 => {
+                    let c = crossings.map(Option::unwrap);
+                    let center = values.iter().sum::<f32>() / 4.0;
+                    if (center > 0.0) == (values[0] > 0.0) {
+                        vec![(c[0], c[1]), (c[2], c[3])]
+                    } else {
+                        vec![(c[3], c[0]), (c[1], c[2])]
+                    }
+                }
+                _ => Vec::new(),
+            };
+            for (a, b) in pairs {
+                clip_segment(a, b, region, &mut lines);
+            }
+        }
+    }
+
+    lines
+}
+
+/// Generates gap fill: single centerlines along the parts of a region too narrow for other infill.
+///
+/// # Remarks
+///
+/// - The region is made of **outer contours** wound counter-clockwise and **holes** wound clockwise,
+///   as produced by the `clip` module.
+/// - The thin areas are what the region loses when it is shrunk by half of `max_width` and grown
+///   back, i.e. the parts where a line of `max_width` does not fit. Areas narrower than `min_width`
+///   are too thin to be extruded and are skipped.
+/// - Each thin area is scanned **across its longest axis**, and the midpoints of the scanlines are
+///   joined into a centerline. The local width of the gap varies along the centerline, between
+///   `min_width` and `max_width`, and is meant to be matched by the extrusion.
+/// - The ends of a centerline stop half a width away from the end of the gap, so the extruded line
+///   does not overflow it.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region left inside the perimeters, without repeating their first
+///   points.
+/// * `min_width` - The narrowest gap worth filling.
+/// * `max_width` - The widest gap to fill, usually the width of a regular extruded line.
+///
+/// # Returns
+///
+/// The extrusion segments of the gap fill, each lying along the middle of a gap.
+pub fn gap_fill(region: &[Vec<Vec2>], min_width: f32, max_width: f32) -> Vec<[Vec2; 2]> {
+    if min_width <= 0.0 || max_width < min_width {
+        return Vec::new();
+    }
+    let opened = offset_region(&offset_region(region, max_width / 2.0), -max_width / 2.0);
+    let thin = clip::difference(region, &opened);
+
+    let mut lines = Vec::new();
+    for piece in thin.iter().filter(|piece| signed_area(piece) > 0.0) {
+        if offset_region(std::slice::from_ref(piece), min_width / 2.0).is_empty() {
+            continue;
+        }
+
+        // Rotate the piece so its longest axis runs along v, and scan it one value of v at a time.
+        let rotation = Vec2::Y.rotate(principal_axis(piece) * Vec2::new(1.0, -1.0));
+        let inverse = rotation * Vec2::new(1.0, -1.0);
+        let polygon: Vec<Vec2> = piece.iter().map(|&p| rotation.rotate(p)).collect();
+        let (v_min, v_max) = polygon
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+                (min.min(p.y), max.max(p.y))
+            });
+        let count = ((v_max - v_min) / (min_width / 2.0)).ceil().max(1.0) as usize;
+        let step = (v_max - v_min) / count as f32;
+
+        let mut run: Vec<(Vec2, f32)> = Vec::new();
+        for k in 0..=count {
+            let mut crossings = if k < count {
+                let v = v_min + (k as f32 + 0.5) * step;
+                scanline_crossings(std::slice::from_ref(&polygon), v)
+                    .into_iter()
+                    .map(|u| Vec2::new(u, v))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            crossings.sort_by(|a: &Vec2, b| a.x.total_cmp(&b.x));
+
+            if let [a, b] = crossings[..] {
+                let width = b.x - a.x;
+                if width > EPSILON && width <= max_width + EPSILON {
+                    run.push(((a + b) / 2.0, width));
+                    continue;
+                }
+            }
+
+            if run.len() >= 2 {
+                // Ends at the first or last scanline face an end of the gap, the others a junction.
+                let mut path: Vec<Vec2> = run.iter().map(|&(p, _)| p).collect();
+                if run.len() == k {
+                    trim_start(&mut path, v_min + run[0].1 / 2.0, |p| p.y);
+                }
+                if k == count {
+                    path.reverse();
+                    trim_start(&mut path, run[run.len() - 1].1 / 2.0 - v_max, |p| -p.y);
+                    path.reverse();
+                }
+                if path[0].y < path[path.len() - 1].y {
+                    let path = simplify(&path, GAP_FILL_TOLERANCE);
+                    lines.extend(
+                        path.windows(2)
+                            .map(|pair| [inverse.rotate(pair[0]), inverse.rotate(pair[1])]),
+                    );
+                }
+            }
+            run.clear();
+        }
+    }
+
+    lines
+}
+
+/// Appends the pieces of the segment from `a` to `b` lying inside the region to `lines`.
+fn clip_segment(a: Vec2, b: Vec2, region: &[Vec<Vec2>], lines: &mut Vec<[Vec2; 2]>) {
+    let direction = b - a;
+    let mut cuts = vec![0.0, 1.0];
+    for polygon in region {
+        for (i, &p) in polygon.iter().enumerate() {
+            let side = polygon[(i + 1) % polygon.len()] - p;
+            let denominator = direction.perp_dot(side);
+            if denominator.abs() <= f32::EPSILON * direction.length() * side.length() {
+                continue;
+            }
+            let t = (p - a).perp_dot(side) / denominator;
+            let u = (p - a).perp_dot(direction) / denominator;
+            if t > 0.0 && t < 1.0 && (0.0..=1.0).contains(&u) {
+                cuts.push(t);
+            }
+        }
+    }
+    cuts.sort_by(f32::total_cmp);
+
+    let inside = |p: Vec2| {
+        region
+            .iter()
+            .filter(|polygon| point_in_polygon(p, polygon))
+            .count()
+            % 2
+            == 1
+    };
+    let mut start: Option<f32> = None;
+    for pair in cuts.windows(2) {
+        let is_inside = pair[1] > pair[0] && inside(a + direction * (pair[0] + pair[1]) / 2.0);
+        match (start, is_inside) {
+            (None, true) => start = Some(pair[0]),
+            (Some(from), false) if pair[1] > pair[0] => {
+                lines.push([a + direction * from, a + direction * pair[0]]);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        lines.push([a + direction * from, b]);
+    }
+}
+
+/// Computes the pieces of the horizontal lines `y = k * spacing` lying inside the polygons, by the
+/// even-odd rule, as the index `k` of their line and their u range. The pieces are ordered by line,
+/// then by u.
+fn scanline_segments(polygons: &[Vec<Vec2>], spacing: f32) -> Vec<(i64, [f32; 2])> {
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let (v_min, v_max) = polygons
+        .iter()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+            (min.min(p.y), max.max(p.y))
+        });
+
+    let mut segments = Vec::new();
+    let first = (v_min / spacing).ceil() as i64;
+    let last = (v_max / spacing).floor() as i64;
+    for k in first..=last {
+        let mut crossings = scanline_crossings(polygons, k as f32 * spacing);
+        crossings.sort_by(f32::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            if pair[1] - pair[0] > f32::EPSILON {
+                segments.push((k, [pair[0], pair[1]]));
+            }
+        }
+    }
+    segments
+}
+
+/// Computes the u coordinates where the horizontal line `y = v` crosses the edges of the polygons.
+///
+/// Each edge is treated as half-open along v, so a line passing exactly through a vertex counts
+/// the crossing once and the number of crossings is always even.
+fn scanline_crossings(polygons: &[Vec<Vec2>], v: f32) -> Vec<f32> {
+    let mut crossings = Vec::new();
+    for polygon in polygons {
+        for (i, &a) in polygon.iter().enumerate() {
+            let b = polygon[(i + 1) % polygon.len()];
+            if (a.y <= v) != (b.y <= v) {
+                let t = (v - a.y) / (b.y - a.y);
+                crossings.push(a.x + (b.x - a.x) * t);
+            }
+        }
+    }
+    crossings
+}
+
+/// Computes the direction of the longest axis of a polygon, from the covariance of its vertices.
+fn principal_axis(polygon: &[Vec2]) -> Vec2 {
+    let mean = polygon.iter().sum::<Vec2>() / polygon.len() as f32;
+    let (xx, xy, yy) = polygon.iter().fold((0.0, 0.0, 0.0), |(xx, xy, yy), &p| {
+        let d = p - mean;
+        (xx + d.x * d.x, xy + d.x * d.y, yy + d.y * d.y)
+    });
+    Vec2::from_angle(0.5 * (2.0 * xy).atan2(xx - yy))
+}
+
+/// Moves the start of a path to the position `v` along it, measured by `along`, dropping the points
+/// before it or extending the first segment to reach it.
+fn trim_start(path: &mut Vec<Vec2>, v: f32, along: impl Fn(Vec2) -> f32) {
+    while path.len() > 2 && along(path[1]) <= v {
+        path.remove(0);
+    }
+    let (a, b) = (path[0], path[1]);
+    let length = along(b) - along(a);
+    if length > EPSILON {
+        path[0] = a + (b - a) * (v - along(a)) / length;
+    }
+}
+
+/// Removes the points of a path lying within `tolerance` of the straight line joining their
+/// neighbours, keeping its ends.
+fn simplify(path: &[Vec2], tolerance: f32) -> Vec<Vec2> {
+    let mut simplified = vec![path[0]];
+    let mut anchor = 0;
+    for end in 2..path.len() {
+        let straight = path[anchor + 1..end]
+            .iter()
+            .all(|&p| distance_to_segment(p, path[anchor], path[end]) <= tolerance);
+        if !straight {
+            anchor = end - 1;
+            simplified.push(path[anchor]);
+        }
+    }
+    simplified.push(path[path.len() - 1]);
+    simplified
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Builds a closed square contour in the XZ plane.
+    #[allow(dead_code)]
+    fn square(min: f32, max: f32) -> Vec<Vec3> {
+        vec![
+            Vec3::new(min, 0.0, min),
+            Vec3::new(max, 0.0, min),
+            Vec3::new(max, 0.0, max),
+            Vec3::new(min, 0.0, max),
+        ]
+    }
+
+    /// Test for the `rectilinear` function skipping the hole of a square region.
+    #[test]
+    fn test_rectilinear_square_with_hole() {
+        let region = [square(0.0, 10.0), square(4.0, 6.0)];
+        let lines = rectilinear(&region, 1.0, 0.0);
+
+        let inside_hole = |p: Vec3| p.x > 4.0 && p.x < 6.0 && p.z > 4.0 && p.z < 6.0;
+        for [a, b] in &lines {
+            assert!((a.z - b.z).abs() < 1e-5);
+            assert!(!inside_hole((*a + *b) / 2.0));
+        }
+
+        // Lines at z = 5 are split in two by the hole, the others span the whole square.
+        let at_five: Vec<_> = lines
+            .iter()
+            .filter(|l| (l[0].z - 5.0).abs() < 1e-5)
+            .collect();
+        assert_eq!(at_five.len(), 2);
+        let at_two: Vec<_> = lines
+            .iter()
+            .filter(|l| (l[0].z - 2.0).abs() < 1e-5)
+            .collect();
+        assert_eq!(at_two.len(), 1);
+        assert!((at_two[0][0].distance(at_two[0][1]) - 10.0).abs() < 1e-4);
+    }
+
+    /// Test for the `rectilinear` function with lines rotated by 90 degrees.
+    #[test]
+    fn test_rectilinear_rotated() {
+        let lines = rectilinear(&[square(0.0, 10.0)], 2.0, 90.0);
+        assert!(!lines.is_empty());
+        for [a, b] in &lines {
+            assert!((a.x - b.x).abs() < 1e-4);
+            assert!((a.distance(*b) - 10.0).abs() < 1e-4);
+        }
+        assert!(rectilinear(&[], 1.0, 0.0).is_empty());
+
+        // A null spacing would need infinitely many lines, the region spans the origin.
+        let centered = [square(-5.0, 10.0)];
+        assert!(rectilinear(&centered, 0.0, 0.0).is_empty());
+        assert!(rectilinear(&centered, -1.0, 0.0).is_empty());
+    }
+
+    /// Test for the `concentric` function filling a circle with rings one spacing apart.
+    #[test]
+    fn test_concentric_circle() {
+        let circle: Vec<Vec2> = (0..64)
+            .map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU / 64.0) * 10.0)
+            .collect();
+        let rings = concentric(&[circle], 1.0);
+        assert_eq!(rings.len(), 10);
+
+        let radii: Vec<f32> = rings
+            .iter()
+            .map(|ring| ring.iter().map(|p| p.length()).sum::<f32>() / ring.len() as f32)
+            .collect();
+        assert!((radii[0] - 9.5).abs() < 0.05);
+        for pair in radii.windows(2) {
+            assert!((pair[0] - pair[1] - 1.0).abs() < 0.05);
+        }
+    }
+
+    /// Test for the `honeycomb` function keeping every line inside a square region.
+    #[test]
+    fn test_honeycomb_square() {
+        let region = [vec![
+            Vec2::ZERO,
+            Vec2::new(20.0, 0.0),
+            Vec2::new(20.0, 20.0),
+            Vec2::new(0.0, 20.0),
+        ]];
+        let lines = honeycomb(&region, 2.0);
+        assert!(lines.len() > 100);
+
+        for [a, b] in &lines {
+            for p in [*a, *b, (*a + *b) / 2.0] {
+                assert!(
+                    p.cmpge(Vec2::splat(-1e-4)).all() && p.cmple(Vec2::splat(20.0 + 1e-4)).all()
+                );
+            }
+            assert!(a.distance(*b) <= 2.0 + 1e-4);
+        }
+
+        // No edge is generated twice, in either direction.
+        for (i, [a, b]) in lines.iter().enumerate() {
+            let midpoint = (*a + *b) / 2.0;
+            assert!(!lines[i + 1..]
+                .iter()
+                .any(|[c, d]| ((*c + *d) / 2.0).abs_diff_eq(midpoint, 1e-3)));
+        }
+
+        // A full cell in the middle of the region has all of its six sides.
+        let center = Vec2::new(3.0_f32.sqrt() * 2.0 * 3.0, 6.0 * 2.0);
+        let sides = lines
+            .iter()
+            .filter(|[a, b]| ((*a + *b) / 2.0).distance(center) < 2.0)
+            .count();
+        assert_eq!(sides, 6);
+    }
+
+    /// Test for the `grid` function crossing lines along both axes inside a square with a hole.
+    #[test]
+    fn test_grid_square() {
+        let region = [
+            vec![
+                Vec2::new(0.5, 0.5),
+                Vec2::new(10.5, 0.5),
+                Vec2::new(10.5, 10.5),
+                Vec2::new(0.5, 10.5),
+            ],
+            vec![
+                Vec2::new(4.5, 4.5),
+                Vec2::new(4.5, 6.5),
+                Vec2::new(6.5, 6.5),
+                Vec2::new(6.5, 4.5),
+            ],
+        ];
+        let lines = grid(&region, 2.0);
+        let horizontal: Vec<_> = lines.iter().filter(|[a, b]| a.y == b.y).collect();
+        let vertical: Vec<_> = lines.iter().filter(|[a, b]| a.x == b.x).collect();
+        assert_eq!(horizontal.len() + vertical.len(), lines.len());
+        // 5 lines along each axis, the one through the hole split in two.
+        assert_eq!(horizontal.len(), 6);
+        assert_eq!(vertical.len(), 6);
+
+        // Both sets sit on multiples of the spacing, so they cross on a common grid.
+        assert!(horizontal.iter().all(|[a, _]| a.y % 2.0 == 0.0));
+        assert!(vertical.iter().all(|[a, _]| a.x % 2.0 == 0.0));
+        for [a, b] in &lines {
+            let middle = (*a + *b) / 2.0;
+            assert!(middle.cmpgt(Vec2::splat(0.5)).all() && middle.cmplt(Vec2::splat(10.5)).all());
+            assert!(!point_in_polygon(middle, &region[1]));
+        }
+    }
+
+    /// Test for the `monotonic` function sweeping a U-shaped region in a single direction.
+    #[test]
+    fn test_monotonic_u_shape() {
+        let region = [vec![
+            Vec2::new(0.5, 0.5),
+            Vec2::new(10.5, 0.5),
+            Vec2::new(10.5, 10.5),
+            Vec2::new(7.5, 10.5),
+            Vec2::new(7.5, 4.5),
+            Vec2::new(3.5, 4.5),
+            Vec2::new(3.5, 10.5),
+            Vec2::new(0.5, 10.5),
+        ]];
+        let lines = monotonic(&region, 1.0, 0.0);
+        // 4 lines across the base and 6 in each arm.
+        assert_eq!(lines.len(), 16);
+        assert!(lines.iter().all(|[a, b]| b.x > a.x && a.y == b.y));
+
+        // Every line comes after the lines of the previous scanline it lies on.
+        for (i, [a, b]) in lines.iter().enumerate() {
+            assert!(!lines[i + 1..]
+                .iter()
+                .any(|[c, d]| c.y == a.y - 1.0 && c.x < b.x && a.x < d.x));
+        }
+
+        // Each arm is finished before the other is started.
+        let arms: Vec<bool> = lines
+            .iter()
+            .filter(|[a, _]| a.y > 4.5)
+            .map(|[a, _]| a.x > 5.0)
+            .collect();
+        assert_eq!(arms.windows(2).filter(|w| w[0] != w[1]).count(), 1);
+
+        // Rotating the lines rotates their direction too.
+        let rotated = monotonic(&region, 1.0, 90.0);
+        assert!(rotated
+            .iter()
+            .all(|[a, b]| b.y > a.y && (a.x - b.x).abs() < 1e-5));
+    }
+
+    /// Test for the `monotonic` function on a top surface perforated by many small holes.
+    #[test]
+    fn test_monotonic_perforated() {
+        let mut region = vec![vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(80.0, 0.0),
+            Vec2::new(80.0, 80.0),
+            Vec2::new(0.0, 80.0),
+        ]];
+        for i in 0..40 {
+            for j in 0..40 {
+                let corner = Vec2::new(i as f32, j as f32) * 2.0 + 0.5;
+                region.push(vec![
+                    corner,
+                    corner + Vec2::new(0.0, 1.0),
+                    corner + Vec2::new(1.0, 1.0),
+                    corner + Vec2::new(1.0, 0.0),
+                ]);
+            }
+        }
+
+        let lines = monotonic(&region, 0.25, 0.0);
+        assert_eq!(lines.len(), scanline_segments(&region, 0.25).len());
+        assert!(lines.len() > 5000);
+        assert!(lines.iter().all(|[a, b]| b.x > a.x && a.y == b.y));
+    }
+
+    /// Test for the `gap_fill` function filling a narrow slot with a single centerline.
+    #[test]
+    fn test_gap_fill_slot() {
+        let slot = vec![vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 0.6),
+            Vec2::new(0.0, 0.6),
+        ]];
+        let lines = gap_fill(&slot, 0.2, 1.0);
+        assert_eq!(lines.len(), 1);
+        let [a, b] = lines[0];
+        let (start, end) = if a.x < b.x { (a, b) } else { (b, a) };
+        assert!(start.abs_diff_eq(Vec2::new(0.3, 0.3), 1e-3));
+        assert!(end.abs_diff_eq(Vec2::new(9.7, 0.3), 1e-3));
+
+        // A slanted slot is followed along its own axis.
+        let direction = Vec2::from_angle(0.5);
+        let slanted: Vec<Vec<Vec2>> = slot
+            .iter()
+            .map(|c| c.iter().map(|&p| direction.rotate(p)).collect())
+            .collect();
+        let lines = gap_fill(&slanted, 0.2, 1.0);
+        assert_eq!(lines.len(), 1);
+        for p in lines[0] {
+            let local = (direction * Vec2::new(1.0, -1.0)).rotate(p);
+            assert!((local.y - 0.3).abs() < 1e-3);
+        }
+
+        // Wide regions and gaps narrower than the minimum width are left alone.
+        let square = vec![vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::splat(10.0),
+            Vec2::new(0.0, 10.0),
+        ]];
+        assert!(gap_fill(&square, 0.2, 1.0).is_empty());
+        assert!(gap_fill(&slot, 0.8, 1.0).is_empty());
+    }
+
+    /// Test for the `gyroid` function changing its pattern with the height of the layer.
+    #[test]
+    fn test_gyroid_square() {
+        let region = [vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ]];
+        let bottom = gyroid(&region, 2.0, 0.0);
+        let top = gyroid(&region, 2.0, 1.0);
+        assert!(bottom.len() > 50);
+
+        for [a, b] in bottom.iter().chain(&top) {
+            for p in [*a, *b] {
+                assert!(
+                    p.cmpge(Vec2::splat(-1e-4)).all() && p.cmple(Vec2::splat(10.0 + 1e-4)).all()
+                );
+            }
+            assert!(a.distance(*b) <= 2.0_f32.sqrt() * 0.5 + 1e-4);
+        }
+
+        // At z = 0 the cross-section is `sin(ku) cos(kv) + sin(kv) = 0`, which holds on the lines
+        // v = 0 and v = spacing where both terms vanish.
+        assert!(bottom
+            .iter()
+            .any(|[a, b]| a.y.abs() < 1e-4 && b.y.abs() < 1e-4));
+        // A quarter period higher, the pattern has moved.
+        let midpoints = |lines: &[[Vec2; 2]]| -> Vec<Vec2> {
+            lines.iter().map(|[a, b]| (*a + *b) / 2.0).collect()
+        };
+        let (bottom, top) = (midpoints(&bottom), midpoints(&top));
+        let shared = top
+            .iter()
+            .filter(|p| bottom.iter().any(|q| q.abs_diff_eq(**p, 1e-3)))
+            .count();
+        assert!(shared * 4 < top.len());
+        assert!(gyroid(&region, 0.0, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use std::io::{self, Read, Write};
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::slicer::{SlicedLayer, SlicedModel, Slicer};
+
+/// The bytes every file written by `write_sliced` starts with.
+const MAGIC: &[u8; 4] = b"KTNS";
+
+/// The version of the format written by `write_sliced`, bumped whenever the layout changes.
+const VERSION: u32 = 1;
+
+/// Writes a sliced model in a compact binary format, e.g. to cache slicing results between runs.
+///
+/// # Remarks
+///
+/// - The file starts with the `KTNS` magic bytes and a format version, followed by the bounds, the
+///   settings of the `Slicer` and the layers with their contours, walls and infill.
+/// - Numbers are written in **little-endian**, coordinates as `f32` and lists prefixed with their
+///   length as a `u32`, so a model reads back **bit for bit** with `read_sliced`.
+///
+/// # Arguments
+///
+/// * `model` - The model to write.
+/// * `writer` - Where to write the model, preferably buffered.
+///
+/// # Returns
+///
+/// An `io::Error` if the writer fails.
+pub fn write_sliced(model: &SlicedModel, mut writer: impl Write) -> io::Result<()> {
+    let w = &mut writer;
+    w.write_all(MAGIC)?;
+    write_u32(w, VERSION)?;
+
+    let (min, max) = model.bounds;
+    for v in [min, max] {
+        write_f32s(w, &v.to_array())?;
+    }
+    write_f32(w, model.layer_height)?;
+
+    let slicer = &model.slicer;
+    write_f32(w, slicer.layer_height)?;
+    write_f32(w, slicer.epsilon)?;
+    match slicer.first_layer_height {
+        Some(height) => {
+            w.write_all(&[1])?;
+            write_f32(w, height)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    write_f32(w, slicer.nozzle_width)?;
+    write_u32(w, slicer.perimeters as u32)?;
+    write_f32(w, slicer.infill_density)?;
+    write_f32(w, slicer.infill_angle)?;
+    write_u32(w, slicer.top_layers as u32)?;
+    write_u32(w, slicer.bottom_layers as u32)?;
+
+    write_u32(w, model.layers.len() as u32)?;
+    for layer in &model.layers {
+        write_f32(w, layer.height)?;
+        write_f32(w, layer.thickness)?;
+        for loops in [&layer.contours, &layer.perimeters] {
+            write_u32(w, loops.len() as u32)?;
+            for points in loops {
+                write_points(w, points)?;
+            }
+        }
+        for lines in [&layer.solid_infill, &layer.sparse_infill] {
+            write_u32(w, lines.len() as u32)?;
+            for line in lines {
+                write_f32s(w, &[line[0].x, line[0].y, line[1].x, line[1].y])?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+/// Reads a sliced model written by `write_sliced`.
+///
+/// # Arguments
+///
+/// * `reader` - Where to read the model from, preferably buffered.
+///
+/// # Returns
+///
+/// The model, or an `io::Error` if the reader fails, and of kind `InvalidData` if the data does not
+/// start with the magic bytes or was written in another version of the format.
+pub fn read_sliced(mut reader: impl Read) -> io::Result<SlicedModel> {
+    let r = &mut reader;
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a sliced model, the magic bytes do not match",
+        ));
+    }
+    let version = read_u32(r)?;
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported sliced model version {version}, expected {VERSION}"),
+        ));
+    }
+
+    let bounds = (read_vec3(r)?, read_vec3(r)?);
+    let layer_height = read_f32(r)?;
+
+    let mut slicer = Slicer::new();
+    slicer.layer_height = read_f32(r)?;
+    slicer.epsilon = read_f32(r)?;
+    let mut flag = [0];
+    r.read_exact(&mut flag)?;
+    slicer.first_layer_height = match flag[0] {
+        0 => None,
+        _ => Some(read_f32(r)?),
+    };
+    slicer.nozzle_width = read_f32(r)?;
+    slicer.perimeters = read_u32(r)? as usize;
+    slicer.infill_density = read_f32(r)?;
+    slicer.infill_angle = read_f32(r)?;
+    slicer.top_layers = read_u32(r)? as usize;
+    slicer.bottom_layers = read_u32(r)? as usize;
+
+    let layer_count = read_u32(r)?;
+    let mut layers = Vec::new();
+    for _ in 0..layer_count {
+        let height = read_f32(r)?;
+        let thickness = read_f32(r)?;
+        let contours = read_loops(r)?;
+        let perimeters = read_loops(r)?;
+        let solid_infill = read_lines(r)?;
+        let sparse_infill = read_lines(r)?;
+        layers.push(SlicedLayer {
+            height,
+            thickness,
+            contours,
+            perimeters,
+            solid_infill,
+            sparse_infill,
+        });
+    }
+
+    Ok(SlicedModel {
+        bounds,
+        layer_height,
+        layers,
+        slicer,
+    })
+}
+
+/// Writes a `u32` in little-endian.
+fn write_u32(w: &mut impl Write, value: u32) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+/// Writes an `f32` in little-endian.
+fn write_f32(w: &mut impl Write, value: f32) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+/// Writes several `f32` in a row.
+fn write_f32s(w: &mut impl Write, values: &[f32]) -> io::Result<()> {
+    values.iter().try_for_each(|&value| write_f32(w, value))
+}
+
+/// Writes a list of points, prefixed with its length.
+fn write_points(w: &mut impl Write, points: &[Vec2]) -> io::Result<()> {
+    write_u32(w, points.len() as u32)?;
+    points.iter().try_for_each(|p| write_f32s(w, &[p.x, p.y]))
+}
+
+/// Reads a little-endian `u32`.
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Reads a little-endian `f32`.
+fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+/// Reads a 2D point.
+fn read_vec2(r: &mut impl Read) -> io::Result<Vec2> {
+    Ok(Vec2::new(read_f32(r)?, read_f32(r)?))
+}
+
+/// Reads a 3D point.
+fn read_vec3(r: &mut impl Read) -> io::Result<Vec3> {
+    Ok(Vec3::new(read_f32(r)?, read_f32(r)?, read_f32(r)?))
+}
+
+/// Reads a list of loops, each a list of points, as written by `write_points`.
+fn read_loops(r: &mut impl Read) -> io::Result<Vec<Vec<Vec2>>> {
+    let count = read_u32(r)?;
+    (0..count)
+        .map(|_| {
+            let len = read_u32(r)?;
+            (0..len).map(|_| read_vec2(r)).collect()
+        })
+        .collect()
+}
+
+/// Reads a list of lines, each a pair of points.
+fn read_lines(r: &mut impl Read) -> io::Result<Vec<[Vec2; 2]>> {
+    let count = read_u32(r)?;
+    (0..count)
+        .map(|_| Ok([read_vec2(r)?, read_vec2(r)?]))
+        .collect()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `write_sliced` and `read_sliced` functions round-tripping a sliced cube.
+    #[test]
+    fn test_write_sliced_round_trip() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(10.0));
+        let model = Slicer::new().first_layer_height(0.3).slice(&mesh).unwrap();
+
+        let mut bytes = Vec::new();
+        write_sliced(&model, &mut bytes).unwrap();
+        assert_eq!(&bytes[..4], MAGIC);
+        let read = read_sliced(bytes.as_slice()).unwrap();
+        assert_eq!(read, model);
+        assert!(!read.layers[0].perimeters.is_empty());
+
+        // Truncated or foreign data is rejected.
+        assert!(read_sliced(&bytes[..bytes.len() - 1]).is_err());
+        let error = read_sliced(&b"solid cube"[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
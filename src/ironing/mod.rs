@@ -0,0 +1,114 @@
+use glam::f32::{Vec2, Vec3};
+
+use crate::clip;
+use crate::geometry::{from_planar, to_planar};
+use crate::infill::rectilinear;
+use crate::slicer::Layer;
+
+/// The direction of the ironing lines, in degrees from the X axis, across the usual infill angles.
+const IRONING_ANGLE: f32 = 45.0;
+
+/// Finds the top surfaces of every layer: the areas not covered by the layer above.
+///
+/// # Remarks
+///
+/// - The whole region of the **last** layer is a top surface.
+/// - Only these areas are seen on the finished print, so ironing should not run anywhere else.
+///
+/// # Arguments
+///
+/// * `layers` - The sliced layers, from the bottom to the top.
+///
+/// # Returns
+///
+/// The top surface of every layer, in the same order as `layers`, outer contours counter-clockwise
+/// and holes clockwise. Layers fully covered by the next one have an empty surface.
+pub fn top_surfaces(layers: &[Layer]) -> Vec<Vec<Vec<Vec2>>> {
+    let regions: Vec<Vec<Vec<Vec2>>> = layers.iter().map(Layer::regions).collect();
+    (0..regions.len())
+        .map(|i| match regions.get(i + 1) {
+            Some(above) => clip::difference(&regions[i], above),
+            None => regions[i].clone(),
+        })
+        .collect()
+}
+
+/// Generates ironing: dense lines passed over a top surface with very little flow, flattening it.
+///
+/// # Remarks
+///
+/// - The lines are parallel, `line_spacing` apart and clipped to the surface, like `rectilinear`
+///   infill. They run at 45 degrees to cross the lines of the top layer below.
+/// - The lines only carry their geometry: their extrusion is scaled by `flow`, a fraction of the
+///   regular flow, when they are printed. A non-positive `flow` disables ironing.
+/// - Ironing is meant for the surfaces found by `top_surfaces`, right after their layer is printed.
+///
+/// # Arguments
+///
+/// * `top_surface` - The loops of the surface to iron, without repeating their first points.
+/// * `line_spacing` - The distance between two neighbouring lines.
+/// * `flow` - The fraction of the regular extrusion used while ironing, typically 10 to 20 percent.
+///
+/// # Returns
+///
+/// The segments of the ironing pass, each fully inside the surface.
+pub fn generate_ironing(top_surface: &[Vec<Vec2>], line_spacing: f32, flow: f32) -> Vec<[Vec2; 2]> {
+    if flow <= 0.0 || line_spacing <= 0.0 {
+        return Vec::new();
+    }
+
+    let contours: Vec<Vec<Vec3>> = top_surface
+        .iter()
+        .map(|c| c.iter().map(|&p| from_planar(p, 0.0)).collect())
+        .collect();
+    rectilinear(&contours, line_spacing, IRONING_ANGLE)
+        .into_iter()
+        .map(|line| line.map(to_planar))
+        .collect()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::geometry::point_in_polygon;
+
+    /// Test for the `top_surfaces` and `generate_ironing` functions on a stepped block.
+    #[test]
+    fn test_generate_ironing_step() {
+        // A 4x2x2 block with a 2x1x2 step on its left half.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(4.0, 1.0, 2.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(2.0, 2.0, 2.0),
+        ));
+        let mesh = crate::mesh::Mesh::from_triangles(&triangles);
+        let layers = crate::slicer::slice_mesh(&mesh, 0.25).unwrap();
+        let surfaces = top_surfaces(&layers);
+        assert_eq!(surfaces.len(), 8);
+
+        // Only the right half of the fourth layer and the last layer are exposed.
+        for (i, surface) in surfaces.iter().enumerate() {
+            assert_eq!(surface.is_empty(), i != 3 && i != 7, "layer {i}");
+        }
+        assert!(surfaces[3][0].iter().all(|p| p.x >= 2.0 - 1e-4));
+
+        let lines = generate_ironing(&surfaces[3], 0.1, 0.15);
+        assert!(!lines.is_empty());
+        let direction = Vec2::from_angle(IRONING_ANGLE.to_radians());
+        let mut offsets: Vec<f32> = lines
+            .iter()
+            .map(|[a, b]| {
+                assert!(point_in_polygon((*a + *b) / 2.0, &surfaces[3][0]));
+                assert!((*b - *a).normalize().perp_dot(direction).abs() < 1e-4);
+                direction.perp_dot(*a)
+            })
+            .collect();
+        offsets.sort_by(f32::total_cmp);
+        for pair in offsets.windows(2) {
+            assert!((pair[1] - pair[0] - 0.1).abs() < 1e-3);
+        }
+
+        assert!(generate_ironing(&surfaces[3], 0.1, 0.0).is_empty());
+    }
+}
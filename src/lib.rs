@@ -1 +1,24 @@
-mod slicer;
+pub mod adhesion;
+pub mod analysis;
+pub mod bridge;
+pub mod clip;
+pub mod contour;
+pub mod estimate;
+pub mod export;
+pub mod gcode;
+pub mod geometry;
+pub mod infill;
+pub mod io;
+pub mod ironing;
+pub mod machine;
+pub mod mesh;
+pub mod perimeter;
+pub(crate) mod random;
+pub mod seam;
+pub mod slicer;
+pub mod solid;
+pub mod support;
+pub mod travel;
+
+#[cfg(test)]
+mod fixtures;
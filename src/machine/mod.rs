@@ -0,0 +1,133 @@
+use std::fmt;
+
+use glam::f32::{Vec2, Vec3};
+
+use crate::mesh::Mesh;
+
+/// The kinematic and material limits of a printer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineConfig {
+    /// The diameter of the filament in millimeters.
+    pub filament_diameter: f32,
+    /// The speed of extruding moves in millimeters per second.
+    pub print_feedrate: f32,
+    /// The speed of travel moves in millimeters per second.
+    pub travel_feedrate: f32,
+    /// The speed of retractions in millimeters per second.
+    pub retraction_feedrate: f32,
+    /// The acceleration of the print head in millimeters per second squared.
+    pub acceleration: f32,
+    /// The printable area of the bed in millimeters, along the X and Z axes.
+    pub bed_size: Vec2,
+    /// The tallest model the printer can build, in millimeters along the Y axis.
+    pub max_height: f32,
+}
+
+impl Default for MachineConfig {
+    fn default() -> Self {
+        MachineConfig {
+            filament_diameter: 1.75,
+            print_feedrate: 40.0,
+            travel_feedrate: 120.0,
+            retraction_feedrate: 35.0,
+            acceleration: 1000.0,
+            bed_size: Vec2::new(220.0, 220.0),
+            max_height: 250.0,
+        }
+    }
+}
+
+/// A model does not fit in the build volume of the printer, see `MachineConfig::fits`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitError {
+    /// How many millimeters the model overflows the build volume by on each axis, zero on the
+    /// axes where it fits.
+    pub overflow: Vec3,
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let axes = [
+            ("X", self.overflow.x),
+            ("Y", self.overflow.y),
+            ("Z", self.overflow.z),
+        ];
+        let overflows: Vec<String> = axes
+            .iter()
+            .filter(|(_, overflow)| *overflow > 0.0)
+            .map(|(axis, overflow)| format!("{axis} by {overflow}mm"))
+            .collect();
+        write!(
+            f,
+            "the model does not fit in the build volume, it overflows {}",
+            overflows.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for FitError {}
+
+impl MachineConfig {
+    /// Checks that a mesh fits in the build volume of the printer.
+    ///
+    /// # Remarks
+    ///
+    /// - The mesh is checked as if it were **centered** on the bed and resting on it, so only the
+    ///   size of its bounding box matters, not where it is placed.
+    /// - A mesh with no vertices always fits.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The model to check.
+    ///
+    /// # Returns
+    ///
+    /// A `FitError` reporting the overflow on each axis if the mesh is larger than the build volume.
+    pub fn fits(&self, mesh: &Mesh) -> Result<(), FitError> {
+        let Some((min, max)) = mesh.bounding_box() else {
+            return Ok(());
+        };
+        let volume = Vec3::new(self.bed_size.x, self.max_height, self.bed_size.y);
+        let overflow = (max - min - volume).max(Vec3::ZERO);
+        if overflow == Vec3::ZERO {
+            Ok(())
+        } else {
+            Err(FitError { overflow })
+        }
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `MachineConfig::fits` function on a model placed off the bed that still fits.
+    #[test]
+    fn test_machine_config_fits() {
+        let config = MachineConfig {
+            bed_size: Vec2::new(100.0, 50.0),
+            max_height: 80.0,
+            ..MachineConfig::default()
+        };
+        let mesh = crate::fixtures::box_mesh(Vec3::splat(500.0), Vec3::new(600.0, 580.0, 550.0));
+        assert_eq!(config.fits(&mesh), Ok(()));
+        assert_eq!(config.fits(&Mesh::default()), Ok(()));
+    }
+
+    /// Test for the `MachineConfig::fits` function on a model wider than the bed.
+    #[test]
+    fn test_machine_config_fits_overflow_x() {
+        let config = MachineConfig {
+            bed_size: Vec2::new(100.0, 50.0),
+            max_height: 80.0,
+            ..MachineConfig::default()
+        };
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(112.5, 10.0, 10.0));
+        let err = config.fits(&mesh).unwrap_err();
+        assert_eq!(err.overflow, Vec3::new(12.5, 0.0, 0.0));
+        assert_eq!(
+            err.to_string(),
+            "the model does not fit in the build volume, it overflows X by 12.5mm"
+        );
+    }
+}
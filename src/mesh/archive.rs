@@ -0,0 +1,77 @@
+use std::io::{Cursor, Read};
+
+use super::MeshError;
+
+/// Reads and decompresses a single entry of an in-memory zip archive.
+///
+/// # Remarks
+///
+/// - Only available with the `zip` feature, the archive is read with the `zip` crate.
+/// - The entry is looked up in the central directory, names are compared **ignoring ASCII case**.
+/// - Only the `stored` and `deflate` compression methods are enabled.
+///
+/// # Arguments
+///
+/// * `bytes` - The full content of the archive.
+/// * `name` - The path of the entry inside the archive, e.g. `3D/3dmodel.model`.
+///
+/// # Returns
+///
+/// The uncompressed content of the entry, or a `MeshError::Archive` if the archive is malformed or
+/// has no such entry.
+pub(super) fn read_entry(bytes: &[u8], name: &str) -> Result<Vec<u8>, MeshError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(archive_error)?;
+    let index = (0..archive.len())
+        .find(|&i| {
+            archive
+                .name_for_index(i)
+                .is_some_and(|entry| entry.is_ok_and(|entry| entry.eq_ignore_ascii_case(name)))
+        })
+        .ok_or_else(|| MeshError::Archive(format!("no entry named `{name}`")))?;
+
+    let mut entry = archive.by_index(index).map_err(archive_error)?;
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|err| MeshError::Archive(format!("cannot read `{name}`: {err}")))?;
+    Ok(data)
+}
+
+/// Wraps an error of the `zip` crate into a `MeshError::Archive`.
+fn archive_error(err: zip::result::ZipError) -> MeshError {
+    MeshError::Archive(err.to_string())
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use std::io::Write;
+
+    /// Test for the `read_entry` function on stored and deflated entries, looked up by name.
+    #[test]
+    fn test_read_entry() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        let deflated = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        writer.start_file("a.txt", stored).unwrap();
+        writer.write_all(b"first").unwrap();
+        let repeated = "<vertex x=\"1\" y=\"2\" z=\"3\" />\n".repeat(4);
+        writer.start_file("Dir/B.txt", deflated).unwrap();
+        writer.write_all(repeated.as_bytes()).unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+
+        assert_eq!(read_entry(&zip, "a.txt").unwrap(), b"first");
+        assert_eq!(read_entry(&zip, "dir/b.txt").unwrap(), repeated.as_bytes());
+        assert!(matches!(
+            read_entry(&zip, "c.txt"),
+            Err(MeshError::Archive(_))
+        ));
+        assert!(matches!(
+            read_entry(b"not a zip", "a.txt"),
+            Err(MeshError::Archive(_))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use super::Mesh;
+
+/// The result of `check_manifold`, describing how the faces of a mesh are connected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManifoldReport {
+    /// The number of edges shared by exactly two faces.
+    pub manifold_edges: usize,
+    /// The edges used by a single face, bordering a hole in the surface. Each edge is given by its
+    /// vertex indices, the lowest first.
+    pub boundary_edges: Vec<[u32; 2]>,
+    /// The edges shared by more than two faces, the lowest vertex index first.
+    pub non_manifold_edges: Vec<[u32; 2]>,
+    /// The number of groups of faces connected to each other through their edges.
+    pub components: usize,
+}
+
+impl ManifoldReport {
+    /// Returns whether every edge is shared by exactly two faces, i.e. the mesh is watertight.
+    pub fn is_manifold(&self) -> bool {
+        self.boundary_edges.is_empty() && self.non_manifold_edges.is_empty()
+    }
+}
+
+/// Checks whether a mesh is a closed, manifold surface that can be printed.
+///
+/// # Remarks
+///
+/// - Edges are compared by **vertex index**, so the mesh should be welded first, as done by
+///   `Mesh::from_triangles`.
+/// - Edges are reported sorted by vertex indices, so the report is deterministic.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to check.
+///
+/// # Returns
+///
+/// A `ManifoldReport` classifying the edges of the mesh and counting its connected components.
+pub fn check_manifold(mesh: &Mesh) -> ManifoldReport {
+    let mut edge_faces: HashMap<[u32; 2], Vec<usize>> = HashMap::new();
+    for (i, face) in mesh.faces.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            edge_faces.entry([a.min(b), a.max(b)]).or_default().push(i);
+        }
+    }
+
+    let mut report = ManifoldReport::default();
+    let mut parents: Vec<usize> = (0..mesh.faces.len()).collect();
+    for (&edge, faces) in &edge_faces {
+        match faces.len() {
+            1 => report.boundary_edges.push(edge),
+            2 => report.manifold_edges += 1,
+            _ => report.non_manifold_edges.push(edge),
+        }
+        for pair in faces.windows(2) {
+            let (a, b) = (find(&mut parents, pair[0]), find(&mut parents, pair[1]));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+    report.boundary_edges.sort_unstable();
+    report.non_manifold_edges.sort_unstable();
+    report.components = (0..parents.len())
+        .filter(|&i| find(&mut parents, i) == i)
+        .count();
+
+    report
+}
+
+/// Finds the root of the set containing `i` in a union-find forest, compressing the path on the way.
+pub(super) fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use glam::f32::Vec3;
+
+    /// Test for the `check_manifold` function on a closed cube.
+    #[test]
+    fn test_check_manifold_cube() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        let report = check_manifold(&mesh);
+        assert!(report.is_manifold());
+        assert_eq!(report.manifold_edges, 18);
+        assert_eq!(report.components, 1);
+
+        // Two disjoint cubes form two components.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::ONE);
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::splat(2.0),
+            Vec3::splat(3.0),
+        ));
+        let report = check_manifold(&Mesh::from_triangles(&triangles));
+        assert!(report.is_manifold());
+        assert_eq!(report.components, 2);
+    }
+
+    /// Test for the `check_manifold` function on a box missing one of its faces.
+    #[test]
+    fn test_check_manifold_open_box() {
+        let mut mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        mesh.faces.truncate(10);
+
+        let report = check_manifold(&mesh);
+        assert!(!report.is_manifold());
+        assert_eq!(report.boundary_edges.len(), 4);
+        assert!(report.non_manifold_edges.is_empty());
+        assert_eq!(report.manifold_edges, 18 - 1 - 4);
+        assert_eq!(report.components, 1);
+    }
+}
@@ -0,0 +1,670 @@
+use std::fmt;
+use std::io;
+
+use glam::f32::{Affine3A, Quat, Vec3};
+
+use crate::geometry::weld_indices;
+use crate::slicer::EPSILON;
+
+#[cfg(feature = "zip")]
+mod archive;
+mod manifold;
+mod obj;
+mod ply;
+mod stl;
+#[cfg(feature = "zip")]
+mod threemf;
+mod winding;
+
+pub use manifold::{check_manifold, ManifoldReport};
+pub use obj::load_obj;
+pub use ply::load_ply_ascii;
+#[cfg(feature = "memmap2")]
+pub use stl::load_binary_stl_mmap;
+pub use stl::{
+    load_ascii_stl, load_binary_stl, load_stl, parse_binary_stl, parse_binary_stl_skip_invalid,
+};
+#[cfg(feature = "zip")]
+pub use threemf::load_3mf;
+pub use winding::unify_winding;
+
+/// Errors that can occur while loading or building a mesh.
+#[derive(Debug)]
+pub enum MeshError {
+    /// The underlying reader or file could not be read.
+    Io(io::Error),
+    /// The data is too short to contain the fixed-size header of the format.
+    TruncatedHeader { actual: usize, expected: usize },
+    /// The data length does not match the length implied by the declared triangle count.
+    LengthMismatch {
+        triangle_count: u32,
+        expected: usize,
+        actual: usize,
+    },
+    /// A token of a text format did not match what the grammar expects at that position.
+    Parse {
+        line: usize,
+        column: usize,
+        token: String,
+        expected: &'static str,
+    },
+    /// A text format ended before the grammar was complete.
+    UnexpectedEof { line: usize, expected: &'static str },
+    /// A zip container, such as a 3MF file, is malformed or lacks an expected entry.
+    Archive(String),
+    /// A vertex of a face has a NaN or infinite coordinate, which the slicer cannot place.
+    NonFiniteVertex { face_index: usize },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(err) => write!(f, "failed to read mesh: {err}"),
+            MeshError::TruncatedHeader { actual, expected } => write!(
+                f,
+                "mesh data is truncated: got {actual} bytes, the header alone needs {expected}"
+            ),
+            MeshError::LengthMismatch {
+                triangle_count,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "header declares {triangle_count} triangles ({expected} bytes) but the data is {actual} bytes long"
+            ),
+            MeshError::Parse {
+                line,
+                column,
+                token,
+                expected,
+            } => write!(
+                f,
+                "line {line}, column {column}: expected {expected}, found `{token}`"
+            ),
+            MeshError::UnexpectedEof { line, expected } => {
+                write!(f, "line {line}: expected {expected}, found end of file")
+            }
+            MeshError::Archive(reason) => write!(f, "invalid archive: {reason}"),
+            MeshError::NonFiniteVertex { face_index } => {
+                write!(f, "face {face_index} has a vertex with a NaN or infinite coordinate")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MeshError {
+    fn from(err: io::Error) -> Self {
+        MeshError::Io(err)
+    }
+}
+
+/// Returns a `MeshError::NonFiniteVertex` for the first triangle with a NaN or infinite coordinate.
+pub(super) fn check_finite_triangles(triangles: &[[Vec3; 3]]) -> Result<(), MeshError> {
+    match triangles
+        .iter()
+        .position(|triangle| !triangle.iter().all(|v| v.is_finite()))
+    {
+        Some(face_index) => Err(MeshError::NonFiniteVertex { face_index }),
+        None => Ok(()),
+    }
+}
+
+/// Returns a `MeshError::NonFiniteVertex` for the first face of `mesh` using a vertex with a NaN or
+/// infinite coordinate.
+pub(super) fn check_finite_faces(mesh: &Mesh) -> Result<(), MeshError> {
+    match mesh
+        .faces
+        .iter()
+        .position(|face| !face.iter().all(|&i| mesh.vertices[i as usize].is_finite()))
+    {
+        Some(face_index) => Err(MeshError::NonFiniteVertex { face_index }),
+        None => Ok(()),
+    }
+}
+
+/// The axis pointing up in the coordinate system of a model file.
+///
+/// The slicer builds along Y, but many tools, such as CAD packages and 3MF files, use Z as up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpAxis {
+    /// The model stands along X.
+    X,
+    /// The model stands along Y, the build axis of the slicer, so nothing needs to be rotated.
+    #[default]
+    Y,
+    /// The model stands along Z, as in most CAD packages and in 3MF files.
+    Z,
+}
+
+/// A triangle mesh storing each distinct vertex once and faces as indices into the vertex list.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vec3>,
+    pub faces: Vec<[u32; 3]>,
+    /// The unit normal of every face, when cached by `Mesh::cache_face_normals`.
+    normals: Option<Vec<Vec3>>,
+}
+
+/// Two meshes are equal when they have the same vertices and faces, whether or not their normals
+/// are cached.
+impl PartialEq for Mesh {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices && self.faces == other.faces
+    }
+}
+
+impl Mesh {
+    /// Builds an indexed mesh from loose triangles, welding coincident vertices together.
+    ///
+    /// # Remarks
+    ///
+    /// - Vertices closer than `EPSILON` on every axis are **merged** into the first one encountered.
+    /// - Vertices keep the order in which they first appear in `tris`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tris` - The triangles of the mesh, each given by its three vertices.
+    ///
+    /// # Returns
+    ///
+    /// A `Mesh` with one face per input triangle, in the same order.
+    pub fn from_triangles(tris: &[[Vec3; 3]]) -> Mesh {
+        let loose: Vec<Vec3> = tris.iter().flatten().copied().collect();
+
+        // Map every loose vertex to the first loose vertex it is welded to.
+        let representative = weld_indices(&loose, EPSILON);
+
+        let mut vertices = Vec::new();
+        let mut remap = vec![u32::MAX; loose.len()];
+        for i in 0..loose.len() {
+            let rep = representative[i];
+            if remap[rep] == u32::MAX {
+                remap[rep] = vertices.len() as u32;
+                vertices.push(loose[rep]);
+            }
+            remap[i] = remap[rep];
+        }
+
+        let faces = remap
+            .chunks_exact(3)
+            .map(|face| [face[0], face[1], face[2]])
+            .collect();
+
+        Mesh {
+            vertices,
+            faces,
+            normals: None,
+        }
+    }
+
+    /// Returns the three vertices of the face at index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn triangle(&self, i: usize) -> [Vec3; 3] {
+        self.faces[i].map(|v| self.vertices[v as usize])
+    }
+
+    /// Returns an iterator over the faces of the mesh as triangles of vertices.
+    pub fn iter_triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        (0..self.faces.len()).map(|i| self.triangle(i))
+    }
+
+    /// Returns the unit normal of every face, in the order of the faces.
+    ///
+    /// # Remarks
+    ///
+    /// - The normal is the normalized cross product of the edges of the face, so it points out of
+    ///   the solid for faces wound **counter-clockwise** seen from outside.
+    /// - Degenerate faces, whose edges have a cross product shorter than `EPSILON`, get a **zero**
+    ///   normal, see `Mesh::degenerate_faces`.
+    /// - The normals are returned from the cache if `Mesh::cache_face_normals` was called, and
+    ///   computed otherwise.
+    pub fn face_normals(&self) -> Vec<Vec3> {
+        match &self.normals {
+            Some(normals) => normals.clone(),
+            None => self.compute_face_normals(),
+        }
+    }
+
+    /// Computes the normals of the faces and keeps them, so the passes that need them, such as
+    /// overhang detection, do not compute them again.
+    ///
+    /// # Remarks
+    ///
+    /// - The transformations of `Mesh` keep the cache up to date, but it goes **stale** when
+    ///   `vertices` or `faces` are edited directly. Call this function again, or
+    ///   `Mesh::clear_face_normals`, after such an edit.
+    pub fn cache_face_normals(&mut self) {
+        self.normals = Some(self.compute_face_normals());
+    }
+
+    /// Drops the normals cached by `Mesh::cache_face_normals`.
+    pub fn clear_face_normals(&mut self) {
+        self.normals = None;
+    }
+
+    /// Returns the normals cached by `Mesh::cache_face_normals`, if any.
+    pub fn cached_face_normals(&self) -> Option<&[Vec3]> {
+        self.normals.as_deref()
+    }
+
+    /// Returns the indices of the degenerate faces of the mesh, which have a zero normal in
+    /// `Mesh::face_normals`.
+    pub fn degenerate_faces(&self) -> Vec<usize> {
+        self.face_normals()
+            .iter()
+            .enumerate()
+            .filter(|(_, normal)| **normal == Vec3::ZERO)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn compute_face_normals(&self) -> Vec<Vec3> {
+        self.iter_triangles()
+            .map(|[a, b, c]| {
+                let normal = (b - a).cross(c - a);
+                if normal.length() <= EPSILON {
+                    Vec3::ZERO
+                } else {
+                    normal.normalize()
+                }
+            })
+            .collect()
+    }
+
+    /// Computes the axis-aligned bounding box of the mesh in a single pass over its vertices.
+    ///
+    /// # Returns
+    ///
+    /// The minimum and maximum corners of the box, or `None` if the mesh has no vertices.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        let first = *self.vertices.first()?;
+        Some(
+            self.vertices
+                .iter()
+                .fold((first, first), |(min, max), &v| (min.min(v), max.max(v))),
+        )
+    }
+
+    /// Returns the center of the bounding box of the mesh, or the origin if the mesh has no vertices.
+    pub fn center(&self) -> Vec3 {
+        self.bounding_box()
+            .map_or(Vec3::ZERO, |(min, max)| (min + max) / 2.0)
+    }
+
+    /// Computes the volume enclosed by the mesh, in cubic millimeters.
+    ///
+    /// # Remarks
+    ///
+    /// - The signed volumes of the tetrahedra joining each face to the center of the mesh are
+    ///   **summed**, by the divergence theorem. Using the center rather than the origin keeps the
+    ///   terms small for meshes placed far from it.
+    /// - The result is only meaningful for a **closed** mesh whose faces are wound consistently
+    ///   outwards. For a non-manifold or inconsistently wound mesh it is garbage, so check it with
+    ///   `check_manifold` and repair it with `unify_winding` first. A mesh wound inwards has a
+    ///   negative volume.
+    pub fn volume(&self) -> f32 {
+        let center = self.center();
+        self.iter_triangles()
+            .map(|[a, b, c]| (a - center).dot((b - center).cross(c - center)))
+            .sum::<f32>()
+            / 6.0
+    }
+
+    /// Estimates the mass of the part, in grams, from the density of its material in grams per cubic
+    /// centimeter (e.g. 1.24 for PLA).
+    ///
+    /// # Remarks
+    ///
+    /// - The part is assumed **solid**, see `Mesh::volume` for the requirements on the mesh.
+    pub fn mass(&self, density_g_per_cm3: f32) -> f32 {
+        self.volume() / 1000.0 * density_g_per_cm3
+    }
+
+    /// Moves every vertex of the mesh by `offset`.
+    pub fn translate(&mut self, offset: Vec3) {
+        for v in &mut self.vertices {
+            *v += offset;
+        }
+    }
+
+    /// Scales every vertex of the mesh by `factor` along each axis, relative to the origin.
+    ///
+    /// # Remarks
+    ///
+    /// - If an odd number of factors is **negative** the mesh is mirrored, so the faces are rewound to
+    ///   keep their normals pointing out of the solid.
+    pub fn scale(&mut self, factor: Vec3) {
+        for v in &mut self.vertices {
+            *v *= factor;
+        }
+        if factor.x * factor.y * factor.z < 0.0 {
+            for face in &mut self.faces {
+                face.swap(1, 2);
+            }
+        }
+        if self.normals.is_some() {
+            self.cache_face_normals();
+        }
+    }
+
+    /// Rotates the mesh in place by `quat` around the center of its bounding box.
+    ///
+    /// # Remarks
+    ///
+    /// - The rotation is applied around `Mesh::center`, **not the origin**, so the model stays where it
+    ///   is on the build plate. Combine with `translate` to rotate around another point.
+    pub fn rotate(&mut self, quat: Quat) {
+        let center = self.center();
+        for v in &mut self.vertices {
+            *v = center + quat * (*v - center);
+        }
+        if let Some(normals) = &mut self.normals {
+            for n in normals {
+                *n = quat * *n;
+            }
+        }
+    }
+
+    /// Applies an affine transform to every vertex of the mesh, relative to the origin.
+    ///
+    /// # Remarks
+    ///
+    /// - A transform that **mirrors** the mesh, with a negative determinant, rewinds the faces like
+    ///   `Mesh::scale`.
+    pub fn transform(&mut self, transform: Affine3A) {
+        for v in &mut self.vertices {
+            *v = transform.transform_point3(*v);
+        }
+        if transform.matrix3.determinant() < 0.0 {
+            for face in &mut self.faces {
+                face.swap(1, 2);
+            }
+        }
+        if self.normals.is_some() {
+            self.cache_face_normals();
+        }
+    }
+
+    /// Rotates a mesh authored with `from` as its up axis so that axis maps to Y, the build axis.
+    ///
+    /// # Remarks
+    ///
+    /// - The mesh is rotated by a quarter turn with `Mesh::rotate`, around the center of its
+    ///   bounding box, so the faces keep their winding and the model stays where it is.
+    /// - A Z-up model is turned around X, `(x, y, z)` becoming `(x, z, -y)` relative to the center,
+    ///   and an X-up model around Z, `(x, y, z)` becoming `(-y, x, z)`.
+    pub fn set_up_axis(&mut self, from: UpAxis) {
+        let quat = match from {
+            UpAxis::X => Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            UpAxis::Y => return,
+            UpAxis::Z => Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+        };
+        self.rotate(quat);
+    }
+
+    /// Translates the mesh so the center of its bounding box sits at the origin.
+    pub fn center_on_origin(&mut self) {
+        let center = self.center();
+        self.translate(-center);
+    }
+
+    /// Splits the mesh into its connected components, e.g. the separate objects of a single file.
+    ///
+    /// # Remarks
+    ///
+    /// - Two faces belong to the same component when they are linked by a chain of faces sharing a
+    ///   **vertex**, so parts touching at a single corner stay together.
+    /// - Components are ordered by their first face, and each keeps its faces in the original order.
+    ///   Vertices are **re-indexed** in the order the faces first use them, unused vertices being
+    ///   dropped.
+    ///
+    /// # Returns
+    ///
+    /// One `Mesh` per component, without cached normals.
+    pub fn split_connected_components(&self) -> Vec<Mesh> {
+        let mut parents: Vec<usize> = (0..self.vertices.len()).collect();
+        for face in &self.faces {
+            for k in 1..3 {
+                let a = manifold::find(&mut parents, face[0] as usize);
+                let b = manifold::find(&mut parents, face[k] as usize);
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Mesh> = Vec::new();
+        let mut component_of = vec![usize::MAX; self.vertices.len()];
+        let mut remap = vec![u32::MAX; self.vertices.len()];
+        for face in &self.faces {
+            let root = manifold::find(&mut parents, face[0] as usize);
+            if component_of[root] == usize::MAX {
+                component_of[root] = components.len();
+                components.push(Mesh::default());
+            }
+            let mesh = &mut components[component_of[root]];
+            let face = face.map(|v| {
+                let v = v as usize;
+                if remap[v] == u32::MAX {
+                    remap[v] = mesh.vertices.len() as u32;
+                    mesh.vertices.push(self.vertices[v]);
+                }
+                remap[v]
+            });
+            mesh.faces.push(face);
+        }
+
+        components
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `Mesh::split_connected_components` function on an STL file holding two cubes.
+    #[test]
+    fn test_mesh_split_connected_components() {
+        let mut stl = String::new();
+        for (name, min) in [("left", Vec3::ZERO), ("right", Vec3::new(5.0, 0.0, 0.0))] {
+            stl += &format!("solid {name}\n");
+            for [a, b, c] in crate::fixtures::box_triangles(min, min + Vec3::ONE) {
+                stl += "facet normal 0 0 0\nouter loop\n";
+                for v in [a, b, c] {
+                    stl += &format!("vertex {} {} {}\n", v.x, v.y, v.z);
+                }
+                stl += "endloop\nendfacet\n";
+            }
+            stl += &format!("endsolid {name}\n");
+        }
+        let path = std::env::temp_dir().join("katana_test_split_connected_components.stl");
+        std::fs::write(&path, stl).unwrap();
+        let mesh = load_stl(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let parts = mesh.split_connected_components();
+        assert_eq!(parts.len(), 2);
+        for (part, min) in parts.iter().zip([Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0)]) {
+            assert_eq!(part.vertices.len(), 8);
+            assert_eq!(part.faces.len(), 12);
+            assert_eq!(part.bounding_box(), Some((min, min + Vec3::ONE)));
+            assert!((part.volume() - 1.0).abs() < 1e-5);
+        }
+        assert_eq!(
+            parts[1].iter_triangles().collect::<Vec<_>>(),
+            mesh.iter_triangles().skip(12).collect::<Vec<_>>()
+        );
+    }
+
+    /// Test for the `Mesh::from_triangles` function collapsing the shared vertices of a cube.
+    #[test]
+    fn test_mesh_from_triangles_cube() {
+        let triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::ONE);
+        assert_eq!(triangles.len(), 12);
+
+        let mesh = Mesh::from_triangles(&triangles);
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 12);
+        assert_eq!(mesh.iter_triangles().collect::<Vec<_>>(), triangles);
+    }
+
+    /// Test for the `Mesh::from_triangles` function welding vertices closer than `EPSILON`.
+    #[test]
+    fn test_mesh_from_triangles_tolerance() {
+        let nudged = Vec3::new(1.0 + EPSILON / 2.0, 0.0, 0.0);
+        let far = Vec3::new(1.0 + EPSILON * 10.0, 0.0, 0.0);
+        let triangles = [[Vec3::ZERO, Vec3::X, Vec3::Y], [nudged, far, Vec3::Y]];
+
+        let mesh = Mesh::from_triangles(&triangles);
+        assert_eq!(mesh.vertices, vec![Vec3::ZERO, Vec3::X, Vec3::Y, far]);
+        assert_eq!(mesh.faces, vec![[0, 1, 2], [1, 3, 2]]);
+        assert_eq!(mesh.triangle(1), [Vec3::X, far, Vec3::Y]);
+
+        // Vertices on both sides of a cell boundary of `compare_by_xyz` are welded too, even with
+        // a vertex sorting between them.
+        let below = Vec3::new(1.0 - 4e-7, 0.0, 0.0);
+        let above = Vec3::new(1.0 - 4e-7, 5.0, 0.0);
+        let mesh = Mesh::from_triangles(&[[Vec3::X, Vec3::ZERO, Vec3::Y], [below, above, Vec3::Y]]);
+        assert_eq!(mesh.vertices, vec![Vec3::X, Vec3::ZERO, Vec3::Y, above]);
+        assert_eq!(mesh.faces, vec![[0, 1, 2], [0, 3, 2]]);
+    }
+
+    /// Test for the `Mesh::volume` and `Mesh::mass` functions on cubes.
+    #[test]
+    fn test_mesh_volume() {
+        let mut mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        assert!((mesh.volume() - 1.0).abs() < 1e-5);
+
+        let mut large = crate::fixtures::box_mesh(Vec3::splat(100.0), Vec3::splat(110.0));
+        assert!((large.volume() - 1000.0).abs() < 1e-2);
+        assert!((large.mass(1.24) - 1.24).abs() < 1e-5);
+
+        // Mirroring rewinds the faces and keeps the volume positive, inverting them makes it negative.
+        mesh.scale(Vec3::new(-1.0, 1.0, 1.0));
+        large.faces.iter_mut().for_each(|face| face.swap(1, 2));
+        assert!((mesh.volume() - 1.0).abs() < 1e-5);
+        assert!((large.volume() + 1000.0).abs() < 1e-2);
+    }
+
+    /// Test for the `Mesh::face_normals` function on a cube and on a mesh with a degenerate face.
+    #[test]
+    fn test_mesh_face_normals() {
+        let mut mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        let normals = mesh.face_normals();
+        assert_eq!(normals.len(), 12);
+        for (normal, [a, b, c]) in normals.iter().zip(mesh.iter_triangles()) {
+            assert_eq!(normal.abs().max_element(), 1.0);
+            assert_eq!(normal.abs().dot(Vec3::ONE), 1.0);
+            // The normal points away from the center of the cube.
+            assert!(normal.dot((a + b + c) / 3.0 - mesh.center()) > 0.0);
+        }
+        assert_eq!(mesh.degenerate_faces(), Vec::<usize>::new());
+
+        assert_eq!(mesh.cached_face_normals(), None);
+        mesh.cache_face_normals();
+        mesh.rotate(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        let cached = mesh.cached_face_normals().unwrap().to_vec();
+        mesh.clear_face_normals();
+        for (cached, computed) in cached.iter().zip(mesh.face_normals()) {
+            assert!(cached.abs_diff_eq(computed, 1e-6));
+        }
+
+        let mesh = Mesh::from_triangles(&[
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            [Vec3::ZERO, Vec3::X, Vec3::X * 2.0],
+        ]);
+        assert_eq!(mesh.face_normals(), vec![Vec3::Z, Vec3::ZERO]);
+        assert_eq!(mesh.degenerate_faces(), vec![1]);
+    }
+
+    /// Test for the `Mesh::bounding_box` and `Mesh::center` functions on an off-center tetrahedron.
+    #[test]
+    fn test_mesh_bounding_box() {
+        let a = Vec3::new(2.0, -1.0, 3.0);
+        let b = Vec3::new(5.0, -1.0, 3.0);
+        let c = Vec3::new(2.0, -1.0, 7.0);
+        let d = Vec3::new(3.0, 4.0, 4.0);
+        let mesh = Mesh::from_triangles(&[[a, c, b], [a, b, d], [b, c, d], [c, a, d]]);
+
+        let (min, max) = mesh.bounding_box().unwrap();
+        assert_eq!(min, Vec3::new(2.0, -1.0, 3.0));
+        assert_eq!(max, Vec3::new(5.0, 4.0, 7.0));
+        assert_eq!(mesh.center(), Vec3::new(3.5, 1.5, 5.0));
+
+        assert_eq!(Mesh::default().bounding_box(), None);
+        assert_eq!(Mesh::default().center(), Vec3::ZERO);
+    }
+
+    /// Test for the `Mesh::scale` function doubling the bounding box dimensions.
+    #[test]
+    fn test_mesh_scale() {
+        let mut mesh = Mesh::from_triangles(&crate::fixtures::box_triangles(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(2.0, 4.0, 6.0),
+        ));
+        mesh.scale(Vec3::splat(2.0));
+
+        let (min, max) = mesh.bounding_box().unwrap();
+        assert_eq!(max - min, Vec3::new(2.0, 4.0, 6.0));
+        assert_eq!(min, Vec3::new(2.0, 4.0, 6.0));
+
+        let faces = mesh.faces.clone();
+        mesh.scale(Vec3::new(-1.0, 1.0, 1.0));
+        assert_eq!(mesh.faces[0], [faces[0][0], faces[0][2], faces[0][1]]);
+    }
+
+    /// Test for the `Mesh::translate`, `Mesh::rotate` and `Mesh::center_on_origin` functions.
+    #[test]
+    fn test_mesh_translate_rotate() {
+        let mut mesh = Mesh::from_triangles(&crate::fixtures::box_triangles(
+            Vec3::ZERO,
+            Vec3::new(2.0, 1.0, 1.0),
+        ));
+        mesh.translate(Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(mesh.center(), Vec3::new(2.0, 0.5, 0.5));
+
+        mesh.rotate(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        let (min, max) = mesh.bounding_box().unwrap();
+        assert!(mesh.center().abs_diff_eq(Vec3::new(2.0, 0.5, 0.5), 1e-5));
+        assert!((max - min).abs_diff_eq(Vec3::new(1.0, 1.0, 2.0), 1e-5));
+
+        mesh.center_on_origin();
+        assert!(mesh.center().abs_diff_eq(Vec3::ZERO, 1e-5));
+    }
+
+    /// Test for the `Mesh::set_up_axis` function on a Z-up box loaded from an OBJ file.
+    #[test]
+    fn test_mesh_set_up_axis() {
+        // A 1x1 box standing 2 units tall along Z.
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 2\nv 1 0 2\nv 1 1 2\nv 0 1 2
+f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+        let mut mesh = load_obj(source.as_bytes()).unwrap();
+        let volume = mesh.volume();
+        assert!(volume > 0.0);
+
+        mesh.set_up_axis(UpAxis::Z);
+        let (min, max) = mesh.bounding_box().unwrap();
+        assert!((max - min).abs_diff_eq(Vec3::new(1.0, 2.0, 1.0), 1e-5));
+        // The rotation keeps the faces wound outwards.
+        assert!((mesh.volume() - volume).abs() < 1e-4);
+
+        let mut x_up = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(3.0, 1.0, 1.0));
+        x_up.set_up_axis(UpAxis::X);
+        let (min, max) = x_up.bounding_box().unwrap();
+        assert!((max - min).abs_diff_eq(Vec3::new(1.0, 3.0, 1.0), 1e-5));
+
+        let before = x_up.clone();
+        x_up.set_up_axis(UpAxis::Y);
+        assert_eq!(x_up, before);
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::BufRead;
+
+use glam::f32::Vec3;
+
+use super::{check_finite_faces, Mesh, MeshError};
+
+/// Loads a mesh from a Wavefront OBJ file.
+///
+/// # Remarks
+///
+/// - Only the `v` and `f` statements are read, every other statement (normals, texture coordinates,
+///   groups, materials...) is **ignored**.
+/// - Face vertices may use the `v/vt/vn` syntax, only the **position index** is kept.
+/// - Negative indices are **relative** to the vertices defined so far, `-1` being the last one.
+/// - Faces with more than three vertices are **triangulated** as a fan around their first vertex, so
+///   they are expected to be convex.
+/// - Vertices are used as-is and are not welded, OBJ files already share them between faces.
+/// - A face using a vertex with a **NaN or infinite** coordinate fails the load with
+///   `MeshError::NonFiniteVertex`, its index counting the triangles of the fans.
+///
+/// # Arguments
+///
+/// * `reader` - A reader over the content of an OBJ file.
+///
+/// # Returns
+///
+/// The mesh of the file, or a `MeshError` pointing at the offending token if the file is malformed.
+pub fn load_obj(reader: impl BufRead) -> Result<Mesh, MeshError> {
+    let mut mesh = Mesh::default();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = number + 1;
+        let mut words = words(&line);
+        let Some((_, keyword)) = words.next() else {
+            continue;
+        };
+
+        match keyword {
+            "v" => {
+                let mut coordinates = [0.0; 3];
+                for coordinate in coordinates.iter_mut() {
+                    let (column, word) = words.next().ok_or(MeshError::UnexpectedEof {
+                        line: number,
+                        expected: "a number",
+                    })?;
+                    *coordinate = word
+                        .parse::<f32>()
+                        .map_err(|_| parse_error(number, column, word, "a number"))?;
+                }
+                mesh.vertices.push(Vec3::from_array(coordinates));
+            }
+            "f" => {
+                let indices = words
+                    .map(|(column, word)| {
+                        vertex_index(word, mesh.vertices.len())
+                            .ok_or_else(|| parse_error(number, column, word, "a vertex index"))
+                    })
+                    .collect::<Result<Vec<u32>, MeshError>>()?;
+                if indices.len() < 3 {
+                    return Err(MeshError::UnexpectedEof {
+                        line: number,
+                        expected: "at least three face vertices",
+                    });
+                }
+                for i in 1..indices.len() - 1 {
+                    mesh.faces.push([indices[0], indices[i], indices[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    check_finite_faces(&mesh)?;
+    Ok(mesh)
+}
+
+/// Resolves the position index of a face vertex, given the number of vertices defined so far.
+fn vertex_index(word: &str, vertex_count: usize) -> Option<u32> {
+    let position = word.split('/').next()?.parse::<i64>().ok()?;
+    let index = match position {
+        1.. => position - 1,
+        ..=-1 => vertex_count as i64 + position,
+        0 => return None,
+    };
+    (0..vertex_count as i64)
+        .contains(&index)
+        .then_some(index as u32)
+}
+
+/// Splits a line into its whitespace-separated words along with their 1-based columns, stopping at
+/// a `#` comment.
+pub(super) fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let content = line.split('#').next().unwrap_or_default();
+    content
+        .char_indices()
+        .filter(|&(i, c)| {
+            !c.is_whitespace() && content[..i].chars().last().is_none_or(char::is_whitespace)
+        })
+        .map(move |(i, _)| {
+            let word = content[i..].split(char::is_whitespace).next().unwrap();
+            (content[..i].chars().count() + 1, word)
+        })
+}
+
+pub(super) fn parse_error(
+    line: usize,
+    column: usize,
+    token: &str,
+    expected: &'static str,
+) -> MeshError {
+    MeshError::Parse {
+        line,
+        column,
+        token: token.to_string(),
+        expected,
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `load_obj` function triangulating the quad faces of a cube.
+    #[test]
+    fn test_load_obj_quad_cube() {
+        let source = "# A unit cube
+o cube
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+vn 0 0 -1
+f 1//1 4//1 3//1 2//1
+f 5 6 7 8
+f 1/1 2/2 6/3 5/4
+f 2 3 7 6
+f -5 -1 -2 -6
+f 4 1 5 8
+";
+        let mesh = load_obj(source.as_bytes()).unwrap();
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 12);
+        assert_eq!(mesh.faces[0], [0, 3, 2]);
+        assert_eq!(mesh.faces[1], [0, 2, 1]);
+        // The relative face refers to vertices 4, 8, 7 and 3.
+        assert_eq!(mesh.faces[8], [3, 7, 6]);
+        assert_eq!(mesh.bounding_box(), Some((Vec3::ZERO, Vec3::ONE)));
+    }
+
+    /// Test for the `load_obj` function when a face refers to a vertex that does not exist.
+    #[test]
+    fn test_load_obj_invalid_index() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2  4\n";
+        match load_obj(source.as_bytes()) {
+            Err(MeshError::Parse {
+                line,
+                column,
+                token,
+                ..
+            }) => {
+                assert_eq!(line, 4);
+                assert_eq!(column, 8);
+                assert_eq!(token, "4");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
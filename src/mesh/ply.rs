@@ -0,0 +1,323 @@
+use std::io::BufRead;
+
+use glam::f32::Vec3;
+
+use super::obj::{parse_error, words};
+use super::{check_finite_faces, Mesh, MeshError};
+
+/// A property of an element declared in the header of a PLY file.
+struct Property {
+    name: String,
+    /// Whether the property is a list, prefixed by its length.
+    list: bool,
+}
+
+/// An element declared in the header of a PLY file, e.g. `vertex` or `face`.
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// The words of each property of an element, along with their 1-based columns, by property name.
+type Values<'a> = Vec<(&'a str, Vec<(usize, &'a str)>)>;
+
+/// Loads a mesh from an ASCII PLY file.
+///
+/// # Remarks
+///
+/// - The header is read to find the `vertex` and `face` elements and the layout of their
+///   properties. Other elements, such as `edge`, are **skipped**.
+/// - Vertices are read from their `x`, `y` and `z` properties, any other property (normals, colors...)
+///   is **ignored**.
+/// - Faces are read from their `vertex_indices` (or `vertex_index`) list. Faces with more than three
+///   vertices are **triangulated** as a fan around their first vertex, so they are expected to be
+///   convex.
+/// - Only the `ascii` format is supported, binary PLY files are rejected at their `format` line.
+/// - A face using a vertex with a **NaN or infinite** coordinate fails the load with
+///   `MeshError::NonFiniteVertex`, its index counting the triangles of the fans.
+///
+/// # Arguments
+///
+/// * `reader` - A reader over the content of a PLY file.
+///
+/// # Returns
+///
+/// The mesh of the file, or a `MeshError` pointing at the offending token if the file is malformed.
+pub fn load_ply_ascii(reader: impl BufRead) -> Result<Mesh, MeshError> {
+    let mut lines = reader.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let elements = read_header(&mut lines)?;
+
+    let mut mesh = Mesh::default();
+    let mut number = 0;
+    for element in &elements {
+        for _ in 0..element.count {
+            let line = loop {
+                let Some((n, line)) = lines.next() else {
+                    return Err(MeshError::UnexpectedEof {
+                        line: number + 1,
+                        expected: "an element",
+                    });
+                };
+                number = n;
+                let line = line?;
+                if words(&line).next().is_some() {
+                    break line;
+                }
+            };
+            let values = read_values(&line, number, element)?;
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let mut position = [0.0; 3];
+                    for (coordinate, axis) in position.iter_mut().zip(["x", "y", "z"]) {
+                        let (column, word) =
+                            values.iter().find(|(name, _)| *name == axis).unwrap().1[0];
+                        *coordinate = word
+                            .parse::<f32>()
+                            .map_err(|_| parse_error(number, column, word, "a number"))?;
+                    }
+                    mesh.vertices.push(Vec3::from_array(position));
+                }
+                "face" => {
+                    let Some((_, list)) = values
+                        .iter()
+                        .find(|(name, _)| *name == "vertex_indices" || *name == "vertex_index")
+                    else {
+                        continue;
+                    };
+                    let indices = list
+                        .iter()
+                        .map(|&(column, word)| {
+                            word.parse::<u32>()
+                                .ok()
+                                .filter(|&i| (i as usize) < vertex_count(&elements))
+                                .ok_or_else(|| parse_error(number, column, word, "a vertex index"))
+                        })
+                        .collect::<Result<Vec<u32>, MeshError>>()?;
+                    if indices.len() < 3 {
+                        return Err(MeshError::UnexpectedEof {
+                            line: number,
+                            expected: "at least three face vertices",
+                        });
+                    }
+                    for i in 1..indices.len() - 1 {
+                        mesh.faces.push([indices[0], indices[i], indices[i + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    check_finite_faces(&mesh)?;
+    Ok(mesh)
+}
+
+/// Reads the header of a PLY file up to its `end_header` line, returning the declared elements.
+fn read_header(
+    lines: &mut impl Iterator<Item = (usize, std::io::Result<String>)>,
+) -> Result<Vec<Element>, MeshError> {
+    let mut elements: Vec<Element> = Vec::new();
+    let mut number = 0;
+    loop {
+        let Some((n, line)) = lines.next() else {
+            return Err(MeshError::UnexpectedEof {
+                line: number + 1,
+                expected: "`end_header`",
+            });
+        };
+        number = n;
+        let line = line?;
+        let mut words = words(&line);
+        let Some((column, keyword)) = words.next() else {
+            continue;
+        };
+        let mut next = |expected: &'static str| {
+            words.next().ok_or(MeshError::UnexpectedEof {
+                line: number,
+                expected,
+            })
+        };
+
+        match keyword {
+            "ply" if number == 1 => {}
+            _ if number == 1 => return Err(parse_error(number, column, keyword, "`ply`")),
+            "format" => {
+                let (column, format) = next("a format")?;
+                if format != "ascii" {
+                    return Err(parse_error(number, column, format, "`ascii`"));
+                }
+            }
+            "comment" | "obj_info" => {}
+            "element" => {
+                let (_, name) = next("an element name")?;
+                let (column, count) = next("an element count")?;
+                let count = count
+                    .parse()
+                    .map_err(|_| parse_error(number, column, count, "an element count"))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            "property" => {
+                let Some(element) = elements.last_mut() else {
+                    return Err(parse_error(number, column, keyword, "an element"));
+                };
+                let (_, kind) = next("a property type")?;
+                let list = kind == "list";
+                if list {
+                    next("a list length type")?;
+                    next("a list item type")?;
+                }
+                let (_, name) = next("a property name")?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    list,
+                });
+            }
+            "end_header" => {
+                let vertex = elements.iter().find(|e| e.name == "vertex");
+                let complete = vertex.is_none_or(|vertex| {
+                    ["x", "y", "z"]
+                        .iter()
+                        .all(|axis| vertex.properties.iter().any(|p| !p.list && p.name == *axis))
+                });
+                if !complete {
+                    return Err(parse_error(
+                        number,
+                        column,
+                        keyword,
+                        "the `x`, `y` and `z` vertex properties",
+                    ));
+                }
+                return Ok(elements);
+            }
+            _ => return Err(parse_error(number, column, keyword, "a header keyword")),
+        }
+    }
+}
+
+/// Splits the line of an element into the words of each of its properties, along with their
+/// 1-based columns.
+fn read_values<'a>(
+    line: &'a str,
+    number: usize,
+    element: &'a Element,
+) -> Result<Values<'a>, MeshError> {
+    let mut words = words(line);
+    let mut next = || {
+        words.next().ok_or(MeshError::UnexpectedEof {
+            line: number,
+            expected: "a property value",
+        })
+    };
+
+    let mut values = Vec::with_capacity(element.properties.len());
+    for property in &element.properties {
+        let length = if property.list {
+            let (column, word) = next()?;
+            word.parse::<usize>()
+                .map_err(|_| parse_error(number, column, word, "a list length"))?
+        } else {
+            1
+        };
+        let items = (0..length).map(|_| next()).collect::<Result<_, _>>()?;
+        values.push((property.name.as_str(), items));
+    }
+    Ok(values)
+}
+
+/// Returns the number of vertices declared in the header.
+fn vertex_count(elements: &[Element]) -> usize {
+    elements
+        .iter()
+        .filter(|e| e.name == "vertex")
+        .map(|e| e.count)
+        .sum()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `load_ply_ascii` function on a cube with colored vertices and quad faces.
+    #[test]
+    fn test_load_ply_ascii_cube() {
+        let source = "ply
+format ascii 1.0
+comment A unit cube
+element vertex 8
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 6
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 255 0 0
+1 1 0 255 0 0
+0 1 0 255 0 0
+0 0 1 0 0 255
+1 0 1 0 0 255
+1 1 1 0 0 255
+0 1 1 0 0 255
+4 0 3 2 1
+4 4 5 6 7
+4 0 1 5 4
+4 1 2 6 5
+4 2 3 7 6
+4 3 0 4 7
+";
+        let mesh = load_ply_ascii(source.as_bytes()).unwrap();
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 12);
+        assert_eq!(mesh.faces[0], [0, 3, 2]);
+        assert_eq!(mesh.faces[1], [0, 2, 1]);
+        assert_eq!(mesh.bounding_box(), Some((Vec3::ZERO, Vec3::ONE)));
+        assert!(crate::mesh::check_manifold(&mesh).is_manifold());
+    }
+
+    /// Test for the `load_ply_ascii` function rejecting malformed files.
+    #[test]
+    fn test_load_ply_ascii_errors() {
+        let binary = "ply\nformat binary_little_endian 1.0\nend_header\n";
+        assert!(matches!(
+            load_ply_ascii(binary.as_bytes()),
+            Err(MeshError::Parse {
+                line: 2,
+                column: 8,
+                ..
+            })
+        ));
+
+        let header =
+            "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+                      property float z\nelement face 1\nproperty list uchar int vertex_indices\n\
+                      end_header\n";
+        let invalid_index = format!("{header}0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n");
+        match load_ply_ascii(invalid_index.as_bytes()) {
+            Err(MeshError::Parse {
+                line,
+                column,
+                token,
+                ..
+            }) => {
+                assert_eq!((line, column), (13, 7));
+                assert_eq!(token, "3");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let truncated = format!("{header}0 0 0\n1 0 0\n");
+        assert!(matches!(
+            load_ply_ascii(truncated.as_bytes()),
+            Err(MeshError::UnexpectedEof { line: 12, .. })
+        ));
+    }
+}
@@ -0,0 +1,606 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::BufRead;
+use std::path::Path;
+
+use glam::f32::Vec3;
+
+use super::{check_finite_triangles, Mesh, MeshError};
+
+/// Size in bytes of the free-form header at the start of a binary STL file.
+const HEADER_LEN: usize = 80;
+
+/// Size in bytes of the header plus the little-endian triangle count.
+const PREAMBLE_LEN: usize = HEADER_LEN + 4;
+
+/// Size in bytes of a single triangle record: normal, three vertices and the attribute byte count.
+const TRIANGLE_RECORD_LEN: usize = 50;
+
+/// Loads the triangles of a binary STL file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the binary STL file to read.
+///
+/// # Returns
+///
+/// The triangles of the file, or a `MeshError` if the file cannot be read or is malformed.
+pub fn load_binary_stl(path: &Path) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    let bytes = fs::read(path)?;
+    parse_binary_stl(&bytes)
+}
+
+/// Loads a mesh from a binary STL file mapped in memory, for files too large to read comfortably.
+///
+/// # Remarks
+///
+/// - Only available with the `memmap2` feature.
+/// - The triangles are decoded straight from the mapped file, the raw bytes are never copied to the
+///   heap. The declared triangle count is checked against the size of the file as in
+///   `parse_binary_stl`.
+/// - The file must not be modified while it is loaded. Vertices are welded with
+///   `Mesh::from_triangles`, so the mesh is the same as with `load_binary_stl`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the binary STL file to map.
+///
+/// # Returns
+///
+/// The mesh of the file, or a `MeshError` if the file cannot be mapped or is malformed.
+#[cfg(feature = "memmap2")]
+pub fn load_binary_stl_mmap(path: &Path) -> Result<Mesh, MeshError> {
+    let file = fs::File::open(path)?;
+    // SAFETY: the map is only read while the file is open, and the caller is told not to modify
+    // the file in the meantime.
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    parse_binary_stl(&map).map(|triangles| Mesh::from_triangles(&triangles))
+}
+
+/// Parses the triangles of an in-memory binary STL file.
+///
+/// # Remarks
+///
+/// - The stored facet normals are **ignored**, the winding of the vertices is the source of truth.
+/// - Vertices are kept in the file's coordinate system, the slicer uses the Y axis as the build axis.
+/// - A triangle with a **NaN or infinite** coordinate fails the parse with
+///   `MeshError::NonFiniteVertex`, see `parse_binary_stl_skip_invalid` to drop it instead.
+///
+/// # Arguments
+///
+/// * `bytes` - The full content of a binary STL file.
+///
+/// # Returns
+///
+/// The triangles of the file, or a `MeshError` if the length does not match the declared triangle count.
+pub fn parse_binary_stl(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    let triangles = decode_binary_stl(bytes)?;
+    check_finite_triangles(&triangles)?;
+    Ok(triangles)
+}
+
+/// Parses the triangles of an in-memory binary STL file, dropping those that cannot be sliced.
+///
+/// # Remarks
+///
+/// - Same as `parse_binary_stl`, except that triangles with a **NaN or infinite** coordinate are
+///   **skipped** instead of failing the parse. Broken exporters sometimes write a few of them in an
+///   otherwise usable file.
+/// - The remaining triangles keep their order, the mesh may be left with holes where they were.
+///
+/// # Arguments
+///
+/// * `bytes` - The full content of a binary STL file.
+///
+/// # Returns
+///
+/// The finite triangles of the file, or a `MeshError` if the length does not match the declared
+/// triangle count.
+pub fn parse_binary_stl_skip_invalid(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    let mut triangles = decode_binary_stl(bytes)?;
+    triangles.retain(|triangle| triangle.iter().all(|v| v.is_finite()));
+    Ok(triangles)
+}
+
+/// Decodes the triangles of an in-memory binary STL file, without checking their coordinates.
+fn decode_binary_stl(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    if bytes.len() < PREAMBLE_LEN {
+        return Err(MeshError::TruncatedHeader {
+            actual: bytes.len(),
+            expected: PREAMBLE_LEN,
+        });
+    }
+
+    let triangle_count = read_u32(bytes, HEADER_LEN);
+    let expected = PREAMBLE_LEN + triangle_count as usize * TRIANGLE_RECORD_LEN;
+    if bytes.len() != expected {
+        return Err(MeshError::LengthMismatch {
+            triangle_count,
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    let triangles = bytes[PREAMBLE_LEN..]
+        .chunks_exact(TRIANGLE_RECORD_LEN)
+        .map(|record| {
+            // The first 12 bytes are the facet normal.
+            [
+                read_vec3(record, 12),
+                read_vec3(record, 24),
+                read_vec3(record, 36),
+            ]
+        })
+        .collect();
+
+    Ok(triangles)
+}
+
+/// Loads the triangles of an ASCII STL file.
+///
+/// # Remarks
+///
+/// - Tokens may be separated by **any amount of whitespace**, including line breaks.
+/// - Files containing **multiple solids** are supported, the facets of all solids are concatenated.
+/// - The facet normals are **ignored**, the winding of the vertices is the source of truth.
+/// - A facet with a **NaN or infinite** coordinate, such as `nan` or `inf`, fails the load with
+///   `MeshError::NonFiniteVertex`.
+///
+/// # Arguments
+///
+/// * `reader` - A reader over the content of an ASCII STL file.
+///
+/// # Returns
+///
+/// The triangles of the file, or a `MeshError` pointing at the offending token if the file is malformed.
+pub fn load_ascii_stl(reader: impl BufRead) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    let mut tokens = Tokenizer::new(reader);
+    let mut triangles = Vec::new();
+
+    while let Some(token) = tokens.next_token()? {
+        expect_keyword(&token, "solid")?;
+        // The solid name is free-form text up to the end of the line.
+        tokens.skip_line();
+
+        loop {
+            let token = tokens.expect_token("`facet` or `endsolid`")?;
+            match token.text.as_str() {
+                "facet" => triangles.push(parse_facet(&mut tokens)?),
+                "endsolid" => {
+                    tokens.skip_line();
+                    break;
+                }
+                _ => return Err(token.unexpected("`facet` or `endsolid`")),
+            }
+        }
+    }
+
+    check_finite_triangles(&triangles)?;
+    Ok(triangles)
+}
+
+/// Loads a mesh from an STL file, detecting whether it is ASCII or binary.
+///
+/// # Remarks
+///
+/// - A file starting with `solid` is first parsed as **ASCII**. Some exporters also start the free-form
+///   header of binary files with `solid`, so if the ASCII parse fails the file is parsed as
+///   **binary** instead.
+/// - When both parses fail, the error of the ASCII parser is returned for files starting with `solid`,
+///   as it points at the offending token, and the error of the binary parser otherwise.
+/// - Vertices are welded with `Mesh::from_triangles`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the STL file to read.
+///
+/// # Returns
+///
+/// The mesh of the file, or a `MeshError` if the file cannot be read or is malformed.
+pub fn load_stl(path: &Path) -> Result<Mesh, MeshError> {
+    let bytes = fs::read(path)?;
+    parse_stl(&bytes).map(|triangles| Mesh::from_triangles(&triangles))
+}
+
+/// Parses the triangles of an in-memory STL file of either flavor, see `load_stl`.
+fn parse_stl(bytes: &[u8]) -> Result<Vec<[Vec3; 3]>, MeshError> {
+    if !bytes.trim_ascii_start().starts_with(b"solid") {
+        return parse_binary_stl(bytes);
+    }
+    load_ascii_stl(bytes).or_else(|err| parse_binary_stl(bytes).map_err(|_| err))
+}
+
+/// Parses a facet body, from the `normal` keyword up to and including `endfacet`.
+fn parse_facet<R: BufRead>(tokens: &mut Tokenizer<R>) -> Result<[Vec3; 3], MeshError> {
+    expect_keyword(&tokens.expect_token("`normal`")?, "normal")?;
+    parse_vec3(tokens)?;
+    expect_keyword(&tokens.expect_token("`outer`")?, "outer")?;
+    expect_keyword(&tokens.expect_token("`loop`")?, "loop")?;
+
+    let mut triangle = [Vec3::ZERO; 3];
+    for vertex in triangle.iter_mut() {
+        expect_keyword(&tokens.expect_token("`vertex`")?, "vertex")?;
+        *vertex = parse_vec3(tokens)?;
+    }
+
+    expect_keyword(&tokens.expect_token("`endloop`")?, "endloop")?;
+    expect_keyword(&tokens.expect_token("`endfacet`")?, "endfacet")?;
+
+    Ok(triangle)
+}
+
+fn parse_vec3<R: BufRead>(tokens: &mut Tokenizer<R>) -> Result<Vec3, MeshError> {
+    let mut coordinates = [0.0; 3];
+    for coordinate in coordinates.iter_mut() {
+        let token = tokens.expect_token("a number")?;
+        *coordinate = token
+            .text
+            .parse::<f32>()
+            .map_err(|_| token.unexpected("a number"))?;
+    }
+    Ok(Vec3::from_array(coordinates))
+}
+
+fn expect_keyword(token: &Token, keyword: &'static str) -> Result<(), MeshError> {
+    if token.text.eq_ignore_ascii_case(keyword) {
+        Ok(())
+    } else {
+        Err(token.unexpected(keyword))
+    }
+}
+
+/// A whitespace-separated word of an ASCII file along with its 1-based position.
+struct Token {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn unexpected(&self, expected: &'static str) -> MeshError {
+        MeshError::Parse {
+            line: self.line,
+            column: self.column,
+            token: self.text.clone(),
+            expected,
+        }
+    }
+}
+
+/// Splits an ASCII reader into tokens lazily, one line at a time.
+struct Tokenizer<R> {
+    reader: R,
+    line: usize,
+    pending: VecDeque<Token>,
+}
+
+impl<R: BufRead> Tokenizer<R> {
+    fn new(reader: R) -> Self {
+        Tokenizer {
+            reader,
+            line: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the next token, or `None` once the reader is exhausted.
+    fn next_token(&mut self) -> Result<Option<Token>, MeshError> {
+        let mut buf = String::new();
+        while self.pending.is_empty() {
+            buf.clear();
+            if self.reader.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            let mut column = 1;
+            for word in buf.split_inclusive(char::is_whitespace) {
+                let text = word.trim_end();
+                if !text.is_empty() {
+                    self.pending.push_back(Token {
+                        text: text.to_string(),
+                        line: self.line,
+                        column,
+                    });
+                }
+                column += word.chars().count();
+            }
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Returns the next token, failing with `UnexpectedEof` if the reader is exhausted.
+    fn expect_token(&mut self, expected: &'static str) -> Result<Token, MeshError> {
+        self.next_token()?.ok_or(MeshError::UnexpectedEof {
+            line: self.line,
+            expected,
+        })
+    }
+
+    /// Discards the remaining tokens of the current line.
+    fn skip_line(&mut self) {
+        self.pending.clear();
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(read_u32(bytes, offset))
+}
+
+fn read_vec3(bytes: &[u8], offset: usize) -> Vec3 {
+    Vec3::new(
+        read_f32(bytes, offset),
+        read_f32(bytes, offset + 4),
+        read_f32(bytes, offset + 8),
+    )
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Builds a binary STL buffer containing the given triangles.
+    #[allow(dead_code)]
+    fn build_binary_stl(triangles: &[[Vec3; 3]]) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LEN];
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+            for v in std::iter::once(normal).chain(triangle.iter().copied()) {
+                for c in v.to_array() {
+                    bytes.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Test for the `parse_binary_stl` function with a hand-built buffer of two triangles.
+    #[test]
+    fn test_parse_binary_stl_round_trip() {
+        let triangles = [
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            [Vec3::new(1.0, 2.0, 3.0), Vec3::new(-4.5, 5.0, 6.0), Vec3::Z],
+        ];
+        let bytes = build_binary_stl(&triangles);
+        assert_eq!(bytes.len(), 84 + 2 * 50);
+
+        let parsed = parse_binary_stl(&bytes).unwrap();
+        assert_eq!(parsed, triangles);
+    }
+
+    /// Test for the `load_binary_stl` function reading the buffer back from disk.
+    #[test]
+    fn test_load_binary_stl_file() {
+        let triangles = [[Vec3::ZERO, Vec3::X, Vec3::Z]];
+        let path = std::env::temp_dir().join("katana_test_load_binary_stl.stl");
+        std::fs::write(&path, build_binary_stl(&triangles)).unwrap();
+
+        let loaded = load_binary_stl(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, triangles);
+    }
+
+    /// Test for the `load_binary_stl_mmap` function matching the buffered loader on a larger file.
+    #[test]
+    #[cfg(feature = "memmap2")]
+    fn test_load_binary_stl_mmap() {
+        let triangles: Vec<[Vec3; 3]> = (0..1000)
+            .flat_map(|i| {
+                let min = Vec3::new((i % 10) as f32, (i / 100) as f32, (i / 10 % 10) as f32) * 2.0;
+                crate::fixtures::box_triangles(min, min + Vec3::ONE)
+            })
+            .collect();
+        let path = std::env::temp_dir().join("katana_test_load_binary_stl_mmap.stl");
+        let mut bytes = build_binary_stl(&triangles);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mapped = load_binary_stl_mmap(&path).unwrap();
+        let buffered = Mesh::from_triangles(&load_binary_stl(&path).unwrap());
+        assert_eq!(mapped.faces.len(), 12_000);
+        assert_eq!(mapped.vertices.len(), 8_000);
+        assert_eq!(mapped, buffered);
+
+        bytes.pop();
+        std::fs::write(&path, &bytes).unwrap();
+        let truncated = load_binary_stl_mmap(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(truncated, Err(MeshError::LengthMismatch { .. })));
+    }
+
+    /// Test for the `parse_binary_stl` function when the length does not match the declared count.
+    #[test]
+    fn test_parse_binary_stl_length_mismatch() {
+        let mut bytes = build_binary_stl(&[[Vec3::ZERO, Vec3::X, Vec3::Y]]);
+        bytes.pop();
+        match parse_binary_stl(&bytes) {
+            Err(MeshError::LengthMismatch {
+                triangle_count,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(triangle_count, 1);
+                assert_eq!(expected, 134);
+                assert_eq!(actual, 133);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let result = parse_binary_stl(&[0u8; 10]);
+        assert!(matches!(result, Err(MeshError::TruncatedHeader { .. })));
+    }
+
+    /// Test for the `load_stl` function detecting both flavors from disk.
+    #[test]
+    fn test_load_stl_detects_flavor() {
+        let ascii = "solid part
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid part
+";
+        let triangles = [
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            [Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y],
+        ];
+        let path = std::env::temp_dir().join("katana_test_load_stl.stl");
+        for (content, faces) in [
+            (ascii.as_bytes().to_vec(), 1),
+            (build_binary_stl(&triangles), 2),
+        ] {
+            std::fs::write(&path, content).unwrap();
+            let mesh = load_stl(&path).unwrap();
+            assert_eq!(mesh.faces.len(), faces);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Test for the `parse_stl` function on a binary file whose header starts with `solid`.
+    #[test]
+    fn test_parse_stl_binary_starting_with_solid() {
+        // A count of 10 triangles is a line feed byte, ending the header line like in real files, so
+        // the ASCII parser only fails on the records.
+        let triangles: Vec<[Vec3; 3]> = (0..10)
+            .map(|i| [Vec3::ZERO, Vec3::X, Vec3::new(0.0, 1.0, i as f32)])
+            .collect();
+        let mut bytes = build_binary_stl(&triangles);
+        let header = b"solid exported by a careless tool";
+        bytes[..header.len()].copy_from_slice(header);
+        assert_eq!(parse_stl(&bytes).unwrap(), triangles);
+
+        // A malformed ASCII file reports the ASCII error rather than the binary one.
+        let broken = "solid broken\n  facet normal 0 0 1\n  endfacet\nendsolid broken\n";
+        assert!(matches!(
+            parse_stl(broken.as_bytes()),
+            Err(MeshError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_stl(&[0u8; 10]),
+            Err(MeshError::TruncatedHeader { .. })
+        ));
+    }
+
+    /// Test for the `load_ascii_stl` function with a valid file containing two solids.
+    #[test]
+    fn test_load_ascii_stl_valid() {
+        let source = "solid first part
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex\t0   1 0
+    endloop
+  endfacet
+endsolid first part
+solid
+facet normal 0 0 0 outer loop
+vertex 1e0 2.5 -3 vertex 4 5 6
+vertex 7 8 9 endloop endfacet
+endsolid
+";
+        let triangles = load_ascii_stl(source.as_bytes()).unwrap();
+        assert_eq!(
+            triangles,
+            vec![
+                [Vec3::ZERO, Vec3::X, Vec3::Y],
+                [
+                    Vec3::new(1.0, 2.5, -3.0),
+                    Vec3::new(4.0, 5.0, 6.0),
+                    Vec3::new(7.0, 8.0, 9.0)
+                ],
+            ]
+        );
+    }
+
+    /// Test for the `load_ascii_stl` function when a facet is missing its `endloop`.
+    #[test]
+    fn test_load_ascii_stl_missing_endloop() {
+        let source = "solid broken
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+  endfacet
+endsolid broken
+";
+        match load_ascii_stl(source.as_bytes()) {
+            Err(MeshError::Parse {
+                line,
+                column,
+                token,
+                expected,
+            }) => {
+                assert_eq!(line, 7);
+                assert_eq!(column, 3);
+                assert_eq!(token, "endfacet");
+                assert_eq!(expected, "endloop");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    /// Test for the `load_ascii_stl` function when a vertex has only two coordinates.
+    #[test]
+    fn test_load_ascii_stl_short_vertex() {
+        let source = "solid broken
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid broken
+";
+        match load_ascii_stl(source.as_bytes()) {
+            Err(MeshError::Parse { line, token, .. }) => {
+                assert_eq!(line, 6);
+                assert_eq!(token, "vertex");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let truncated = "solid broken\n facet normal 0 0 1\n outer loop\n vertex 0 0";
+        let result = load_ascii_stl(truncated.as_bytes());
+        assert!(matches!(
+            result,
+            Err(MeshError::UnexpectedEof { line: 4, .. })
+        ));
+    }
+
+    /// Test for the `parse_binary_stl` function rejecting a triangle with a NaN coordinate.
+    #[test]
+    fn test_parse_binary_stl_non_finite() {
+        let triangles = [
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            [Vec3::ZERO, Vec3::new(f32::NAN, 0.0, 0.0), Vec3::Z],
+            [Vec3::X, Vec3::Y, Vec3::Z],
+        ];
+        let bytes = build_binary_stl(&triangles);
+
+        let result = parse_binary_stl(&bytes);
+        assert!(matches!(
+            result,
+            Err(MeshError::NonFiniteVertex { face_index: 1 })
+        ));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "face 1 has a vertex with a NaN or infinite coordinate"
+        );
+
+        let skipped = parse_binary_stl_skip_invalid(&bytes).unwrap();
+        assert_eq!(skipped, [triangles[0], triangles[2]]);
+    }
+}
@@ -0,0 +1,582 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use glam::f32::{Affine3A, Vec3};
+
+use super::archive::read_entry;
+use super::obj::parse_error;
+use super::{check_finite_faces, Mesh, MeshError};
+
+/// Path of the model part inside a 3MF archive.
+const MODEL_PATH: &str = "3D/3dmodel.model";
+
+/// Loads the meshes of a 3MF file.
+///
+/// # Remarks
+///
+/// - Only available with the `zip` feature.
+/// - Only the geometry of the `3D/3dmodel.model` part is read, materials, colors and any other
+///   extension are **ignored**.
+/// - One mesh is returned per `<item>` of the `<build>`, in order, with the `transform` of the item
+///   applied. An object made of `<components>` is flattened into a single mesh, each component
+///   placed by its own `transform`.
+/// - Coordinates are converted to millimeters from the `unit` of the model, and kept in the file's
+///   coordinate system: 3MF uses the Z axis as the build axis, the slicer uses the Y axis, see
+///   `Mesh::set_up_axis`.
+/// - A mesh with a face using a vertex with a **NaN or infinite** coordinate, once placed, fails
+///   the load with `MeshError::NonFiniteVertex`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the 3MF file to read.
+///
+/// # Returns
+///
+/// The meshes of the build, or a `MeshError` if the archive or its model is malformed.
+pub fn load_3mf(path: &Path) -> Result<Vec<Mesh>, MeshError> {
+    let bytes = fs::read(path)?;
+    let model = read_entry(&bytes, MODEL_PATH)?;
+    let model = String::from_utf8(model)
+        .map_err(|_| MeshError::Archive(format!("`{MODEL_PATH}` is not valid UTF-8")))?;
+    parse_model(&model)
+}
+
+/// An object of the `<resources>` of a model, see `load_3mf`.
+#[derive(Default)]
+struct Object {
+    mesh: Mesh,
+    /// The objects this object is made of, with their placement.
+    components: Vec<(Tag, Affine3A)>,
+}
+
+/// Parses the XML of a 3MF model part into the meshes of its build, see `load_3mf`.
+fn parse_model(xml: &str) -> Result<Vec<Mesh>, MeshError> {
+    let mut scale = 1.0;
+    let mut objects: HashMap<String, Object> = HashMap::new();
+    let mut items = Vec::new();
+
+    // The names of the open elements, and the id of the object being read along with its content.
+    let mut path: Vec<String> = Vec::new();
+    let mut object: Option<(String, Object)> = None;
+
+    for tag in Tags::new(xml) {
+        let tag = tag?;
+        if tag.closing {
+            path.pop();
+            if tag.name == "object" {
+                if let Some((id, object)) = object.take() {
+                    objects.insert(id, object);
+                }
+            }
+            continue;
+        }
+
+        let parent = path.last().map(String::as_str);
+        let opened = (!tag.self_closing).then(|| tag.name.clone());
+        match (parent, tag.name.as_str()) {
+            (None, "model") => {
+                if let Some(unit) = tag.attribute("unit") {
+                    scale = unit_scale(unit).ok_or_else(|| {
+                        parse_error(unit.line, unit.column, &unit.value, "a unit")
+                    })?;
+                }
+            }
+            (Some("resources"), "object") => {
+                let id = tag.required("id", "an `id` attribute")?.value.clone();
+                object = Some((id, Object::default()));
+            }
+            (Some("vertices"), "vertex") => {
+                if let Some((_, object)) = &mut object {
+                    let mut position = [0.0; 3];
+                    for (coordinate, (axis, expected)) in position.iter_mut().zip([
+                        ("x", "an `x` coordinate"),
+                        ("y", "a `y` coordinate"),
+                        ("z", "a `z` coordinate"),
+                    ]) {
+                        *coordinate = tag.required(axis, expected)?.parse(expected)?;
+                    }
+                    object
+                        .mesh
+                        .vertices
+                        .push(Vec3::from_array(position) * scale);
+                }
+            }
+            (Some("triangles"), "triangle") => {
+                if let Some((_, object)) = &mut object {
+                    let mut face = [0; 3];
+                    for (index, (name, expected)) in face.iter_mut().zip([
+                        ("v1", "a `v1` vertex index"),
+                        ("v2", "a `v2` vertex index"),
+                        ("v3", "a `v3` vertex index"),
+                    ]) {
+                        let attribute = tag.required(name, expected)?;
+                        *index = attribute.parse(expected)?;
+                        if *index as usize >= object.mesh.vertices.len() {
+                            return Err(attribute.unexpected("a declared vertex index"));
+                        }
+                    }
+                    object.mesh.faces.push(face);
+                }
+            }
+            (Some("components"), "component") => {
+                if let Some((_, object)) = &mut object {
+                    let transform = read_transform(&tag, scale)?;
+                    object.components.push((tag, transform));
+                }
+            }
+            (Some("build"), "item") => {
+                let transform = read_transform(&tag, scale)?;
+                items.push((tag, transform));
+            }
+            _ => {}
+        }
+
+        path.extend(opened);
+    }
+
+    items
+        .iter()
+        .map(|(tag, transform)| {
+            let mut mesh = Mesh::default();
+            resolve(&objects, tag, *transform, &mut mesh, 0)?;
+            check_finite_faces(&mesh)?;
+            Ok(mesh)
+        })
+        .collect()
+}
+
+/// Appends the geometry of the object referenced by `tag` to `mesh`, placed by `transform`.
+///
+/// `depth` counts the components followed so far, to reject objects that contain themselves.
+fn resolve(
+    objects: &HashMap<String, Object>,
+    tag: &Tag,
+    transform: Affine3A,
+    mesh: &mut Mesh,
+    depth: usize,
+) -> Result<(), MeshError> {
+    let id = tag.required("objectid", "an `objectid` attribute")?;
+    let object = objects
+        .get(&id.value)
+        .filter(|_| depth <= objects.len())
+        .ok_or_else(|| id.unexpected("the id of a declared object"))?;
+
+    let offset = mesh.vertices.len() as u32;
+    mesh.vertices.extend(
+        object
+            .mesh
+            .vertices
+            .iter()
+            .map(|&v| transform.transform_point3(v)),
+    );
+    // A mirroring placement turns the faces inside out, rewind them like `Mesh::transform`.
+    let mirrored = transform.matrix3.determinant() < 0.0;
+    mesh.faces.extend(object.mesh.faces.iter().map(|face| {
+        let mut face = face.map(|i| i + offset);
+        if mirrored {
+            face.swap(1, 2);
+        }
+        face
+    }));
+
+    for (component, placement) in &object.components {
+        resolve(objects, component, transform * *placement, mesh, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Reads the optional `transform` attribute of an item or a component, in millimeters.
+///
+/// The 12 numbers are the rows of a 4x3 matrix applied to row vectors, so the first three rows are
+/// the images of the axes and the last one is the translation.
+fn read_transform(tag: &Tag, scale: f32) -> Result<Affine3A, MeshError> {
+    let Some(attribute) = tag.attribute("transform") else {
+        return Ok(Affine3A::IDENTITY);
+    };
+    let mut matrix = [0.0; 12];
+    let mut numbers = attribute.value.split_ascii_whitespace();
+    for value in matrix.iter_mut() {
+        *value = numbers
+            .next()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| attribute.unexpected("12 numbers"))?;
+    }
+    if numbers.next().is_some() {
+        return Err(attribute.unexpected("12 numbers"));
+    }
+
+    let mut transform = Affine3A::from_cols_array(&matrix);
+    transform.translation *= scale;
+    Ok(transform)
+}
+
+/// Returns how many millimeters one unit of a 3MF model is, or `None` for an unknown unit.
+fn unit_scale(unit: &Attribute) -> Option<f32> {
+    match unit.value.as_str() {
+        "micron" => Some(0.001),
+        "millimeter" => Some(1.0),
+        "centimeter" => Some(10.0),
+        "inch" => Some(25.4),
+        "foot" => Some(304.8),
+        "meter" => Some(1000.0),
+        _ => None,
+    }
+}
+
+/// An attribute of an XML tag along with the 1-based position of its value.
+struct Attribute {
+    name: String,
+    value: String,
+    line: usize,
+    column: usize,
+}
+
+impl Attribute {
+    fn unexpected(&self, expected: &'static str) -> MeshError {
+        parse_error(self.line, self.column, &self.value, expected)
+    }
+
+    fn parse<T: std::str::FromStr>(&self, expected: &'static str) -> Result<T, MeshError> {
+        self.value.parse().map_err(|_| self.unexpected(expected))
+    }
+}
+
+/// An opening, closing or self-closing XML tag along with the 1-based position of its name.
+struct Tag {
+    name: String,
+    attributes: Vec<Attribute>,
+    closing: bool,
+    self_closing: bool,
+    line: usize,
+    column: usize,
+}
+
+impl Tag {
+    fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    fn required(&self, name: &str, expected: &'static str) -> Result<&Attribute, MeshError> {
+        self.attribute(name)
+            .ok_or_else(|| parse_error(self.line, self.column, &self.name, expected))
+    }
+}
+
+/// Splits an XML document into its tags, skipping text, comments, declarations and processing
+/// instructions.
+///
+/// This is not a validating parser: it is only as strict as needed to read the geometry of a 3MF
+/// model, entities in attribute values are not decoded and namespace prefixes are kept as part of
+/// the names.
+struct Tags<'a> {
+    xml: &'a str,
+    /// The byte offset of the next character to read.
+    offset: usize,
+    /// The byte offset, 1-based line and column of the last located character, so locating the
+    /// next one only scans the text in between.
+    located: Cell<(usize, usize, usize)>,
+}
+
+impl<'a> Tags<'a> {
+    fn new(xml: &'a str) -> Self {
+        Tags {
+            xml,
+            offset: 0,
+            located: Cell::new((0, 1, 1)),
+        }
+    }
+
+    /// Returns the 1-based line and column of the character at the byte offset `at`.
+    fn locate(&self, at: usize) -> (usize, usize) {
+        let (mut from, mut line, mut column) = self.located.get();
+        if at < from {
+            (from, line, column) = (0, 1, 1);
+        }
+        for c in self.xml[from..at].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        self.located.set((at, line, column));
+        (line, column)
+    }
+
+    fn error(&self, at: usize, expected: &'static str) -> MeshError {
+        let (line, column) = self.locate(at);
+        match self.xml[at..].split_whitespace().next() {
+            Some(token) => parse_error(line, column, token, expected),
+            None => MeshError::UnexpectedEof { line, expected },
+        }
+    }
+
+    /// Returns the byte offset of the end of the name starting at `at`.
+    fn name_end(&self, at: usize) -> usize {
+        self.xml[at..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .map_or(self.xml.len(), |i| at + i)
+    }
+
+    /// Skips the whitespace starting at `at`, returning the offset of the next character.
+    fn skip_whitespace(&self, at: usize) -> usize {
+        self.xml[at..]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(self.xml.len(), |i| at + i)
+    }
+
+    /// Reads the tag whose `<` is at `start`, leaving `offset` just after its `>`.
+    fn read_tag(&mut self, start: usize) -> Result<Tag, MeshError> {
+        let closing = self.xml[start + 1..].starts_with('/');
+        let name_start = start + 1 + closing as usize;
+        let name_end = self.name_end(name_start);
+        if name_end == name_start {
+            return Err(self.error(name_start, "an element name"));
+        }
+        let (line, column) = self.locate(name_start);
+        let mut tag = Tag {
+            name: self.xml[name_start..name_end].to_string(),
+            attributes: Vec::new(),
+            closing,
+            self_closing: false,
+            line,
+            column,
+        };
+
+        let mut at = name_end;
+        loop {
+            at = self.skip_whitespace(at);
+            let rest = &self.xml[at..];
+            if rest.starts_with('>') {
+                self.offset = at + 1;
+                return Ok(tag);
+            }
+            if rest.starts_with("/>") && !closing {
+                tag.self_closing = true;
+                self.offset = at + 2;
+                return Ok(tag);
+            }
+            if closing {
+                return Err(self.error(at, "`>`"));
+            }
+
+            let attribute_end = self.name_end(at);
+            if attribute_end == at {
+                return Err(self.error(at, "an attribute name, `>` or `/>`"));
+            }
+            let name = self.xml[at..attribute_end].to_string();
+            at = self.skip_whitespace(attribute_end);
+            if !self.xml[at..].starts_with('=') {
+                return Err(self.error(at, "`=`"));
+            }
+            at = self.skip_whitespace(at + 1);
+            let quote = match self.xml[at..].chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error(at, "a quoted attribute value")),
+            };
+            let value_start = at + 1;
+            let value_end = self.xml[value_start..]
+                .find(quote)
+                .map(|i| value_start + i)
+                .ok_or_else(|| self.error(self.xml.len(), "a closing quote"))?;
+            let (line, column) = self.locate(value_start);
+            tag.attributes.push(Attribute {
+                name,
+                value: self.xml[value_start..value_end].to_string(),
+                line,
+                column,
+            });
+            at = value_end + 1;
+        }
+    }
+}
+
+impl Iterator for Tags<'_> {
+    type Item = Result<Tag, MeshError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.offset + self.xml[self.offset..].find('<')?;
+            let rest = &self.xml[start..];
+            let skipped = [("<!--", "-->"), ("<?", "?>"), ("<!", ">")]
+                .iter()
+                .find(|(open, _)| rest.starts_with(open));
+            match skipped {
+                Some((open, close)) => match rest[open.len()..].find(close) {
+                    Some(i) => self.offset = start + open.len() + i + close.len(),
+                    None => {
+                        self.offset = self.xml.len();
+                        return Some(Err(self.error(self.xml.len(), close)));
+                    }
+                },
+                None => {
+                    let tag = self.read_tag(start);
+                    if tag.is_err() {
+                        self.offset = self.xml.len();
+                    }
+                    return Some(tag);
+                }
+            }
+        }
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use std::io::{Cursor, Write};
+
+    /// Test for the `load_3mf` function on a build of two objects, one of them moved by its item.
+    #[test]
+    fn test_load_3mf() {
+        let model = r#"<?xml version="1.0" encoding="UTF-8"?>
+<model unit="centimeter" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+  <resources>
+    <!-- A single triangle. -->
+    <object id="1" type="model">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0" />
+          <vertex x="1" y="0" z="0" />
+          <vertex x="0" y="1" z="0" />
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="1" v3="2" />
+        </triangles>
+      </mesh>
+    </object>
+    <object id="2" type='model'>
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/><vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="1" z="0"/><vertex x="0" y="0" z="1"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="2" v3="1"/><triangle v1="0" v2="1" v3="3"/>
+          <triangle v1="0" v2="3" v3="2"/><triangle v1="1" v2="2" v3="3"/>
+        </triangles>
+      </mesh>
+    </object>
+  </resources>
+  <build>
+    <item objectid="1" />
+    <item objectid="2" transform="1 0 0 0 1 0 0 0 1 5 0 2" />
+  </build>
+</model>"#;
+        let path = std::env::temp_dir().join("katana_test_load_3mf.3mf");
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("[Content_Types].xml", options).unwrap();
+        writer.write_all(b"<Types />").unwrap();
+        writer.start_file(MODEL_PATH, options).unwrap();
+        writer.write_all(model.as_bytes()).unwrap();
+        std::fs::write(&path, writer.finish().unwrap().into_inner()).unwrap();
+
+        let meshes = load_3mf(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].faces, vec![[0, 1, 2]]);
+        assert_eq!(meshes[0].vertices[1], Vec3::new(10.0, 0.0, 0.0));
+        assert_eq!(meshes[1].faces.len(), 4);
+        // The item moves the tetrahedron by 5cm along X and 2cm along Z.
+        let (min, max) = meshes[1].bounding_box().unwrap();
+        assert_eq!(min, Vec3::new(50.0, 0.0, 20.0));
+        assert_eq!(max, Vec3::new(60.0, 10.0, 30.0));
+    }
+
+    /// Test for the `parse_model` function on an object made of components.
+    #[test]
+    fn test_parse_model_components() {
+        let model = r#"<model>
+  <resources>
+    <object id="1">
+      <mesh>
+        <vertices><vertex x="0" y="0" z="0"/><vertex x="1" y="0" z="0"/><vertex x="0" y="1" z="0"/></vertices>
+        <triangles><triangle v1="0" v2="1" v3="2"/></triangles>
+      </mesh>
+    </object>
+    <object id="2">
+      <components>
+        <component objectid="1"/>
+        <component objectid="1" transform="1 0 0 0 1 0 0 0 1 0 0 3"/>
+      </components>
+    </object>
+  </resources>
+  <build><item objectid="2"/></build>
+</model>"#;
+        let meshes = parse_model(model).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].faces, vec![[0, 1, 2], [3, 4, 5]]);
+        assert_eq!(meshes[0].vertices[5], Vec3::new(0.0, 1.0, 3.0));
+    }
+
+    /// Test for the `parse_model` function rewinding the faces of mirrored items and components.
+    #[test]
+    fn test_parse_model_mirrored() {
+        let model = r#"<model>
+  <resources>
+    <object id="1">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/><vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="1" z="0"/><vertex x="0" y="0" z="1"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="2" v3="1"/><triangle v1="0" v2="1" v3="3"/>
+          <triangle v1="0" v2="3" v3="2"/><triangle v1="1" v2="2" v3="3"/>
+        </triangles>
+      </mesh>
+    </object>
+    <object id="2">
+      <components><component objectid="1" transform="1 0 0 0 1 0 0 0 -1 0 0 0"/></components>
+    </object>
+  </resources>
+  <build>
+    <item objectid="1"/>
+    <item objectid="1" transform="-1 0 0 0 1 0 0 0 1 0 0 0"/>
+    <item objectid="2" transform="-1 0 0 0 1 0 0 0 1 0 0 0"/>
+  </build>
+</model>"#;
+        let meshes = parse_model(model).unwrap();
+        assert_eq!(meshes.len(), 3);
+        let volume = meshes[0].volume();
+        assert!((volume - 1.0 / 6.0).abs() < 1e-6);
+
+        // A single mirror rewinds the faces, so the solid keeps pointing out.
+        assert_eq!(meshes[1].faces[0], [0, 1, 2]);
+        assert!((meshes[1].volume() - volume).abs() < 1e-6);
+
+        // Two nested mirrors cancel out and keep the winding.
+        assert_eq!(meshes[2].faces, meshes[0].faces);
+        assert!((meshes[2].volume() - volume).abs() < 1e-6);
+    }
+
+    /// Test for the `parse_model` function reporting the position of a bad vertex index.
+    #[test]
+    fn test_parse_model_errors() {
+        let model = "<model><resources><object id=\"1\"><mesh>\n<vertices/>\n  <triangles><triangle v1=\"0\" v2=\"1\" v3=\"2\"/></triangles>\n</mesh></object></resources></model>";
+        match parse_model(model) {
+            Err(MeshError::Parse {
+                line,
+                column,
+                token,
+                ..
+            }) => assert_eq!((line, column, token.as_str()), (3, 28, "0")),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+
+        let model = "<model><build><item objectid=\"7\"/></build></model>";
+        assert!(matches!(
+            parse_model(model),
+            Err(MeshError::Parse { token, .. }) if token == "7"
+        ));
+        assert!(matches!(
+            parse_model("<model><build"),
+            Err(MeshError::UnexpectedEof { .. })
+        ));
+    }
+}
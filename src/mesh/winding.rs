@@ -0,0 +1,115 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::Mesh;
+
+/// Flips the faces of a mesh so that all of them are wound consistently, facing outwards.
+///
+/// # Remarks
+///
+/// - Each connected group of faces is walked **breadth-first** from its first face. Two faces
+///   sharing an edge agree when they traverse it in opposite directions, so a neighbour traversing
+///   it in the same direction is flipped.
+/// - Once a group is consistent, it is flipped as a whole if its **signed volume** is negative, so
+///   the normals of a closed surface point outwards. For an open surface this is only a heuristic.
+/// - Edges are compared by **vertex index**, so the mesh should be welded first, as done by
+///   `Mesh::from_triangles`. Edges shared by more than two faces are followed like any other edge,
+///   the first face reaching a neighbour deciding its winding.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to repair.
+///
+/// # Returns
+///
+/// The number of faces that were flipped.
+pub fn unify_winding(mesh: &mut Mesh) -> usize {
+    let mut edge_faces: HashMap<[u32; 2], Vec<usize>> = HashMap::new();
+    for (i, face) in mesh.faces.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            edge_faces.entry([a.min(b), a.max(b)]).or_default().push(i);
+        }
+    }
+
+    let mut flipped = vec![false; mesh.faces.len()];
+    let mut visited = vec![false; mesh.faces.len()];
+    for seed in 0..mesh.faces.len() {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        let mut group = vec![seed];
+        let mut queue = VecDeque::from([seed]);
+        while let Some(i) = queue.pop_front() {
+            let face = mesh.faces[i];
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+                for &j in &edge_faces[&[a.min(b), a.max(b)]] {
+                    if visited[j] {
+                        continue;
+                    }
+                    visited[j] = true;
+                    if has_directed_edge(mesh.faces[j], a, b) {
+                        flip(&mut mesh.faces[j]);
+                        flipped[j] = !flipped[j];
+                    }
+                    group.push(j);
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        let volume: f32 = group
+            .iter()
+            .map(|&i| {
+                let [a, b, c] = mesh.triangle(i);
+                a.dot(b.cross(c))
+            })
+            .sum();
+        if volume < 0.0 {
+            for &i in &group {
+                flip(&mut mesh.faces[i]);
+                flipped[i] = !flipped[i];
+            }
+        }
+    }
+
+    flipped.iter().filter(|&&f| f).count()
+}
+
+/// Returns whether a face traverses the edge from `a` to `b` in this direction.
+fn has_directed_edge(face: [u32; 3], a: u32, b: u32) -> bool {
+    (0..3).any(|k| face[k] == a && face[(k + 1) % 3] == b)
+}
+
+/// Reverses the winding of a face.
+fn flip(face: &mut [u32; 3]) {
+    face.swap(1, 2);
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use glam::f32::Vec3;
+
+    /// Test for the `unify_winding` function on a cube with reversed faces.
+    #[test]
+    fn test_unify_winding_cube() {
+        let mut mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(2.0));
+        assert_eq!(unify_winding(&mut mesh.clone()), 0);
+
+        // Reversing the first face too makes the walk start from a wrong seed.
+        for i in [0, 5, 7] {
+            flip(&mut mesh.faces[i]);
+        }
+        assert_eq!(unify_winding(&mut mesh), 3);
+
+        let center = Vec3::ONE;
+        for [a, b, c] in mesh.iter_triangles() {
+            let normal = (b - a).cross(c - a);
+            assert!(normal.dot((a + b + c) / 3.0 - center) > 0.0);
+        }
+        assert!(crate::mesh::check_manifold(&mesh).is_manifold());
+    }
+}
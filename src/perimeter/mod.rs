@@ -0,0 +1,790 @@
+use glam::f32::Vec2;
+
+use crate::clip::{self, JoinType};
+use crate::geometry::{distance_to_segment, signed_area};
+use crate::infill::gap_fill;
+use crate::random::SplitMix64;
+use crate::slicer::{SlicedLayer, EPSILON};
+
+/// Miter length, in multiples of the offset distance, beyond which a corner is squared off instead.
+const MITER_LIMIT: f32 = 2.0;
+
+/// The narrowest thin wall worth printing, in multiples of the nozzle width.
+const THIN_WALL_MIN_WIDTH: f32 = 0.25;
+
+/// Offsets a closed polygon inwards by a signed distance.
+///
+/// # Remarks
+///
+/// - A **positive** distance shrinks the polygon, a **negative** distance grows it. The inside is
+///   deduced from the winding, so both clockwise and counter-clockwise polygons are supported.
+/// - Each edge is moved along its normal and neighbouring edges are joined with a **miter**, squared
+///   when the miter would be longer than twice the distance.
+/// - The moved edges may cross each other, e.g. in concave corners or when a narrow neck collapses.
+///   Like clipper, the raw offset loop is resolved by keeping the areas it winds around positively,
+///   so the result may contain several loops or none at all.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the polygon, without repeating the first one.
+/// * `distance` - The signed distance to move the boundary inwards.
+///
+/// # Returns
+///
+/// The loops of the offset polygon, wound like the input.
+pub fn offset_contour(contour: &[Vec2], distance: f32) -> Vec<Vec<Vec2>> {
+    let mut contour = remove_duplicates(contour);
+    let area = signed_area(&contour);
+    if contour.len() < 3 || area.abs() <= EPSILON || is_too_thin(&contour, distance) {
+        return Vec::new();
+    }
+
+    // Work on a counter-clockwise loop, whose inside is on the left of every edge.
+    let clockwise = area < 0.0;
+    if clockwise {
+        contour.reverse();
+    }
+
+    let mut loops = clip::offset(&[contour], -distance, JoinType::Miter(MITER_LIMIT));
+    if clockwise {
+        for piece in &mut loops {
+            piece.reverse();
+        }
+    }
+    loops
+}
+
+/// Offsets a region made of outer contours and holes inwards by a signed distance.
+///
+/// # Remarks
+///
+/// - Unlike `offset_contour`, the winding is **not** normalized: outer contours must be
+///   counter-clockwise and holes clockwise, as produced by the `clip` module.
+/// - A **positive** distance shrinks the outer contours and grows the holes, so the region shrinks.
+///   Corners are joined as in `offset_contour`.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region, without repeating their first points.
+/// * `distance` - The signed distance to move the boundary inwards.
+///
+/// # Returns
+///
+/// The loops of the offset region, outer contours counter-clockwise and holes clockwise.
+pub fn offset_region(region: &[Vec<Vec2>], distance: f32) -> Vec<Vec<Vec2>> {
+    if is_too_thin(&region.concat(), distance) {
+        return Vec::new();
+    }
+    let loops: Vec<Vec<Vec2>> = region
+        .iter()
+        .map(|contour| remove_duplicates(contour))
+        .filter(|contour| contour.len() >= 3 && signed_area(contour).abs() > EPSILON)
+        .collect();
+    clip::offset(&loops, -distance, JoinType::Miter(MITER_LIMIT))
+}
+
+/// Generates the nested wall loops of a closed polygon, from the outermost to the innermost.
+///
+/// # Remarks
+///
+/// - The loops are **centerlines**: the first one is inset by half a nozzle width so the extruded
+///   line is flush with the contour, and each following one by a full nozzle width more.
+/// - Narrow regions may split a wall into several loops, and generation stops early once the
+///   region is too thin for another wall.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the polygon, without repeating the first one.
+/// * `nozzle_width` - The width of an extruded line.
+/// * `count` - The number of walls to generate.
+///
+/// # Returns
+///
+/// The loops of every wall, outermost wall first.
+pub fn generate_shells(contour: &[Vec2], nozzle_width: f32, count: usize) -> Vec<Vec<Vec2>> {
+    let mut shells = Vec::new();
+    for wall in 0..count {
+        let loops = offset_contour(contour, nozzle_width * (wall as f32 + 0.5));
+        if loops.is_empty() {
+            break;
+        }
+        shells.extend(loops);
+    }
+    shells
+}
+
+/// Finds the features of a region too thin for a wall, and returns their centerlines.
+///
+/// # Remarks
+///
+/// - A feature narrower than `nozzle_width`, such as a fin, gets no loop from `generate_shells`, or
+///   two collapsed ones. It is printed instead as a single line along its middle, found like the
+///   gap fill, see `infill::gap_fill`.
+/// - The centerlines stop half a width away from the ends of the features, and features narrower
+///   than a quarter of `nozzle_width` are too thin to be printed and are skipped.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region, outer contours counter-clockwise and holes clockwise,
+///   without repeating their first points.
+/// * `nozzle_width` - The width of an extruded line.
+///
+/// # Returns
+///
+/// The open centerlines of the thin features, one path per feature.
+pub fn detect_thin_walls(region: &[Vec<Vec2>], nozzle_width: f32) -> Vec<Vec<Vec2>> {
+    let mut paths: Vec<Vec<Vec2>> = Vec::new();
+    for [a, b] in gap_fill(region, nozzle_width * THIN_WALL_MIN_WIDTH, nozzle_width) {
+        match paths.last_mut() {
+            Some(path) if path[path.len() - 1].abs_diff_eq(a, EPSILON) => path.push(b),
+            _ => paths.push(vec![a, b]),
+        }
+    }
+    paths
+}
+
+/// The order in which the walls of a region are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WallOrder {
+    /// Prints the outer wall first, before anything can push it out, for dimensional accuracy.
+    #[default]
+    OuterFirst,
+    /// Prints the outer wall last, leaning on the inner walls, which helps overhangs.
+    InnerFirst,
+}
+
+/// Generates the walls of a polygon like `generate_shells`, in printing order.
+///
+/// # Remarks
+///
+/// - Each loop is paired with the index of its wall, **0** for the outer wall, so the outer wall
+///   stays identifiable whatever the order, e.g. to place the seam on it.
+/// - The loops of a same wall keep the order of `generate_shells`.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the polygon, without repeating the first one.
+/// * `nozzle_width` - The width of an extruded line.
+/// * `count` - The number of walls to generate.
+/// * `order` - Whether the outer or the innermost wall is printed first.
+///
+/// # Returns
+///
+/// The index of the wall and the loop of every wall loop, in printing order.
+pub fn generate_ordered_shells(
+    contour: &[Vec2],
+    nozzle_width: f32,
+    count: usize,
+    order: WallOrder,
+) -> Vec<(usize, Vec<Vec2>)> {
+    let mut walls: Vec<Vec<Vec<Vec2>>> = Vec::new();
+    for wall in 0..count {
+        let loops = offset_contour(contour, nozzle_width * (wall as f32 + 0.5));
+        if loops.is_empty() {
+            break;
+        }
+        walls.push(loops);
+    }
+    if order == WallOrder::InnerFirst {
+        walls.reverse();
+    }
+
+    let depth = walls.len();
+    walls
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, loops)| {
+            let wall = match order {
+                WallOrder::OuterFirst => i,
+                WallOrder::InnerFirst => depth - 1 - i,
+            };
+            loops.into_iter().map(move |shell| (wall, shell))
+        })
+        .collect()
+}
+
+/// The settings of the fuzzy skin, a random jitter of the outer perimeters giving a rough texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzySkin {
+    /// The largest displacement of a point from the original perimeter, in millimeters.
+    pub thickness: f32,
+    /// The distance between two consecutive jittered points, in millimeters.
+    pub point_distance: f32,
+    /// Whether the first layer is fuzzed too. A smooth first layer sticks better to the bed.
+    pub first_layer: bool,
+}
+
+/// Resamples a closed contour and displaces its points randomly, giving it a fuzzy texture.
+///
+/// # Remarks
+///
+/// - The contour is replaced by points evenly spaced along its length, as close to `point_distance`
+///   apart as possible while closing the loop, and **at least 3** of them.
+/// - Each point is moved along the normal of the edge it lies on by a random distance between
+///   `-thickness` and `thickness`, so it stays **within `thickness`** of the original contour.
+/// - The displacement is **deterministic**: the same `seed` always gives the same contour.
+/// - Contours of fewer than 3 points and non-positive `point_distance` are left untouched.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `thickness` - The largest displacement of a point, in millimeters.
+/// * `point_distance` - The target distance between two consecutive points, in millimeters.
+/// * `seed` - The seed of the pseudo-random displacements.
+pub fn apply_fuzzy_skin(contour: &mut Vec<Vec2>, thickness: f32, point_distance: f32, seed: u64) {
+    let n = contour.len();
+    if n < 3 || point_distance <= 0.0 {
+        return;
+    }
+    let edge_length = |i: usize| contour[i].distance(contour[(i + 1) % n]);
+    let perimeter: f32 = (0..n).map(edge_length).sum();
+    let count = ((perimeter / point_distance).ceil() as usize).max(3);
+    let step = perimeter / count as f32;
+
+    let mut rng = SplitMix64::new(seed);
+    let mut fuzzed = Vec::with_capacity(count);
+    let (mut edge, mut edge_start) = (0, 0.0);
+    for k in 0..count {
+        let along = k as f32 * step;
+        while edge < n - 1 && along > edge_start + edge_length(edge) {
+            edge_start += edge_length(edge);
+            edge += 1;
+        }
+        let a = contour[edge];
+        let direction = (contour[(edge + 1) % n] - a).normalize_or_zero();
+        let point = a + direction * (along - edge_start);
+        fuzzed.push(point + direction.perp() * rng.next_signed() * thickness);
+    }
+
+    *contour = fuzzed;
+}
+
+/// Insets the bottom layers of a model to compensate for the elephant's foot, the bulge of the first
+/// layers squashed against the bed.
+///
+/// # Remarks
+///
+/// - Layer `i` of the `affected_layers` bottom ones is inset by `compensation * (1 - i /
+///   affected_layers)`, the full amount on the first layer **decreasing** to nothing above.
+/// - The contours and every wall are moved by the same amount, outer contours inwards and holes
+///   outwards, so the outer wall shrinks the part while the walls keep their spacing. The infill is
+///   left alone, the walls overlapping it a little more.
+/// - Each loop is inset on its own with `offset_contour`. A loop too small for the inset, which
+///   would **collapse** to nothing, is kept as it is, so thin features are not lost.
+///
+/// # Arguments
+///
+/// * `layers` - The layers of the model, from the bottom to the top.
+/// * `compensation` - The inset of the first layer, in millimeters.
+/// * `affected_layers` - The number of bottom layers to inset.
+pub fn elephant_foot_compensation(
+    layers: &mut [SlicedLayer],
+    compensation: f32,
+    affected_layers: usize,
+) {
+    for (i, layer) in layers.iter_mut().take(affected_layers).enumerate() {
+        let inset = compensation * (1.0 - i as f32 / affected_layers as f32);
+        for loops in [&mut layer.contours, &mut layer.perimeters] {
+            *loops = loops
+                .iter()
+                .flat_map(|contour| {
+                    // Holes are clockwise, their polygon grows as the part shrinks.
+                    let distance = inset * signed_area(contour).signum();
+                    let moved = offset_contour(contour, distance);
+                    if moved.is_empty() {
+                        vec![contour.clone()]
+                    } else {
+                        moved
+                    }
+                })
+                .collect();
+        }
+    }
+}
+
+/// A line whose width varies along it, see `variable_width`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidePath {
+    /// The centerline of the line, an open polyline.
+    pub points: Vec<Vec2>,
+    /// The width of the line at each point of the centerline.
+    pub widths: Vec<f32>,
+}
+
+/// Generates single lines along the middle of the narrow parts of a region, each as wide as the
+/// region is thick, instead of leaving them to fixed-width walls that either overlap or leave a gap.
+///
+/// # Remarks
+///
+/// - The centerlines follow the **medial axis** of the region: the boundary is sampled every
+///   quarter of `nominal_width`, and from each sample a circle tangent to the boundary is grown
+///   inwards until it touches another part of the boundary. Its center lies on the medial axis, and
+///   its diameter is the local width of the line.
+/// - Parts wider than `max_width` are left to the regular walls and infill, and parts narrower than
+///   `min_width` are too thin to be extruded. Both end the lines.
+/// - Circles touching two edges meeting at a corner, less than 120 degrees apart around the center,
+///   only trace the bisector of the corner and are **dropped**, like the branches Arachne prunes.
+/// - Both sides of a narrow part trace the same centerline, the longest trace is kept and the
+///   others are dropped where they run along it.
+///
+/// # Arguments
+///
+/// * `region` - The loops of the region, outer contours counter-clockwise and holes clockwise,
+///   without repeating their first points.
+/// * `nominal_width` - The width of a regular line, which sets the spacing of the samples.
+/// * `min_width` - The narrowest line worth extruding.
+/// * `max_width` - The widest line to extrude.
+///
+/// # Returns
+///
+/// The centerlines of the narrow parts with the width of the line at each of their points.
+pub fn variable_width(
+    region: &[Vec<Vec2>],
+    nominal_width: f32,
+    min_width: f32,
+    max_width: f32,
+) -> Vec<WidePath> {
+    if nominal_width <= 0.0 || min_width <= 0.0 || max_width < min_width {
+        return Vec::new();
+    }
+    let edges: Vec<[Vec2; 2]> = region
+        .iter()
+        .flat_map(|contour| {
+            (0..contour.len()).map(move |i| [contour[i], contour[(i + 1) % contour.len()]])
+        })
+        .filter(|[a, b]| a.distance(*b) > EPSILON)
+        .collect();
+    let spacing = nominal_width / 4.0;
+
+    let mut traces: Vec<Vec<(Vec2, f32)>> = Vec::new();
+    for contour in region {
+        let mut samples: Vec<Option<(Vec2, f32)>> = Vec::new();
+        for i in 0..contour.len() {
+            let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+            let length = a.distance(b);
+            if length <= EPSILON {
+                continue;
+            }
+            // The inside of the region is on the left of every edge.
+            let normal = ((b - a) / length).perp();
+            let count = (length / spacing).ceil() as usize;
+            for k in 0..count {
+                let point = a.lerp(b, (k as f32 + 0.5) / count as f32);
+                let medial = inscribed_circle(point, normal, &edges, max_width / 2.0)
+                    .filter(|&(radius, contact)| {
+                        let center = point + normal * radius;
+                        let spread = (point - center)
+                            .normalize()
+                            .dot((contact - center).normalize());
+                        2.0 * radius >= min_width && spread < -0.5
+                    })
+                    .map(|(radius, _)| (point + normal * radius, 2.0 * radius));
+                samples.push(medial);
+            }
+        }
+
+        // Start right after a gap, so a trace is not cut in two where the loop closes.
+        let start = samples.iter().position(Option::is_none).unwrap_or(0);
+        let mut trace: Vec<(Vec2, f32)> = Vec::new();
+        for k in 0..=samples.len() {
+            let sample = samples
+                .get((start + k) % samples.len().max(1))
+                .copied()
+                .flatten();
+            match sample {
+                Some(medial)
+                    if k < samples.len()
+                        && trace
+                            .last()
+                            .is_none_or(|&(last, _)| last.distance(medial.0) <= max_width) =>
+                {
+                    trace.push(medial)
+                }
+                _ => {
+                    if trace.len() >= 2 {
+                        traces.push(std::mem::take(&mut trace));
+                    }
+                    trace.clear();
+                    if let Some(medial) = sample.filter(|_| k < samples.len()) {
+                        trace.push(medial);
+                    }
+                }
+            }
+        }
+    }
+
+    // The longest traces are kept first, the others only where they do not run along a kept one.
+    traces.sort_by_key(|trace| std::cmp::Reverse(trace.len()));
+    let mut paths: Vec<WidePath> = Vec::new();
+    for trace in traces {
+        let mut piece: Vec<(Vec2, f32)> = Vec::new();
+        for k in 0..=trace.len() {
+            let covered = trace.get(k).is_none_or(|&(point, _)| {
+                paths.iter().any(|path| {
+                    path.points
+                        .windows(2)
+                        .any(|pair| distance_to_segment(point, pair[0], pair[1]) <= spacing)
+                })
+            });
+            if !covered {
+                piece.push(trace[k]);
+            } else if piece.len() >= 2 {
+                let (points, widths) = std::mem::take(&mut piece).into_iter().unzip();
+                paths.push(WidePath { points, widths });
+            } else {
+                piece.clear();
+            }
+        }
+    }
+    paths
+}
+
+/// Grows a circle tangent to the boundary at `point`, its center moving along `normal`, until it
+/// touches another part of the boundary.
+///
+/// Returns the radius of the circle and the other point it touches, or `None` when a circle of
+/// `max_radius` fits.
+fn inscribed_circle(
+    point: Vec2,
+    normal: Vec2,
+    edges: &[[Vec2; 2]],
+    max_radius: f32,
+) -> Option<(f32, Vec2)> {
+    // The edge of `point` is exactly `radius` away from the center, the tolerance absorbs rounding.
+    let contact = |radius: f32| {
+        let center = point + normal * radius;
+        edges
+            .iter()
+            .map(|&[a, b]| closest_point(center, a, b))
+            .filter(|q| q.distance(center) < radius * (1.0 - 1e-3) - 1e-6)
+            .min_by(|p, q| p.distance(center).total_cmp(&q.distance(center)))
+    };
+    let mut touched = contact(max_radius)?;
+    let (mut low, mut high) = (0.0, max_radius);
+    for _ in 0..24 {
+        let middle = (low + high) / 2.0;
+        match contact(middle) {
+            Some(q) => (high, touched) = (middle, q),
+            None => low = middle,
+        }
+    }
+    Some((low, touched))
+}
+
+/// Returns the point of the segment from `a` to `b` closest to `point`.
+fn closest_point(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 {
+        ((point - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    a + ab * t
+}
+
+/// Returns whether insetting by `distance` is bound to leave nothing, because the bounding box of the
+/// points is not even twice as wide as the distance.
+///
+/// Resolving such an offset would be wasted work, and a costly one: the moved edges all cross each
+/// other, which splits the raw offset into a number of slabs quadratic in its size.
+fn is_too_thin(points: &[Vec2], distance: f32) -> bool {
+    let Some(&first) = points.first() else {
+        return true;
+    };
+    let (min, max) = points
+        .iter()
+        .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+    2.0 * distance >= (max - min).min_element()
+}
+
+/// Drops consecutive points closer than `EPSILON`, including the closing point if repeated.
+fn remove_duplicates(contour: &[Vec2]) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = Vec::with_capacity(contour.len());
+    for &p in contour {
+        if points
+            .last()
+            .is_none_or(|last| !last.abs_diff_eq(p, EPSILON))
+        {
+            points.push(p);
+        }
+    }
+    while points.len() > 1 && points[0].abs_diff_eq(points[points.len() - 1], EPSILON) {
+        points.pop();
+    }
+    points
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `offset_contour` function insetting a 10mm square by 1mm.
+    #[test]
+    fn test_offset_contour_square() {
+        let loops = offset_contour(&crate::fixtures::square(Vec2::ZERO, 10.0), 1.0);
+        assert_eq!(loops.len(), 1);
+        let expected = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(9.0, 1.0),
+            Vec2::new(9.0, 9.0),
+            Vec2::new(1.0, 9.0),
+        ];
+        assert_eq!(loops[0].len(), 4);
+        for expected in expected {
+            assert!(loops[0].iter().any(|p| p.abs_diff_eq(expected, 1e-5)));
+        }
+        assert!(signed_area(&loops[0]) > 0.0);
+
+        // The same square wound clockwise is inset the same way.
+        let mut clockwise = crate::fixtures::square(Vec2::ZERO, 10.0);
+        clockwise.reverse();
+        let loops = offset_contour(&clockwise, 1.0);
+        assert!((signed_area(&loops[0]) + 64.0).abs() < 1e-3);
+
+        // Outsetting grows it and insetting past the middle makes it vanish.
+        let loops = offset_contour(&crate::fixtures::square(Vec2::ZERO, 10.0), -1.0);
+        assert!((signed_area(&loops[0]) - 144.0).abs() < 1e-3);
+        assert!(offset_contour(&crate::fixtures::square(Vec2::ZERO, 10.0), 6.0).is_empty());
+    }
+
+    /// Test for the `offset_contour` function splitting a dumbbell whose neck collapses.
+    #[test]
+    fn test_offset_contour_split() {
+        // Two 10x10 squares joined by a 2mm wide neck.
+        let dumbbell = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 4.0),
+            Vec2::new(14.0, 4.0),
+            Vec2::new(14.0, 0.0),
+            Vec2::new(24.0, 0.0),
+            Vec2::new(24.0, 10.0),
+            Vec2::new(14.0, 10.0),
+            Vec2::new(14.0, 6.0),
+            Vec2::new(10.0, 6.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        assert_eq!(offset_contour(&dumbbell, 0.5).len(), 1);
+
+        let loops = offset_contour(&dumbbell, 1.5);
+        assert_eq!(loops.len(), 2);
+        for piece in &loops {
+            assert!((signed_area(piece) - 49.0).abs() < 1e-3);
+        }
+    }
+
+    /// Test for the `offset_contour` function squaring off the sharp concave corners of a star.
+    #[test]
+    fn test_offset_contour_star() {
+        // Five spikes of radius 10 around valleys of radius 4.
+        let star: Vec<Vec2> = (0..10)
+            .map(|i| {
+                let radius = if i % 2 == 0 { 10.0 } else { 4.0 };
+                Vec2::from_angle(i as f32 * std::f32::consts::PI / 5.0) * radius
+            })
+            .collect();
+
+        // Every point of the inset stays at least the distance away from the valleys.
+        let loops = offset_contour(&star, 2.0);
+        assert_eq!(loops.len(), 1);
+        for valley in star.iter().skip(1).step_by(2) {
+            assert!(loops[0].iter().all(|p| p.distance(*valley) >= 2.0 - 1e-3));
+        }
+
+        // The valleys are 4mm from the center, so nothing survives a 5mm inset.
+        assert!(offset_contour(&star, 5.0).is_empty());
+    }
+
+    /// Test for the `offset_region` function shrinking a square with a square hole.
+    #[test]
+    fn test_offset_region_hole() {
+        let mut hole = vec![
+            Vec2::new(4.0, 4.0),
+            Vec2::new(6.0, 4.0),
+            Vec2::new(6.0, 6.0),
+            Vec2::new(4.0, 6.0),
+        ];
+        hole.reverse();
+        let mut loops = offset_region(&[crate::fixtures::square(Vec2::ZERO, 10.0), hole], 1.0);
+        loops.sort_by(|a, b| signed_area(b).total_cmp(&signed_area(a)));
+        assert_eq!(loops.len(), 2);
+        assert!((signed_area(&loops[0]) - 64.0).abs() < 1e-3);
+        assert!((signed_area(&loops[1]) + 16.0).abs() < 1e-3);
+
+        // The hole swallows the region once the walls around it are gone.
+        assert!(offset_region(&loops, 2.0).is_empty());
+    }
+
+    /// Test for the `generate_shells` function producing nested walls.
+    #[test]
+    fn test_generate_shells() {
+        let shells = generate_shells(&crate::fixtures::square(Vec2::ZERO, 10.0), 0.4, 3);
+        assert_eq!(shells.len(), 3);
+        for (wall, shell) in shells.iter().enumerate() {
+            let inset = 0.4 * (wall as f32 + 0.5);
+            assert!(shell
+                .iter()
+                .any(|p| p.abs_diff_eq(Vec2::splat(inset), 1e-5)));
+        }
+
+        assert_eq!(
+            generate_shells(&crate::fixtures::square(Vec2::ZERO, 1.5), 0.4, 3).len(),
+            2
+        );
+    }
+
+    /// Test for the `generate_ordered_shells` function in both wall orders.
+    #[test]
+    fn test_generate_ordered_shells() {
+        let outer_first = generate_ordered_shells(
+            &crate::fixtures::square(Vec2::ZERO, 10.0),
+            0.4,
+            3,
+            WallOrder::OuterFirst,
+        );
+        let walls: Vec<usize> = outer_first.iter().map(|(wall, _)| *wall).collect();
+        assert_eq!(walls, vec![0, 1, 2]);
+        let shells: Vec<Vec<Vec2>> = outer_first.into_iter().map(|(_, shell)| shell).collect();
+        assert_eq!(
+            shells,
+            generate_shells(&crate::fixtures::square(Vec2::ZERO, 10.0), 0.4, 3)
+        );
+
+        let inner_first = generate_ordered_shells(
+            &crate::fixtures::square(Vec2::ZERO, 10.0),
+            0.4,
+            3,
+            WallOrder::InnerFirst,
+        );
+        let walls: Vec<usize> = inner_first.iter().map(|(wall, _)| *wall).collect();
+        assert_eq!(walls, vec![2, 1, 0]);
+        assert_eq!(inner_first[2].1, shells[0]);
+    }
+
+    /// Test for the `apply_fuzzy_skin` function keeping the points close to the original contour.
+    #[test]
+    fn test_apply_fuzzy_skin() {
+        let original = crate::fixtures::square(Vec2::ZERO, 10.0);
+        let mut fuzzed = original.clone();
+        apply_fuzzy_skin(&mut fuzzed, 0.3, 0.5, 42);
+        assert_eq!(fuzzed.len(), 80);
+
+        let n = original.len();
+        let mut displaced = 0;
+        for &p in &fuzzed {
+            let distance = (0..n)
+                .map(|i| distance_to_segment(p, original[i], original[(i + 1) % n]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= 0.3 + 1e-5);
+            if distance > 1e-3 {
+                displaced += 1;
+            }
+        }
+        assert!(displaced > 40);
+
+        // The same seed gives the same skin, another seed a different one.
+        let mut again = original.clone();
+        apply_fuzzy_skin(&mut again, 0.3, 0.5, 42);
+        assert_eq!(again, fuzzed);
+        let mut other = original.clone();
+        apply_fuzzy_skin(&mut other, 0.3, 0.5, 43);
+        assert_ne!(other, fuzzed);
+    }
+
+    /// Test for the `detect_thin_walls` function on a fin narrower than the nozzle.
+    #[test]
+    fn test_detect_thin_walls_fin() {
+        let fin = vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 0.3),
+            Vec2::new(0.0, 0.3),
+        ];
+        let paths = detect_thin_walls(std::slice::from_ref(&fin), 0.4);
+        assert_eq!(paths.len(), 1);
+        let path = &paths[0];
+        assert!(path.iter().all(|p| (p.y - 0.15).abs() < 1e-3));
+        let (start, end) = (
+            path[0].x.min(path[path.len() - 1].x),
+            path[0].x.max(path[path.len() - 1].x),
+        );
+        assert!((start - 0.15).abs() < 0.05);
+        assert!((end - 9.85).abs() < 0.05);
+        // The fin gets no regular wall.
+        assert!(generate_shells(&fin, 0.4, 1).is_empty());
+
+        assert!(detect_thin_walls(&[crate::fixtures::square(Vec2::ZERO, 10.0)], 0.4).is_empty());
+    }
+
+    /// Test for the `elephant_foot_compensation` function on the bottom layers of a sliced cube.
+    #[test]
+    fn test_elephant_foot_compensation() {
+        let mesh = crate::fixtures::box_mesh(glam::Vec3::ZERO, glam::Vec3::splat(10.0));
+        let model = crate::slicer::Slicer::new().slice(&mesh).unwrap();
+        let mut layers = model.layers.clone();
+        elephant_foot_compensation(&mut layers, 0.2, 2);
+
+        // The first layer is inset by 0.2mm and the second by 0.1mm, the third is untouched.
+        let side = |layer: &SlicedLayer| signed_area(&layer.contours[0]).sqrt();
+        assert!((side(&layers[0]) - 9.6).abs() < 1e-3);
+        assert!((side(&layers[1]) - 9.8).abs() < 1e-3);
+        assert_eq!(layers[2], model.layers[2]);
+        assert!(side(&layers[0]) < side(&layers[2]));
+        let outer = |layer: &SlicedLayer| signed_area(&layer.perimeters[0]);
+        assert!(outer(&layers[0]) < outer(&layers[2]));
+        assert_eq!(layers[0].sparse_infill, model.layers[0].sparse_infill);
+
+        // A feature smaller than the inset is kept rather than lost.
+        let pin = vec![
+            Vec2::ZERO,
+            Vec2::new(0.3, 0.0),
+            Vec2::splat(0.3),
+            Vec2::new(0.0, 0.3),
+        ];
+        let mut tiny = [SlicedLayer {
+            height: 0.1,
+            thickness: 0.2,
+            contours: vec![pin.clone()],
+            perimeters: Vec::new(),
+            solid_infill: Vec::new(),
+            sparse_infill: Vec::new(),
+        }];
+        elephant_foot_compensation(&mut tiny, 0.2, 1);
+        assert_eq!(tiny[0].contours, vec![pin]);
+    }
+
+    /// Test for the `variable_width` function following the middle of a wedge as it widens.
+    #[test]
+    fn test_variable_width_wedge() {
+        let wedge = vec![Vec2::ZERO, Vec2::new(10.0, -0.4), Vec2::new(10.0, 0.4)];
+        let paths = variable_width(&[wedge], 0.4, 0.1, 1.0);
+
+        // The two long sides trace the same centerline, the corners of the base are pruned.
+        assert_eq!(paths.len(), 1);
+        let path = &paths[0];
+        assert_eq!(path.points.len(), path.widths.len());
+        assert!(path.points.iter().all(|p| p.y.abs() < 1e-3));
+        // The width is the thickness of the wedge, 0.08 per millimeter from the tip.
+        for (point, width) in path.points.iter().zip(&path.widths) {
+            assert!((width - 0.08 * point.x).abs() < 0.01, "{point} {width}");
+        }
+        let (narrowest, widest) = path
+            .widths
+            .iter()
+            .fold((f32::INFINITY, 0.0f32), |(min, max), &w| {
+                (min.min(w), max.max(w))
+            });
+        assert!(narrowest < 0.2);
+        assert!(widest > 0.7);
+
+        // A wedge everywhere wider than the widest line gets no path.
+        let wide = vec![
+            Vec2::new(0.0, -2.0),
+            Vec2::new(10.0, -4.0),
+            Vec2::new(10.0, 4.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert!(variable_width(&[wide], 0.4, 0.1, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use glam::f32::Vec2;
+
+use crate::random::SplitMix64;
+
+/// Where each perimeter loop starts and ends, leaving a visible seam on the surface of the print.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SeamPlacement {
+    /// Starts at the vertex nearest to the nozzle, minimizing travel.
+    #[default]
+    Nearest,
+    /// Starts at the vertex furthest towards the back of the bed, i.e. with the largest Y.
+    Rear,
+    /// Starts at a pseudo-random vertex, scattering the seam instead of lining it up.
+    Random,
+    /// Starts at the sharpest corner, preferably a concave one, where the seam is least visible.
+    SharpestCorner,
+    /// Starts at the vertex nearest to the seam of the layer below, lining the seams up in a straight
+    /// vertical line.
+    Aligned,
+}
+
+/// Finds the vertex a perimeter loop should start at.
+///
+/// # Remarks
+///
+/// - Ties are broken in favour of the **first** vertex of the loop.
+/// - `Random` is **deterministic**: the same `seed` always selects the same vertex, e.g. the index
+///   of the layer can be used to vary the seam from one layer to the next.
+/// - `SharpestCorner` measures the turn of the loop at each vertex. Concave corners, turning against
+///   the winding of the loop, are preferred over convex ones of any sharpness.
+/// - `Aligned` picks the vertex nearest to `position`, which is the seam of the layer below rather
+///   than the nozzle. See `aligned_reference` to find it among the seams of that layer.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `placement` - The seam placement strategy.
+/// * `position` - The current position of the nozzle, used by `Nearest`, or the seam to line up
+///   with, used by `Aligned`.
+/// * `seed` - The seed of the pseudo-random choice, used by `Random`.
+///
+/// # Returns
+///
+/// The index of the seam vertex, or 0 if the loop is empty.
+pub fn seam_index(contour: &[Vec2], placement: SeamPlacement, position: Vec2, seed: u64) -> usize {
+    let n = contour.len();
+    if n == 0 {
+        return 0;
+    }
+
+    let first_max_by = |key: &dyn Fn(usize) -> f32| {
+        (1..n).fold(0, |best, i| if key(i) > key(best) { i } else { best })
+    };
+    match placement {
+        SeamPlacement::Nearest | SeamPlacement::Aligned => {
+            first_max_by(&|i| -contour[i].distance_squared(position))
+        }
+        SeamPlacement::Rear => first_max_by(&|i| contour[i].y),
+        SeamPlacement::Random => (SplitMix64::new(seed).next_u64() % n as u64) as usize,
+        SeamPlacement::SharpestCorner => {
+            let winding = (0..n)
+                .map(|i| contour[i].perp_dot(contour[(i + 1) % n]))
+                .sum::<f32>()
+                .signum();
+            // Turn angles are in [-PI, PI], shifting the concave ones up puts them first.
+            first_max_by(&|i| {
+                let incoming = contour[i] - contour[(i + n - 1) % n];
+                let outgoing = contour[(i + 1) % n] - contour[i];
+                let turn = incoming.perp_dot(outgoing).atan2(incoming.dot(outgoing)) * winding;
+                if turn < 0.0 {
+                    -turn + 2.0 * std::f32::consts::PI
+                } else {
+                    turn
+                }
+            })
+        }
+    }
+}
+
+/// Finds the seam of the layer below that a loop should line up with, for `SeamPlacement::Aligned`.
+///
+/// # Remarks
+///
+/// - The seam **closest to the loop** is picked, measured to its nearest vertex, so each island
+///   follows its own seam when the layer below has several.
+/// - The layers may change shape: a seam that drifts off the loop is still followed, and an island
+///   splitting in two lines both halves up with the same seam.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `seams` - The seam points of the outer walls of the layer below.
+///
+/// # Returns
+///
+/// The seam to line up with, or `None` if there are no seams below or the loop is empty, e.g. on
+/// the first layer.
+pub fn aligned_reference(contour: &[Vec2], seams: &[Vec2]) -> Option<Vec2> {
+    let distance = |seam: Vec2| {
+        contour
+            .iter()
+            .map(|p| p.distance_squared(seam))
+            .fold(f32::INFINITY, f32::min)
+    };
+    seams
+        .iter()
+        .copied()
+        .filter(|_| !contour.is_empty())
+        .min_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+}
+
+/// Rotates the vertices of a perimeter loop so it starts at its seam, see `seam_index`.
+///
+/// # Arguments
+///
+/// * `contour` - The vertices of the loop, without repeating the first one.
+/// * `placement` - The seam placement strategy.
+/// * `position` - The current position of the nozzle, used by `Nearest`, or the seam to line up
+///   with, used by `Aligned`.
+/// * `seed` - The seed of the pseudo-random choice, used by `Random`.
+pub fn place_seam(contour: &mut [Vec2], placement: SeamPlacement, position: Vec2, seed: u64) {
+    let start = seam_index(contour, placement, position, seed);
+    contour.rotate_left(start);
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Builds a counter-clockwise L shape with a concave corner at (1, 1).
+    #[allow(dead_code)]
+    fn l_shape() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ]
+    }
+
+    /// Test for the `place_seam` function starting the loop at the rear vertex.
+    #[test]
+    fn test_place_seam_rear() {
+        let mut contour = l_shape();
+        place_seam(&mut contour, SeamPlacement::Rear, Vec2::ZERO, 0);
+        assert_eq!(contour[0], Vec2::new(1.0, 3.0));
+        assert_eq!(contour[1], Vec2::new(0.0, 3.0));
+        assert_eq!(contour.len(), 6);
+    }
+
+    /// Test for the `seam_index` function with the other placements.
+    #[test]
+    fn test_seam_index() {
+        let contour = l_shape();
+        let nearest = seam_index(&contour, SeamPlacement::Nearest, Vec2::new(2.5, 1.5), 0);
+        assert_eq!(nearest, 2);
+        assert_eq!(
+            seam_index(&contour, SeamPlacement::SharpestCorner, Vec2::ZERO, 0),
+            3
+        );
+
+        // The same seed gives the same vertex, and the seeds spread over the whole loop.
+        let random = |seed| seam_index(&contour, SeamPlacement::Random, Vec2::ZERO, seed);
+        assert_eq!(random(7), random(7));
+        let mut seen = [false; 6];
+        for seed in 0..100 {
+            seen[random(seed)] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        // Clockwise loops find the same concave corner.
+        let mut clockwise = contour.clone();
+        clockwise.reverse();
+        let corner = seam_index(&clockwise, SeamPlacement::SharpestCorner, Vec2::ZERO, 0);
+        assert_eq!(clockwise[corner], Vec2::ONE);
+    }
+
+    /// Test for the `aligned_reference` function picking the seam of the nearest island.
+    #[test]
+    fn test_aligned_reference() {
+        let contour = l_shape();
+        let seams = [Vec2::new(10.0, 10.0), Vec2::new(1.2, 3.1)];
+        let reference = aligned_reference(&contour, &seams).unwrap();
+        assert_eq!(reference, seams[1]);
+        let start = seam_index(&contour, SeamPlacement::Aligned, reference, 0);
+        assert_eq!(contour[start], Vec2::new(1.0, 3.0));
+
+        assert_eq!(aligned_reference(&contour, &[]), None);
+        assert_eq!(aligned_reference(&[], &seams), None);
+    }
+}
@@ -1,6 +1,121 @@
 use std::cmp::Ordering;
+use std::fmt;
 
-use glam::f32::Vec3;
+use glam::f32::{Vec2, Vec3};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::clip;
+use crate::contour::{build_contours, merge_collinear_segments};
+use crate::geometry::{dedup_points, signed_area, to_planar};
+use crate::mesh::{Mesh, MeshError};
+
+mod pipeline;
+mod plate;
+
+pub use pipeline::{SlicedLayer, SlicedModel, Slicer};
+pub use plate::{slice_plate, Plate, PlateContour, PlateLayer};
+
+/// Maximum absolute difference between two coordinates for them to be considered the same point.
+pub const EPSILON: f32 = 1e-6;
+
+/// Fraction of the layer thickness by which a slicing plane is moved off the vertices it passes
+/// through, see `PreparedMesh::plane_height`.
+const VERTEX_NUDGE: f32 = 1e-3;
+
+/// The order in which the layers of a mesh are returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SliceDirection {
+    /// From the lowest layer, on the bed, to the highest one.
+    #[default]
+    BottomUp,
+    /// From the highest layer down to the lowest one.
+    TopDown,
+}
+
+/// Options controlling how a mesh is sliced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceConfig {
+    /// Maximum absolute difference between two coordinates for them to be considered the same point.
+    pub epsilon: f32,
+    /// The thickness of the first layer, if it differs from the other layers. The following layers
+    /// are stacked from the top of the first one.
+    pub first_layer_height: Option<f32>,
+    /// The order of the layers. The layers are always placed from the bottom of the mesh, where the
+    /// first layer is printed, only their order changes.
+    pub direction: SliceDirection,
+    /// The largest turn, in degrees, between consecutive segments of a layer joined by
+    /// `merge_collinear_segments` before the layer is returned. Without it segments are kept as
+    /// sliced.
+    pub merge_collinear: Option<f32>,
+    /// The fraction of the inside of the object filled by sparse infill, from 0 to 1. Slicing itself
+    /// ignores it, it is kept for the later stages, e.g. `Plate::object_infill`.
+    pub infill_density: f32,
+}
+
+impl Default for SliceConfig {
+    fn default() -> Self {
+        SliceConfig {
+            epsilon: EPSILON,
+            first_layer_height: None,
+            direction: SliceDirection::BottomUp,
+            merge_collinear: None,
+            infill_density: 0.2,
+        }
+    }
+}
+
+/// Errors that can occur while slicing a mesh.
+#[derive(Debug)]
+pub enum SlicerError {
+    /// A vertex of the mesh has a NaN coordinate, so it cannot be ordered or sliced.
+    NanCoordinate { vertex: usize },
+    /// The mesh has no faces to slice.
+    EmptyMesh,
+    /// Every face of the mesh is degenerate, so no layer can have any segment.
+    DegenerateGeometry,
+    /// The mesh is flat, every vertex lying at `height` along the build axis, so it has no volume
+    /// for a layer to cut through.
+    DegenerateMesh { height: f32 },
+    /// A numeric parameter, such as a layer height, is not a positive finite number.
+    InvalidParameter { name: &'static str, value: f32 },
+    /// The mesh could not be loaded or parsed.
+    Mesh(MeshError),
+}
+
+impl fmt::Display for SlicerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlicerError::NanCoordinate { vertex } => {
+                write!(f, "vertex {vertex} has a NaN coordinate")
+            }
+            SlicerError::EmptyMesh => write!(f, "the mesh has no faces"),
+            SlicerError::DegenerateGeometry => write!(f, "every face of the mesh is degenerate"),
+            SlicerError::DegenerateMesh { height } => {
+                write!(f, "the mesh is flat, every vertex lies at height {height}")
+            }
+            SlicerError::InvalidParameter { name, value } => {
+                write!(f, "`{name}` must be a positive finite number, got {value}")
+            }
+            SlicerError::Mesh(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SlicerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SlicerError::Mesh(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MeshError> for SlicerError {
+    fn from(err: MeshError) -> Self {
+        SlicerError::Mesh(err)
+    }
+}
 
 /// Computes the intersection points between a line segment and an infinite horizontal plane at a given height.
 ///
@@ -9,6 +124,8 @@ use glam::f32::Vec3;
 /// - If the intersection point is **not within the bounds of the line segment**, the function returns an empty vector.
 /// - If the line segment is **parallel to the plane**, the function returns the two end points of the line segment.
 /// - If the line segment **intersects the plane**, the function returns the intersection point.
+/// - This is `slice_segment_plane` with a plane normal to `Vec3::Y`, the returned points are
+///   placed exactly at `current_layer_height`.
 ///
 /// # Arguments
 ///
@@ -18,25 +135,49 @@ use glam::f32::Vec3;
 /// # Returns
 ///
 /// A vector containing the intersection points between the line segment and the plane.
-fn slice_segment(line: &[Vec3; 2], current_layer_height: f32) -> Vec<Vec3> {
+pub fn slice_segment(line: &[Vec3; 2], current_layer_height: f32) -> Vec<Vec3> {
+    let plane_point = Vec3::new(0.0, current_layer_height, 0.0);
+    let mut intersections = slice_segment_plane(line, plane_point, Vec3::Y);
+    for point in &mut intersections {
+        point.y = current_layer_height;
+    }
+    intersections
+}
+
+/// Computes the intersection points between a line segment and an arbitrary infinite plane.
+///
+/// # Remarks
+///
+/// - If the intersection point is **not within the bounds of the line segment**, the function returns an empty vector.
+/// - If the line segment **lies in the plane**, the function returns the two end points of the line segment.
+/// - If the line segment **intersects the plane**, the function returns the intersection point.
+///
+/// # Arguments
+///
+/// * `line` - An array containing the start and end points of the line segment.
+/// * `plane_point` - Any point of the plane.
+/// * `plane_normal` - The normal of the plane, it does not need to be normalized.
+///
+/// # Returns
+///
+/// A vector containing the intersection points between the line segment and the plane.
+pub fn slice_segment_plane(line: &[Vec3; 2], plane_point: Vec3, plane_normal: Vec3) -> Vec<Vec3> {
     let line_direction = line[1] - line[0];
     let mut intersections = Vec::new();
 
-    let is_parallel = line_direction.y.total_cmp(&0.0) == Ordering::Equal;
-    let same_height = current_layer_height.total_cmp(&line[0].y) == Ordering::Equal;
+    let distance = (line[0] - plane_point).dot(plane_normal);
+    let speed = line_direction.dot(plane_normal);
+
+    let is_parallel = speed.total_cmp(&0.0) == Ordering::Equal;
+    let in_plane = distance.total_cmp(&0.0) == Ordering::Equal;
 
-    if is_parallel && same_height {
-        intersections.push(Vec3::new(line[0].x, current_layer_height, line[0].z));
-        intersections.push(Vec3::new(line[1].x, current_layer_height, line[1].z));
+    if is_parallel && in_plane {
+        intersections.push(line[0]);
+        intersections.push(line[1]);
     } else if !is_parallel {
-        let t = (current_layer_height - line[0].y) / line_direction.y;
+        let t = -distance / speed;
         if (0.0..=1.0).contains(&t) {
-            let intersection = line[0] + line_direction * t;
-            intersections.push(Vec3::new(
-                intersection.x,
-                current_layer_height,
-                intersection.z,
-            ));
+            intersections.push(line[0] + line_direction * t);
         }
     }
 
@@ -51,63 +192,857 @@ fn slice_segment(line: &[Vec3; 2], current_layer_height: f32) -> Vec<Vec3> {
 /// - If the triangle is **parallel to the plane**, the function returns the three vertices of the triangle.
 /// - If the triangle **intersects the plane**, the function returns the intersection points.
 /// - If the triangle is **completely above or below the plane**, the function returns an empty vector.
+/// - If the triangle is **degenerate** (see `is_degenerate`), the function returns an empty vector.
+/// - This is `slice_triangle_plane` with a plane normal to `Vec3::Y`, the returned points are
+///   placed exactly at `current_layer_height`.
 ///
 /// # Arguments
 ///
 /// * `triangle` - An array containing the three vertices of the triangle.
 /// * `current_layer_height` - The height of the plane at which to compute the intersections.
+/// * `config` - The slicing options, its `epsilon` is used to merge coincident intersection points.
+///
+/// # Returns
+///
+/// A vector containing the intersection points between the triangle and the plane.
+pub fn slice_triangle(
+    triangle: &[Vec3; 3],
+    current_layer_height: f32,
+    config: &SliceConfig,
+) -> Vec<Vec3> {
+    let plane_point = Vec3::new(0.0, current_layer_height, 0.0);
+    let mut intersections = slice_triangle_plane(triangle, plane_point, Vec3::Y, config);
+    for point in &mut intersections {
+        point.y = current_layer_height;
+    }
+    intersections
+}
+
+/// Computes the intersection points between a triangle and an arbitrary infinite plane.
+///
+/// # Remarks
+///
+/// - If the triangle **lies in the plane**, the function returns the three vertices of the triangle.
+/// - If the triangle **intersects the plane**, the function returns the intersection points.
+/// - If the triangle is **completely on one side of the plane**, the function returns an empty vector.
+/// - If the triangle is **degenerate** (see `is_degenerate`), the function returns an empty vector.
+///
+/// # Arguments
+///
+/// * `triangle` - An array containing the three vertices of the triangle.
+/// * `plane_point` - Any point of the plane.
+/// * `plane_normal` - The normal of the plane, it does not need to be normalized.
+/// * `config` - The slicing options, its `epsilon` is used to merge coincident intersection points.
 ///
 /// # Returns
 ///
 /// A vector containing the intersection points between the triangle and the plane.
-fn slice_triangle(triangle: &[Vec3; 3], current_layer_height: f32) -> Vec<Vec3> {
+pub fn slice_triangle_plane(
+    triangle: &[Vec3; 3],
+    plane_point: Vec3,
+    plane_normal: Vec3,
+    config: &SliceConfig,
+) -> Vec<Vec3> {
     let mut intersections = Vec::new();
 
-    let min_y = triangle.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
-    let max_y = triangle
-        .iter()
-        .map(|v| v.y)
-        .fold(f32::NEG_INFINITY, f32::max);
-    if current_layer_height < min_y || current_layer_height > max_y {
+    let distances = triangle.map(|v| (v - plane_point).dot(plane_normal));
+    if distances.iter().all(|&d| d > 0.0) || distances.iter().all(|&d| d < 0.0) {
+        return intersections;
+    }
+    if is_degenerate(triangle, config.epsilon) {
         return intersections;
     }
 
     for curr_ind in 0..3 {
         let next_ind = (curr_ind + 1) % 3;
         let line = [triangle[curr_ind], triangle[next_ind]];
-        let segment_intersections = slice_segment(&line, current_layer_height);
+        let segment_intersections = slice_segment_plane(&line, plane_point, plane_normal);
         intersections.extend(segment_intersections);
     }
 
-    intersections.sort_by(|a, b| compare_by_xyz(a, b));
-    intersections.dedup_by(|a, b| a.abs_diff_eq(*b, f32::EPSILON));
+    dedup_points(&mut intersections, config.epsilon);
 
     intersections
 }
 
+/// How an infinite horizontal plane intersects a triangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriangleSlice {
+    /// The plane cuts the triangle along a segment, oriented as in `slice_triangle_segment`.
+    Cut([Vec3; 2]),
+    /// The triangle lies in the plane, it is part of a flat top or bottom surface.
+    Coplanar([Vec3; 3]),
+    /// The plane misses the triangle or only touches it in a single point.
+    None,
+}
+
+/// Classifies how an infinite horizontal plane intersects a triangle.
+///
+/// # Remarks
+///
+/// - Unlike `slice_triangle`, a triangle **lying in the plane** is reported as `TriangleSlice::Coplanar`
+///   instead of as loose points, so flat faces are never mistaken for cuts during contour assembly.
+/// - A **degenerate** triangle is reported as `TriangleSlice::None`, it has no area to contribute.
+/// - A **cut** is oriented by the winding of the triangle, see `slice_triangle_segment`.
+///
+/// # Arguments
+///
+/// * `triangle` - An array containing the three vertices of the triangle, in winding order.
+/// * `height` - The height of the plane at which to classify the triangle.
+/// * `config` - The slicing options.
+///
+/// # Returns
+///
+/// The `TriangleSlice` describing the intersection.
+pub fn classify_triangle(triangle: &[Vec3; 3], height: f32, config: &SliceConfig) -> TriangleSlice {
+    if is_degenerate(triangle, config.epsilon) {
+        return TriangleSlice::None;
+    }
+    if triangle
+        .iter()
+        .all(|v| v.y.total_cmp(&height) == Ordering::Equal)
+    {
+        return TriangleSlice::Coplanar(*triangle);
+    }
+
+    let points = slice_triangle(triangle, height, config);
+    if points.len() != 2 {
+        return TriangleSlice::None;
+    }
+
+    let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+    let direction = normal.cross(Vec3::Y);
+    if (points[1] - points[0]).dot(direction) < 0.0 {
+        TriangleSlice::Cut([points[1], points[0]])
+    } else {
+        TriangleSlice::Cut([points[0], points[1]])
+    }
+}
+
+/// Computes the directed segment where an infinite horizontal plane cuts a triangle.
+///
+/// # Remarks
+///
+/// - The segment is **oriented by the winding** of the triangle: it runs along `normal × Y`, so
+///   seen from above with the XZ plane as a 2D frame, the solid lies on the **left** of the segment
+///   and the outer contours of a consistently wound mesh turn counter-clockwise.
+/// - If the plane only touches the triangle in **a single point**, the function returns `None`.
+/// - If the triangle is **parallel to the plane**, the function returns `None`.
+///
+/// # Arguments
+///
+/// * `triangle` - An array containing the three vertices of the triangle, in winding order.
+/// * `height` - The height of the plane at which to compute the intersection.
+/// * `config` - The slicing options.
+///
+/// # Returns
+///
+/// The start and end points of the intersection segment, if the plane cuts the triangle along a line.
+pub fn slice_triangle_segment(
+    triangle: &[Vec3; 3],
+    height: f32,
+    config: &SliceConfig,
+) -> Option<[Vec3; 2]> {
+    match classify_triangle(triangle, height, config) {
+        TriangleSlice::Cut(segment) => Some(segment),
+        TriangleSlice::Coplanar(_) | TriangleSlice::None => None,
+    }
+}
+
+/// Checks whether a triangle has (nearly) zero area, i.e. two of its vertices coincide or all three
+/// are collinear.
+///
+/// # Arguments
+///
+/// * `triangle` - An array containing the three vertices of the triangle.
+/// * `epsilon` - The length under which the cross product of two edges is considered zero.
+///
+/// # Returns
+///
+/// `true` if the triangle is degenerate and should be ignored when slicing.
+pub fn is_degenerate(triangle: &[Vec3; 3], epsilon: f32) -> bool {
+    let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+    normal.length() <= epsilon
+}
+
 /// Compares two `Vec3` points by their x, y, and z coordinates with a given maximum absolute difference.
+/// Coordinates falling in the same cell of a grid of size `max_abs_diff` are considered equal.
+///
+/// # Remarks
+///
+/// - Each coordinate is **snapped** to its cell, `floor(c / max_abs_diff)`, and the cells are
+///   compared exactly. Unlike comparing the differences, this is a **total order**, so sorting with
+///   it is deterministic even when a chain of points each within `max_abs_diff` of the next spans
+///   a wider range.
+/// - Coordinates closer than `max_abs_diff` but on both sides of a cell boundary are ordered, and
+///   coordinates of the same cell are equal even when their difference is almost `max_abs_diff`.
+/// - A non-positive `max_abs_diff` compares the coordinates exactly.
+/// - The comparison never panics: NaN coordinates are ordered by `f32::total_cmp`. The slicing
+///   functions reject meshes containing them up front with `SlicerError::NanCoordinate`.
 ///
 /// # Arguments
 ///
 /// * `a` - The first `Vec3` point to compare.
 /// * `b` - The second `Vec3` point to compare.
-/// * `max_abs_diff` - The maximum absolute difference allowed for the coordinates.
+/// * `max_abs_diff` - The size of the cells of the grid.
 ///
 /// # Returns
 ///
 /// An `Ordering` value indicating the relationship between the two points.
-fn compare_by_xyz(a: &Vec3, b: &Vec3) -> Ordering {
-    if a.x.total_cmp(&b.x) != Ordering::Equal {
-        return a.x.total_cmp(&b.x);
-    } else if a.y.total_cmp(&b.y) != Ordering::Equal {
-        return a.y.total_cmp(&b.y);
-    } else if a.z.total_cmp(&b.z) != Ordering::Equal {
-        return a.z.total_cmp(&b.z);
+pub fn compare_by_xyz(a: &Vec3, b: &Vec3, max_abs_diff: f32) -> Ordering {
+    let cell = |c: f32| {
+        if max_abs_diff > 0.0 {
+            (c / max_abs_diff).floor()
+        } else {
+            c
+        }
+    };
+    let compare = |a: f32, b: f32| cell(a).total_cmp(&cell(b));
+
+    compare(a.x, b.x)
+        .then_with(|| compare(a.y, b.y))
+        .then_with(|| compare(a.z, b.z))
+}
+
+/// The cross-section of a mesh by a single horizontal plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    /// The height of the slicing plane along the Y axis, at the middle of the layer.
+    pub height: f32,
+    /// The thickness of the layer, which spans `height ± thickness / 2`.
+    pub thickness: f32,
+    /// The segments where the plane cuts the faces of the mesh, oriented as in `slice_triangle_segment`.
+    pub segments: Vec<[Vec3; 2]>,
+}
+
+/// The number of islands and holes of a layer, see `Layer::topology`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayerTopology {
+    /// The number of outer contours, wound counter-clockwise.
+    pub outer_count: usize,
+    /// The number of holes, wound clockwise.
+    pub hole_count: usize,
+}
+
+impl Layer {
+    /// Computes the total length of the contours of the layer.
+    ///
+    /// # Remarks
+    ///
+    /// - The segments are assembled with `build_contours` first, open chains count as well as loops.
+    pub fn perimeter_length(&self) -> f32 {
+        let report = build_contours(&self.segments, EPSILON, EPSILON);
+        report
+            .loops()
+            .chain(&report.dangling)
+            .flat_map(|contour| contour.windows(2))
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum()
+    }
+
+    /// Assembles the closed contours of the layer into the region they enclose, in the (x, z) plane.
+    ///
+    /// # Remarks
+    ///
+    /// - Open chains are ignored, and overlapping loops are merged with `clip::union`.
+    ///
+    /// # Returns
+    ///
+    /// The loops of the region, outer contours counter-clockwise and holes clockwise, without
+    /// repeating their first points.
+    pub fn regions(&self) -> Vec<Vec<Vec2>> {
+        let contours: Vec<Vec<Vec2>> = build_contours(&self.segments, EPSILON, EPSILON)
+            .loops()
+            .map(|contour| {
+                contour[..contour.len() - 1]
+                    .iter()
+                    .map(|&p| to_planar(p))
+                    .collect()
+            })
+            .collect();
+        clip::union(&contours, &[])
+    }
+
+    /// Computes the area enclosed by the contours of the layer, in the (x, z) plane.
+    ///
+    /// # Remarks
+    ///
+    /// - Only **closed loops** enclose an area, open chains are ignored.
+    /// - Each loop contributes its signed area, so the clockwise holes are **subtracted** from the
+    ///   counter-clockwise outer contours surrounding them.
+    pub fn enclosed_area(&self) -> f32 {
+        build_contours(&self.segments, EPSILON, EPSILON)
+            .loops()
+            .map(|contour| {
+                contour
+                    .windows(2)
+                    .map(|pair| to_planar(pair[0]).perp_dot(to_planar(pair[1])))
+                    .sum::<f32>()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// Counts the outer contours and the holes of the layer, e.g. to spot where a defect of the mesh
+    /// opens a spurious hole.
+    ///
+    /// # Remarks
+    ///
+    /// - The segments are assembled with `build_contours`, which winds outer contours
+    ///   counter-clockwise and holes clockwise. Each closed loop is counted by the **sign** of its
+    ///   area, open chains and loops without area are ignored.
+    pub fn topology(&self) -> LayerTopology {
+        let mut topology = LayerTopology::default();
+        for contour in build_contours(&self.segments, EPSILON, EPSILON).loops() {
+            let planar: Vec<Vec2> = contour.iter().map(|&p| to_planar(p)).collect();
+            let area = signed_area(&planar);
+            if area > 0.0 {
+                topology.outer_count += 1;
+            } else if area < 0.0 {
+                topology.hole_count += 1;
+            }
+        }
+        topology
+    }
+}
+
+/// Slices a mesh into horizontal layers of a fixed thickness.
+///
+/// # Remarks
+///
+/// - Each layer is cut at its **mid-height**, the first plane sits at `y_min + layer_height / 2`.
+/// - Triangles whose vertical range does not straddle a plane are **skipped** without being sliced.
+///   The planes are swept from the bottom up over the triangles sorted by their lowest point, so a
+///   triangle is only considered by the layers it spans.
+/// - Faces touching the plane in a single point or lying on it do not produce segments.
+/// - A plane passing within `epsilon` of a vertex is **nudged** down by a thousandth of the layer
+///   height, or up if that lands on another vertex, so faces meeting at the vertex are never cut
+///   twice along a shared edge. The segments are still placed at the height of the layer.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `layer_height` - The thickness of each layer.
+///
+/// # Returns
+///
+/// The layers of the mesh from bottom to top, or a `SlicerError` if the mesh or the layer height is
+/// invalid, see `validate_mesh`.
+pub fn slice_mesh(mesh: &Mesh, layer_height: f32) -> Result<Vec<Layer>, SlicerError> {
+    slice_mesh_with_config(mesh, layer_height, &SliceConfig::default())
+}
+
+/// Slices a mesh into horizontal layers of a fixed thickness using the given options.
+///
+/// # Remarks
+///
+/// - With `SliceConfig::first_layer_height`, the bottom layer is cut at the middle of its own
+///   thickness and the following layers are cut `layer_height` apart from its top, so the layers
+///   still stack without gaps.
+/// - With `SliceDirection::TopDown`, the **same layers** are returned in reverse order: the thicker
+///   first layer, if any, is still the one on the bed.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `layer_height` - The thickness of each layer.
+/// * `config` - The slicing options.
+///
+/// # Returns
+///
+/// The layers of the mesh in the order of `SliceConfig::direction`, or a `SlicerError` if the mesh or
+/// the layer height is invalid.
+pub fn slice_mesh_with_config(
+    mesh: &Mesh,
+    layer_height: f32,
+    config: &SliceConfig,
+) -> Result<Vec<Layer>, SlicerError> {
+    validate_mesh(mesh, config)?;
+    validate_parameter("layer_height", layer_height)?;
+    if let Some(first_layer_height) = config.first_layer_height {
+        validate_parameter("first_layer_height", first_layer_height)?;
+    }
+
+    let mut layers: Vec<Layer> = SliceIter::new(mesh, layer_height, config.clone()).collect();
+    if config.direction == SliceDirection::TopDown {
+        layers.reverse();
+    }
+    Ok(layers)
+}
+
+/// Slices a mesh into horizontal layers of a fixed thickness, one layer at a time.
+///
+/// # Remarks
+///
+/// - Each layer is only sliced when the iterator is **advanced**, so a pipeline can process and drop
+///   the layers one by one, e.g. to stream G-code to disk, without holding all of them in memory.
+/// - The iterator keeps the sweep over the triangles between layers, so slicing every layer costs
+///   the same as with `slice_mesh`, and yields the **same layers** in the same order.
+/// - The mesh is validated upfront, the iterator itself cannot fail.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `layer_height` - The thickness of each layer.
+///
+/// # Returns
+///
+/// An iterator over the layers of the mesh from bottom to top, or a `SlicerError` if the mesh or the
+/// layer height is invalid.
+pub fn slice_mesh_iter(
+    mesh: &Mesh,
+    layer_height: f32,
+) -> Result<impl Iterator<Item = Layer>, SlicerError> {
+    let config = SliceConfig::default();
+    validate_mesh(mesh, &config)?;
+    validate_parameter("layer_height", layer_height)?;
+
+    Ok(SliceIter::new(mesh, layer_height, config))
+}
+
+/// Slices only the layers of a mesh whose slicing plane falls within a range of heights.
+///
+/// # Remarks
+///
+/// - The layers are placed exactly as by `slice_mesh`, starting from the bottom of the mesh, so the
+///   returned layers are **identical** to the matching subset of a full slice and previews align.
+/// - The bounds are heights along the **build axis**, Y, and both are inclusive.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `layer_height` - The thickness of each layer.
+/// * `z_min` - The lowest height of a slicing plane to keep.
+/// * `z_max` - The highest height of a slicing plane to keep.
+///
+/// # Returns
+///
+/// The layers within the range from bottom to top, or a `SlicerError` if the mesh, the layer height
+/// or the range is invalid.
+pub fn slice_mesh_range(
+    mesh: &Mesh,
+    layer_height: f32,
+    z_min: f32,
+    z_max: f32,
+) -> Result<Vec<Layer>, SlicerError> {
+    let config = SliceConfig::default();
+    validate_mesh(mesh, &config)?;
+    validate_parameter("layer_height", layer_height)?;
+    if z_min.is_nan() {
+        return Err(SlicerError::InvalidParameter {
+            name: "z_min",
+            value: z_min,
+        });
+    }
+    if z_max.is_nan() || z_min > z_max {
+        return Err(SlicerError::InvalidParameter {
+            name: "z_max",
+            value: z_max,
+        });
+    }
+
+    let prepared = PreparedMesh::new(mesh);
+    let mut active = ActiveSet::new(&prepared.ranges);
+    Ok(layer_heights(mesh, layer_height, &config)
+        .into_iter()
+        .filter(|height| (z_min..=z_max).contains(height))
+        .map(|height| {
+            let plane = prepared.plane_height(height, layer_height, &config);
+            let indices = active.advance(&prepared.ranges, plane);
+            prepared.slice_triangles(indices, height, plane, layer_height, &config)
+        })
+        .collect())
+}
+
+/// Slices a mesh into horizontal layers of a fixed thickness, spreading the layers across threads.
+///
+/// # Remarks
+///
+/// - Every layer is independent, so the heights are split into **contiguous chunks**, one per
+///   thread of the **rayon** pool, and sliced in parallel. Only available with the `rayon` feature.
+/// - The vertical range of each triangle is computed and sorted **once** and shared by all threads,
+///   each chunk sweeping up through it like `slice_mesh`.
+/// - The output is **identical** to `slice_mesh` and ordered from bottom to top regardless of which
+///   thread finishes first.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `layer_height` - The thickness of each layer.
+///
+/// # Returns
+///
+/// The layers of the mesh from bottom to top, or a `SlicerError` if the mesh or the layer height is
+/// invalid.
+#[cfg(feature = "rayon")]
+pub fn slice_mesh_parallel(mesh: &Mesh, layer_height: f32) -> Result<Vec<Layer>, SlicerError> {
+    let config = SliceConfig::default();
+    validate_mesh(mesh, &config)?;
+    validate_parameter("layer_height", layer_height)?;
+
+    let prepared = PreparedMesh::new(mesh);
+    let sweep = ActiveSet::new(&prepared.ranges);
+    let heights = layer_heights(mesh, layer_height, &config);
+    let chunk_size = heights.len().div_ceil(rayon::current_num_threads()).max(1);
+
+    Ok(heights
+        .par_chunks(chunk_size)
+        .flat_map_iter(|chunk| {
+            let (prepared, config) = (&prepared, &config);
+            let mut active = sweep.clone();
+            chunk.iter().map(move |&height| {
+                let plane = prepared.plane_height(height, layer_height, config);
+                let indices = active.advance(&prepared.ranges, plane);
+                prepared.slice_triangles(indices, height, plane, layer_height, config)
+            })
+        })
+        .collect())
+}
+
+/// Slices a mesh into horizontal layers whose thickness adapts to the slope of the surface.
+///
+/// # Remarks
+///
+/// - The **cusp height**, i.e. the stair-step error of a layer of thickness `h` over a facet whose
+///   unit normal has a vertical component `n.y`, is `h * |n.y|`. Each layer takes the largest
+///   thickness keeping the cusp under `max_error` for every facet within reach of the layer.
+/// - Vertical walls allow `max_h` while flat and shallow surfaces push the thickness down to `min_h`.
+/// - Each layer is still cut at its **mid-height** and carries its exact height and thickness.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to slice.
+/// * `min_h` - The minimum thickness of a layer.
+/// * `max_h` - The maximum thickness of a layer.
+/// * `max_error` - The maximum cusp height allowed.
+///
+/// # Returns
+///
+/// The layers of the mesh from bottom to top, or a `SlicerError` if the mesh or a parameter is
+/// invalid. A `min_h` larger than `max_h` is reported as an invalid `max_h`.
+pub fn slice_mesh_adaptive(
+    mesh: &Mesh,
+    min_h: f32,
+    max_h: f32,
+    max_error: f32,
+) -> Result<Vec<Layer>, SlicerError> {
+    let config = SliceConfig::default();
+    validate_mesh(mesh, &config)?;
+    validate_parameter("min_h", min_h)?;
+    validate_parameter("max_h", max_h)?;
+    validate_parameter("max_error", max_error)?;
+    if min_h > max_h {
+        return Err(SlicerError::InvalidParameter {
+            name: "max_h",
+            value: max_h,
+        });
+    }
+    let Some((min, max)) = mesh.bounding_box() else {
+        return Err(SlicerError::EmptyMesh);
+    };
+
+    let prepared = PreparedMesh::new(mesh);
+    let slopes: Vec<f32> = prepared
+        .triangles
+        .iter()
+        .map(|t| (t[1] - t[0]).cross(t[2] - t[0]).normalize_or_zero().y.abs())
+        .collect();
+
+    let mut layers = Vec::new();
+    let mut bottom = min.y;
+    loop {
+        let reach = bottom + max_h;
+        let thickness = prepared
+            .ranges
+            .iter()
+            .zip(&slopes)
+            .filter(|(&(lowest, highest), _)| lowest <= reach && highest >= bottom)
+            .filter(|(_, &slope)| slope > 0.0)
+            .map(|(_, &slope)| max_error / slope)
+            .fold(max_h, f32::min)
+            .clamp(min_h, max_h);
+
+        let height = bottom + thickness / 2.0;
+        if height > max.y {
+            break;
+        }
+        layers.push(prepared.slice_at(height, thickness, &config));
+        bottom += thickness;
+    }
+
+    Ok(layers)
+}
+
+/// Checks that a mesh can be sliced.
+///
+/// # Remarks
+///
+/// - A mesh without faces is rejected with `SlicerError::EmptyMesh`.
+/// - A vertex with a **NaN** coordinate is rejected with `SlicerError::NanCoordinate`, whether a
+///   face uses it or not.
+/// - A mesh whose faces are all degenerate, see `is_degenerate`, is rejected with
+///   `SlicerError::DegenerateGeometry`.
+/// - A **flat** mesh, e.g. a 2D drawing, whose height along the build axis is below `epsilon` is
+///   rejected with `SlicerError::DegenerateMesh`. It has no layer to print, and slicing it would
+///   silently return none.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to check.
+/// * `config` - The slicing options, its `epsilon` is used to detect degenerate faces and flat
+///   meshes.
+pub fn validate_mesh(mesh: &Mesh, config: &SliceConfig) -> Result<(), SlicerError> {
+    if mesh.faces.is_empty() {
+        return Err(SlicerError::EmptyMesh);
+    }
+    if let Some(vertex) = mesh.vertices.iter().position(|v| v.is_nan()) {
+        return Err(SlicerError::NanCoordinate { vertex });
+    }
+    if mesh
+        .iter_triangles()
+        .all(|t| is_degenerate(&t, config.epsilon))
+    {
+        return Err(SlicerError::DegenerateGeometry);
+    }
+    if let Some((min, max)) = mesh.bounding_box() {
+        if max.y - min.y < config.epsilon {
+            return Err(SlicerError::DegenerateMesh { height: min.y });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a numeric parameter is a positive finite number.
+fn validate_parameter(name: &'static str, value: f32) -> Result<(), SlicerError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
     } else {
-        return Ordering::Equal;
+        Err(SlicerError::InvalidParameter { name, value })
+    }
+}
+
+/// Computes the heights of the slicing planes of a mesh, at the mid-height of each layer.
+fn layer_heights(mesh: &Mesh, layer_height: f32, config: &SliceConfig) -> Vec<f32> {
+    let Some((min, max)) = mesh.bounding_box() else {
+        return Vec::new();
+    };
+
+    (0..)
+        .map(|i| layer_plane(min.y, i, layer_height, config).0)
+        .take_while(|&height| height <= max.y)
+        .collect()
+}
+
+/// Computes the height of the slicing plane and the thickness of the layer at `index`, for a mesh
+/// whose lowest point is at `bottom`.
+fn layer_plane(bottom: f32, index: usize, layer_height: f32, config: &SliceConfig) -> (f32, f32) {
+    match config.first_layer_height {
+        Some(first) if index == 0 => (bottom + first / 2.0, first),
+        Some(first) => (
+            bottom + first + (index as f32 - 0.5) * layer_height,
+            layer_height,
+        ),
+        None => (bottom + (index as f32 + 0.5) * layer_height, layer_height),
+    }
+}
+
+/// The triangles of a mesh along with their vertical ranges, computed once and shared by every layer.
+struct PreparedMesh {
+    triangles: Vec<[Vec3; 3]>,
+    ranges: Vec<(f32, f32)>,
+    /// The Y coordinate of every vertex, sorted.
+    vertex_heights: Vec<f32>,
+}
+
+impl PreparedMesh {
+    fn new(mesh: &Mesh) -> Self {
+        let triangles: Vec<[Vec3; 3]> = mesh.iter_triangles().collect();
+        let ranges = triangles.iter().map(vertical_range).collect();
+        let mut vertex_heights: Vec<f32> = mesh.vertices.iter().map(|v| v.y).collect();
+        vertex_heights.sort_by(f32::total_cmp);
+        PreparedMesh {
+            triangles,
+            ranges,
+            vertex_heights,
+        }
+    }
+
+    /// Returns the height at which to cut the layer whose slicing plane is at `height`.
+    ///
+    /// A plane passing through a vertex makes every face around it touch the plane, so the edges in
+    /// the plane are cut once from each side. The plane is moved by `VERTEX_NUDGE` of the layer
+    /// thickness, below the vertex if possible and above otherwise, to a height where the faces are
+    /// crossed cleanly.
+    fn plane_height(&self, height: f32, thickness: f32, config: &SliceConfig) -> f32 {
+        let nudge = thickness * VERTEX_NUDGE;
+        [height, height - nudge, height + nudge]
+            .into_iter()
+            .find(|&plane| !self.has_vertex_near(plane, config.epsilon))
+            .unwrap_or(height)
+    }
+
+    /// Checks whether a vertex lies within `epsilon` of the plane at `height`.
+    fn has_vertex_near(&self, height: f32, epsilon: f32) -> bool {
+        let first_above = self
+            .vertex_heights
+            .partition_point(|&y| y < height - epsilon);
+        self.vertex_heights
+            .get(first_above)
+            .is_some_and(|&y| y <= height + epsilon)
+    }
+
+    /// Slices the triangles straddling `height`, skipping the others without slicing them.
+    fn slice_at(&self, height: f32, thickness: f32, config: &SliceConfig) -> Layer {
+        let plane = self.plane_height(height, thickness, config);
+        let segments = self
+            .triangles
+            .iter()
+            .zip(&self.ranges)
+            .filter(|(_, &(min, max))| min <= plane && plane <= max)
+            .filter_map(|(triangle, _)| slice_triangle_segment(triangle, plane, config))
+            .map(|segment| segment.map(|p| Vec3::new(p.x, height, p.z)))
+            .collect();
+
+        Layer {
+            height,
+            thickness,
+            segments,
+        }
+    }
+
+    /// Slices the triangles at the given indices, which must all straddle `plane`, the height
+    /// returned by `plane_height` for the layer at `height`.
+    fn slice_triangles(
+        &self,
+        indices: &[usize],
+        height: f32,
+        plane: f32,
+        thickness: f32,
+        config: &SliceConfig,
+    ) -> Layer {
+        let mut segments = indices
+            .iter()
+            .filter_map(|&i| slice_triangle_segment(&self.triangles[i], plane, config))
+            .map(|segment| segment.map(|p| Vec3::new(p.x, height, p.z)))
+            .collect();
+        if let Some(angle_tol_deg) = config.merge_collinear {
+            merge_collinear_segments(&mut segments, angle_tol_deg);
+        }
+
+        Layer {
+            height,
+            thickness,
+            segments,
+        }
+    }
+}
+
+/// A sweep over the triangles of a mesh from the bottom to the top, tracking the ones straddling
+/// the current height.
+///
+/// Triangles are sorted by their lowest point once, so moving to the next height only visits the
+/// triangles entering or leaving the active set instead of testing every triangle again.
+#[derive(Clone)]
+struct ActiveSet {
+    /// Triangle indices sorted by the lowest Y coordinate of the triangle.
+    by_min: Vec<usize>,
+    /// The position in `by_min` of the next triangle to enter the active set.
+    next: usize,
+    /// The indices of the triangles straddling the current height, in increasing order.
+    active: Vec<usize>,
+}
+
+impl ActiveSet {
+    fn new(ranges: &[(f32, f32)]) -> Self {
+        let mut by_min: Vec<usize> = (0..ranges.len()).collect();
+        by_min.sort_by(|&a, &b| ranges[a].0.total_cmp(&ranges[b].0));
+        ActiveSet {
+            by_min,
+            next: 0,
+            active: Vec::new(),
+        }
+    }
+
+    /// Moves the sweep up to `height`, which must not be lower than the previous one, and returns the
+    /// indices of the triangles straddling it in increasing order.
+    ///
+    /// `ranges` must be the same vertical ranges the sweep was created with.
+    fn advance(&mut self, ranges: &[(f32, f32)], height: f32) -> &[usize] {
+        let entering = self.by_min[self.next..]
+            .iter()
+            .take_while(|&&i| ranges[i].0 <= height)
+            .count();
+        self.active
+            .extend_from_slice(&self.by_min[self.next..self.next + entering]);
+        self.next += entering;
+
+        self.active.retain(|&i| ranges[i].1 >= height);
+        if entering > 0 {
+            // Keep the segments in the same order as the faces of the mesh.
+            self.active.sort_unstable();
+        }
+        &self.active
+    }
+}
+
+/// The iterator of `slice_mesh_iter`, owning the prepared mesh and the sweep over its triangles.
+struct SliceIter {
+    prepared: PreparedMesh,
+    active: ActiveSet,
+    /// The bottom and the top of the mesh, or `None` for an empty mesh.
+    bounds: Option<(f32, f32)>,
+    /// The index of the next layer.
+    index: usize,
+    layer_height: f32,
+    config: SliceConfig,
+}
+
+impl SliceIter {
+    fn new(mesh: &Mesh, layer_height: f32, config: SliceConfig) -> Self {
+        let prepared = PreparedMesh::new(mesh);
+        let active = ActiveSet::new(&prepared.ranges);
+        SliceIter {
+            prepared,
+            active,
+            bounds: mesh.bounding_box().map(|(min, max)| (min.y, max.y)),
+            index: 0,
+            layer_height,
+            config,
+        }
+    }
+}
+
+impl Iterator for SliceIter {
+    type Item = Layer;
+
+    fn next(&mut self) -> Option<Layer> {
+        // Same heights as `layer_heights`, computed one at a time.
+        let (bottom, top) = self.bounds?;
+        let (height, thickness) = layer_plane(bottom, self.index, self.layer_height, &self.config);
+        if height > top {
+            return None;
+        }
+        self.index += 1;
+
+        let plane = self.prepared.plane_height(height, thickness, &self.config);
+        let indices = self.active.advance(&self.prepared.ranges, plane);
+        Some(
+            self.prepared
+                .slice_triangles(indices, height, plane, thickness, &self.config),
+        )
     }
 }
 
+/// Returns the lowest and highest Y coordinates of a triangle.
+fn vertical_range(triangle: &[Vec3; 3]) -> (f32, f32) {
+    let min = triangle.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+    let max = triangle
+        .iter()
+        .map(|v| v.y)
+        .fold(f32::NEG_INFINITY, f32::max);
+    (min, max)
+}
+
 mod tests {
     #[allow(unused_imports)]
     use super::*;
@@ -162,7 +1097,8 @@ mod tests {
     fn test_slice_triangle_parallel() {
         let triangle = [Vec3::ZERO, Vec3::X, Vec3::Z];
         let current_layer_height = 0.0;
-        let intersections = slice_triangle(&triangle, current_layer_height);
+        let intersections =
+            slice_triangle(&triangle, current_layer_height, &SliceConfig::default());
         assert_eq!(intersections.len(), 3);
         assert_eq!(intersections[0], Vec3::ZERO);
         assert_eq!(intersections[1], Vec3::Z);
@@ -175,14 +1111,16 @@ mod tests {
     fn test_slice_triangle_orthogonal() {
         let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
         let current_layer_height = 0.5;
-        let intersections = slice_triangle(&triangle, current_layer_height);
+        let intersections =
+            slice_triangle(&triangle, current_layer_height, &SliceConfig::default());
         assert_eq!(intersections.len(), 2);
         assert_eq!(intersections[0], Vec3::new(0.0, 0.5, 0.0));
         assert_eq!(intersections[1], Vec3::new(0.5, 0.5, 0.0));
 
         let triangle = [Vec3::ZERO, Vec3::X, Vec3::new(0.5, 1.0, 0.0)];
         let current_layer_height = 0.5;
-        let intersections = slice_triangle(&triangle, current_layer_height);
+        let intersections =
+            slice_triangle(&triangle, current_layer_height, &SliceConfig::default());
         assert_eq!(intersections.len(), 2);
         assert_eq!(intersections[0], Vec3::new(0.25, 0.5, 0.0));
         assert_eq!(intersections[1], Vec3::new(0.75, 0.5, 0.0));
@@ -192,26 +1130,563 @@ mod tests {
     fn test_compare_by_xyz() {
         let a = Vec3::new(0.0, 0.0, 0.0);
         let b = Vec3::new(0.0, 0.0, 0.0);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Equal);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Equal);
 
         let a = Vec3::new(0.0, 0.0, 0.0);
         let b = Vec3::new(0.0, 0.0, 0.0001);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Less);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Less);
 
         let a = Vec3::new(0.0, 0.0, 0.0);
         let b = Vec3::new(0.0, 0.0, 0.0001);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Less);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Less);
 
         let a = Vec3::new(0.0, 0.0, 1.0);
         let b = Vec3::new(0.0, 0.0001, 1.0);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Less);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Less);
 
         let a = Vec3::new(0.0, 1.0, 0.0);
         let b = Vec3::new(0.0, 0.0, 0.0);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Greater);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Greater);
 
         let a = Vec3::new(1.0, 1.0, 1.0);
         let b = Vec3::new(0.0, 1.0, 1.0);
-        assert_eq!(compare_by_xyz(&a, &b), Ordering::Greater);
+        assert_eq!(compare_by_xyz(&a, &b, EPSILON), Ordering::Greater);
+    }
+
+    /// Test for the `compare_by_xyz` function sorting a chain of points, each within `max_abs_diff`
+    /// of the next but spanning several times that range.
+    #[test]
+    fn test_compare_by_xyz_chain() {
+        let epsilon = 0.1;
+        let chain: Vec<Vec3> = (0..20)
+            .map(|i| Vec3::new(i as f32 * 0.06, (19 - i) as f32, 0.0))
+            .collect();
+        for pair in chain.windows(2) {
+            assert!((pair[1].x - pair[0].x).abs() <= epsilon);
+        }
+
+        // Every order of the input sorts to the same sequence, ordered by the cells of x.
+        let sort = |mut points: Vec<Vec3>| {
+            points.sort_by(|a, b| compare_by_xyz(a, b, epsilon));
+            points
+        };
+        let sorted = sort(chain.clone());
+        let mut reversed = chain.clone();
+        reversed.reverse();
+        assert_eq!(sort(reversed), sorted);
+        let mut shuffled = chain.clone();
+        shuffled.rotate_left(7);
+        shuffled.swap(2, 11);
+        assert_eq!(sort(shuffled), sorted);
+        for pair in sorted.windows(2) {
+            assert_ne!(
+                compare_by_xyz(&pair[0], &pair[1], epsilon),
+                Ordering::Greater
+            );
+            assert!((pair[0].x / epsilon).floor() <= (pair[1].x / epsilon).floor());
+        }
+    }
+
+    /// Test for the `slice_mesh` function on a unit cube.
+    #[test]
+    fn test_slice_mesh_cube() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::box_triangles(Vec3::ZERO, Vec3::ONE));
+        let layers = slice_mesh(&mesh, 0.25).unwrap();
+
+        let heights: Vec<f32> = layers.iter().map(|l| l.height).collect();
+        assert_eq!(heights, vec![0.125, 0.375, 0.625, 0.875]);
+        for layer in &layers {
+            // Each of the four side faces is made of two triangles cut by the plane.
+            assert_eq!(layer.segments.len(), 8);
+            assert!(layer.segments.iter().flatten().all(|p| p.y == layer.height));
+        }
+
+        assert!(matches!(
+            slice_mesh(&Mesh::default(), 0.25),
+            Err(SlicerError::EmptyMesh)
+        ));
+    }
+
+    /// Test for the `slice_triangle_segment` function reversing the segment when the winding flips.
+    #[test]
+    fn test_slice_triangle_segment_winding() {
+        let config = SliceConfig::default();
+        let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        let segment = slice_triangle_segment(&triangle, 0.5, &config).unwrap();
+        // The normal points towards +Z, so the segment runs along +Z x +Y = -X.
+        assert_eq!(
+            segment,
+            [Vec3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.5, 0.0)]
+        );
+
+        let flipped = [Vec3::ZERO, Vec3::Y, Vec3::X];
+        let reversed = slice_triangle_segment(&flipped, 0.5, &config).unwrap();
+        assert_eq!(reversed, [segment[1], segment[0]]);
+
+        assert_eq!(slice_triangle_segment(&triangle, 1.5, &config), None);
+        assert_eq!(
+            slice_triangle_segment(&[Vec3::ZERO, Vec3::X, Vec3::Z], 0.0, &config),
+            None
+        );
+    }
+
+    /// Test for the `slice_mesh` function producing counter-clockwise segments around a cube.
+    #[test]
+    fn test_slice_mesh_segment_orientation() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::box_triangles(Vec3::ZERO, Vec3::ONE));
+        let center = Vec3::new(0.5, 0.5, 0.5);
+        for segment in &slice_mesh(&mesh, 0.5).unwrap()[0].segments {
+            // Counter-clockwise in the XZ frame means the center is on the left of every segment.
+            let along = segment[1] - segment[0];
+            let to_center = center - segment[0];
+            assert!(along.x * to_center.z - along.z * to_center.x > 0.0);
+        }
+    }
+
+    /// Test for the `classify_triangle` function returning each of the `TriangleSlice` variants.
+    #[test]
+    fn test_classify_triangle() {
+        let config = SliceConfig::default();
+        let flat = [Vec3::ZERO, Vec3::Z, Vec3::X];
+        assert_eq!(
+            classify_triangle(&flat, 0.0, &config),
+            TriangleSlice::Coplanar(flat)
+        );
+        assert_eq!(classify_triangle(&flat, 0.5, &config), TriangleSlice::None);
+
+        let upright = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        assert_eq!(
+            classify_triangle(&upright, 0.5, &config),
+            TriangleSlice::Cut([Vec3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.5, 0.0)])
+        );
+        // Touching the top vertex only is not a cut.
+        assert_eq!(
+            classify_triangle(&upright, 1.0, &config),
+            TriangleSlice::None
+        );
+        assert_eq!(
+            classify_triangle(&upright, 2.0, &config),
+            TriangleSlice::None
+        );
+    }
+
+    /// Test for the `SliceConfig` epsilon controlling whether nearby points are merged.
+    #[test]
+    fn test_slice_config_epsilon() {
+        let default = SliceConfig::default();
+        let loose = SliceConfig {
+            epsilon: 1e-5,
+            ..SliceConfig::default()
+        };
+
+        let a = Vec3::ZERO;
+        let b = Vec3::new(5e-6, 0.0, 0.0);
+        assert_eq!(compare_by_xyz(&a, &b, default.epsilon), Ordering::Less);
+        assert_eq!(compare_by_xyz(&a, &b, loose.epsilon), Ordering::Equal);
+
+        // The plane runs along the tiny bottom edge, whose two ends are 5e-6 apart.
+        let triangle = [a, b, Vec3::new(0.0, 10.0, 0.0)];
+        assert_eq!(slice_triangle(&triangle, 0.0, &default), vec![a, b]);
+        assert_eq!(slice_triangle(&triangle, 0.0, &loose), vec![a]);
+    }
+
+    /// Test for the `slice_mesh_parallel` function matching the serial `slice_mesh` output.
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_slice_mesh_parallel_matches_serial() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::tube_triangles(3.0, 5.0, 20.0, 64));
+        let serial = slice_mesh(&mesh, 0.1).unwrap();
+        let parallel = slice_mesh_parallel(&mesh, 0.1).unwrap();
+        assert_eq!(serial.len(), 200);
+        assert_eq!(parallel, serial);
+        assert!(matches!(
+            slice_mesh_parallel(&Mesh::default(), 0.1),
+            Err(SlicerError::EmptyMesh)
+        ));
+    }
+
+    /// Benchmark of the `slice_mesh_parallel` function against the serial `slice_mesh`, run with
+    /// `cargo test --release --features rayon -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    #[cfg(feature = "rayon")]
+    fn bench_slice_mesh_parallel() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::tube_triangles(3.0, 5.0, 20.0, 4096));
+
+        let started = std::time::Instant::now();
+        let serial = slice_mesh(&mesh, 0.01).unwrap();
+        let serial_time = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let parallel = slice_mesh_parallel(&mesh, 0.01).unwrap();
+        let parallel_time = started.elapsed();
+
+        println!("serial: {serial_time:?}, parallel: {parallel_time:?}");
+        assert_eq!(parallel, serial);
+    }
+
+    /// Test for the `slice_mesh_range` function matching the same layers of a full slice.
+    #[test]
+    fn test_slice_mesh_range() {
+        let mesh = crate::fixtures::box_mesh(Vec3::new(0.0, 1.0, 0.0), Vec3::new(2.0, 11.0, 2.0));
+        let full = slice_mesh(&mesh, 0.2).unwrap();
+
+        let range = slice_mesh_range(&mesh, 0.2, 4.0, 6.0).unwrap();
+        let subset: Vec<Layer> = full
+            .iter()
+            .filter(|layer| (4.0..=6.0).contains(&layer.height))
+            .cloned()
+            .collect();
+        assert_eq!(range.len(), 10);
+        assert_eq!(range, subset);
+        assert!((range[0].height - 4.1).abs() < 1e-4);
+
+        assert!(slice_mesh_range(&mesh, 0.2, 20.0, 30.0).unwrap().is_empty());
+        assert!(matches!(
+            slice_mesh_range(&mesh, 0.2, 6.0, 4.0),
+            Err(SlicerError::InvalidParameter { name: "z_max", .. })
+        ));
+        assert!(matches!(
+            slice_mesh_range(&mesh, 0.2, f32::NAN, 4.0),
+            Err(SlicerError::InvalidParameter { name: "z_min", value }) if value.is_nan()
+        ));
+        assert!(matches!(
+            slice_mesh_range(&mesh, 0.2, 4.0, f32::NAN),
+            Err(SlicerError::InvalidParameter { name: "z_max", value }) if value.is_nan()
+        ));
+    }
+
+    /// Test for the `slice_mesh_iter` function yielding the same layers as `slice_mesh`.
+    #[test]
+    fn test_slice_mesh_iter() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::tube_triangles(3.0, 5.0, 4.0, 32));
+        let eager = slice_mesh(&mesh, 0.1).unwrap();
+        let mut lazy = slice_mesh_iter(&mesh, 0.1).unwrap();
+
+        // Layers are produced one by one, on demand.
+        let first = lazy.next().unwrap();
+        assert_eq!(first, eager[0]);
+        let rest: Vec<Layer> = lazy.collect();
+        assert_eq!(rest.len(), 39);
+        assert_eq!(rest, eager[1..]);
+
+        assert!(matches!(
+            slice_mesh_iter(&mesh, -1.0),
+            Err(SlicerError::InvalidParameter { .. })
+        ));
+    }
+
+    /// Test for the `is_degenerate` function and `slice_triangle` skipping degenerate faces.
+    #[test]
+    fn test_slice_triangle_degenerate() {
+        let config = SliceConfig::default();
+
+        let coincident = [Vec3::ZERO, Vec3::ZERO, Vec3::Y];
+        assert!(is_degenerate(&coincident, config.epsilon));
+        assert!(slice_triangle(&coincident, 0.5, &config).is_empty());
+
+        let collinear = [Vec3::ZERO, Vec3::ONE, Vec3::splat(2.0)];
+        assert!(is_degenerate(&collinear, config.epsilon));
+        assert!(slice_triangle(&collinear, 0.5, &config).is_empty());
+        assert_eq!(
+            classify_triangle(&collinear, 0.5, &config),
+            TriangleSlice::None
+        );
+
+        let regular = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        assert!(!is_degenerate(&regular, config.epsilon));
+        assert_eq!(slice_triangle(&regular, 0.5, &config).len(), 2);
+    }
+
+    /// Test for the `slice_mesh_adaptive` function on a 45-degree cone.
+    #[test]
+    fn test_slice_mesh_adaptive_cone() {
+        let mesh = Mesh::from_triangles(&crate::fixtures::cone_triangles(2.0, 2.0, 32));
+        let layers = slice_mesh_adaptive(&mesh, 0.05, 0.3, 0.1).unwrap();
+
+        for pair in layers.windows(2) {
+            let top_of_lower = pair[0].height + pair[0].thickness / 2.0;
+            let bottom_of_upper = pair[1].height - pair[1].thickness / 2.0;
+            assert!((top_of_lower - bottom_of_upper).abs() < 1e-5);
+        }
+        assert!(layers.iter().all(|l| (0.05..=0.3).contains(&l.thickness)));
+
+        // The flat base limits the first layers to the full cusp error, while the 45-degree flank
+        // allows `max_error / cos(45°)` in the middle of the cone.
+        assert!((layers[0].thickness - 0.1).abs() < 1e-5);
+        let middle = layers.iter().find(|l| l.height > 1.0).unwrap();
+        assert!((middle.thickness - 0.1 * std::f32::consts::SQRT_2).abs() < 1e-3);
+        assert!(layers[0].thickness < middle.thickness);
+
+        // A box has vertical walls, so only the layers near its flat faces are thinner than `max_h`.
+        let cube = Mesh::from_triangles(&crate::fixtures::box_triangles(
+            Vec3::ZERO,
+            Vec3::splat(2.0),
+        ));
+        let layers = slice_mesh_adaptive(&cube, 0.05, 0.3, 0.1).unwrap();
+        let middle = layers.iter().find(|l| l.height > 1.0).unwrap();
+        assert_eq!(middle.thickness, 0.3);
+    }
+
+    /// Test for the `ActiveSet` sweep testing far fewer triangles than every triangle on every layer.
+    #[test]
+    fn test_slice_mesh_sweep_tall_model() {
+        // A tall and thin tower of 100 stacked unit cubes.
+        let triangles: Vec<[Vec3; 3]> = (0..100)
+            .flat_map(|i| {
+                let bottom = Vec3::new(0.0, i as f32, 0.0);
+                crate::fixtures::box_triangles(bottom, bottom + Vec3::ONE)
+            })
+            .collect();
+        let mesh = Mesh::from_triangles(&triangles);
+        let prepared = PreparedMesh::new(&mesh);
+        let config = SliceConfig::default();
+        let heights = layer_heights(&mesh, 0.2, &config);
+        assert_eq!(heights.len(), 500);
+
+        let mut active = ActiveSet::new(&prepared.ranges);
+        let mut tested = 0;
+        for &height in &heights {
+            let plane = prepared.plane_height(height, 0.2, &config);
+            let indices = active.advance(&prepared.ranges, plane).to_vec();
+            tested += indices.len();
+            let layer = prepared.slice_triangles(&indices, height, plane, 0.2, &config);
+            assert_eq!(layer, prepared.slice_at(height, 0.2, &config));
+            assert_eq!(layer.segments.len(), 8);
+        }
+
+        // Only the 8 side triangles of the current cube are tested, instead of all 1200 triangles.
+        assert_eq!(tested, heights.len() * 8);
+        assert!(tested * 100 < heights.len() * mesh.faces.len());
+        assert_eq!(slice_mesh(&mesh, 0.2).unwrap().len(), heights.len());
+    }
+
+    /// Test for the `Layer::perimeter_length` and `Layer::enclosed_area` functions.
+    #[test]
+    fn test_layer_metrics() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(10.0, 1.0, 10.0));
+        let layer = &slice_mesh(&mesh, 1.0).unwrap()[0];
+        assert!((layer.perimeter_length() - 40.0).abs() < 1e-4);
+        assert!((layer.enclosed_area() - 100.0).abs() < 1e-3);
+
+        // A square tube whose walls are squares with circumradii 1 and 2.
+        let tube = Mesh::from_triangles(&crate::fixtures::tube_triangles(1.0, 2.0, 1.0, 4));
+        let layer = &slice_mesh(&tube, 1.0).unwrap()[0];
+        let sides = 4.0 * 2.0_f32.sqrt();
+        assert!((layer.perimeter_length() - 3.0 * sides).abs() < 1e-4);
+        assert!((layer.enclosed_area() - 6.0).abs() < 1e-4);
+    }
+
+    /// Test for the `Layer::topology` function on a square plate with two square holes.
+    #[test]
+    fn test_layer_topology() {
+        let square = |min: Vec2, size: f32| {
+            let corners = crate::fixtures::square(min, size);
+            (0..4).map(move |i| {
+                [corners[i], corners[(i + 1) % 4]].map(|p| crate::geometry::from_planar(p, 0.5))
+            })
+        };
+        // The holes are given counter-clockwise too, assembling the loops winds them clockwise.
+        let segments = square(Vec2::ZERO, 10.0)
+            .chain(square(Vec2::splat(2.0), 2.0))
+            .chain(square(Vec2::new(6.0, 2.0), 2.0))
+            .collect();
+        let layer = Layer {
+            height: 0.5,
+            thickness: 1.0,
+            segments,
+        };
+        assert_eq!(
+            layer.topology(),
+            LayerTopology {
+                outer_count: 1,
+                hole_count: 2,
+            }
+        );
+
+        let empty = Layer {
+            segments: Vec::new(),
+            ..layer
+        };
+        assert_eq!(empty.topology(), LayerTopology::default());
+    }
+
+    /// Test for the `slice_triangle_plane` function cutting a triangle with a plane tilted by 45 degrees.
+    #[test]
+    fn test_slice_triangle_plane_tilted() {
+        let triangle = [
+            Vec3::ZERO,
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+        ];
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+        let config = SliceConfig::default();
+
+        // The plane x + y = 1 crosses the two legs of the triangle.
+        let points = slice_triangle_plane(&triangle, Vec3::X, normal, &config);
+        assert_eq!(points, vec![Vec3::Y, Vec3::X]);
+        let segment = slice_segment_plane(&[Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0)], Vec3::X, normal);
+        assert_eq!(segment, vec![Vec3::X]);
+
+        // The plane x + y = 2 contains the hypotenuse, x + y = 3 misses the triangle.
+        let points = slice_triangle_plane(&triangle, Vec3::new(2.0, 0.0, 0.0), normal, &config);
+        assert_eq!(
+            points,
+            vec![Vec3::new(0.0, 2.0, 0.0), Vec3::new(2.0, 0.0, 0.0)]
+        );
+        let points = slice_triangle_plane(&triangle, Vec3::new(3.0, 0.0, 0.0), normal, &config);
+        assert!(points.is_empty());
+
+        // A horizontal plane gives the same result as `slice_triangle`.
+        let plane = slice_triangle_plane(&triangle, Vec3::new(0.0, 0.5, 0.0), Vec3::Y, &config);
+        assert_eq!(plane, slice_triangle(&triangle, 0.5, &config));
+    }
+
+    /// Test for the slicing functions rejecting invalid meshes and parameters with a `SlicerError`.
+    #[test]
+    fn test_slice_mesh_errors() {
+        let mut mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        mesh.vertices[3].y = f32::NAN;
+        match slice_mesh(&mesh, 0.25) {
+            Err(SlicerError::NanCoordinate { vertex }) => assert_eq!(vertex, 3),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        #[cfg(feature = "rayon")]
+        assert!(matches!(
+            slice_mesh_parallel(&mesh, 0.25),
+            Err(SlicerError::NanCoordinate { vertex: 3 })
+        ));
+
+        let cube = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        assert!(matches!(
+            slice_mesh(&cube, 0.0),
+            Err(SlicerError::InvalidParameter {
+                name: "layer_height",
+                ..
+            })
+        ));
+        assert!(matches!(
+            slice_mesh_adaptive(&cube, 0.3, 0.1, 0.1),
+            Err(SlicerError::InvalidParameter { name: "max_h", .. })
+        ));
+
+        let flat = Mesh::from_triangles(&[[Vec3::ZERO, Vec3::X, Vec3::X * 2.0]]);
+        assert!(matches!(
+            slice_mesh(&flat, 0.25),
+            Err(SlicerError::DegenerateGeometry)
+        ));
+
+        // A flat square has proper faces but no height to slice.
+        let square = Mesh::from_triangles(&[
+            [
+                Vec3::new(0.0, 2.0, 0.0),
+                Vec3::new(0.0, 2.0, 1.0),
+                Vec3::new(1.0, 2.0, 1.0),
+            ],
+            [
+                Vec3::new(0.0, 2.0, 0.0),
+                Vec3::new(1.0, 2.0, 1.0),
+                Vec3::new(1.0, 2.0, 0.0),
+            ],
+        ]);
+        assert!(matches!(
+            slice_mesh(&square, 0.25),
+            Err(SlicerError::DegenerateMesh { height }) if height == 2.0
+        ));
+    }
+
+    /// Test for the `slice_mesh` function when a slicing plane passes exactly through vertices of
+    /// the mesh: the top face of a cube, and a ring of vertices halfway up a column.
+    #[test]
+    fn test_slice_mesh_plane_through_vertices() {
+        // Planes sit at 0.125 and 0.375, the second one exactly on the top face.
+        let cube = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(1.0, 0.375, 1.0));
+        let layers = slice_mesh(&cube, 0.25).unwrap();
+        assert_eq!(layers.len(), 2);
+        let report = build_contours(&layers[1].segments, EPSILON, EPSILON);
+        assert_eq!(report.closed.len(), 1);
+        assert!(report.bridged.is_empty() && report.dangling.is_empty());
+        assert!((layers[1].enclosed_area() - 1.0).abs() < 1e-4);
+        assert!(layers[1].segments.iter().flatten().all(|p| p.y == 0.375));
+
+        // Two boxes stacked on each other, sharing the ring of vertices at 0.375 without a face
+        // between them.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(1.0, 0.375, 1.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(0.0, 0.375, 0.0),
+            Vec3::new(1.0, 0.75, 1.0),
+        ));
+        triangles.retain(|t| !t.iter().all(|v| v.y == 0.375));
+        let column = Mesh::from_triangles(&triangles);
+        #[allow(unused_mut)]
+        let mut results = vec![slice_mesh(&column, 0.25).unwrap()];
+        #[cfg(feature = "rayon")]
+        results.push(slice_mesh_parallel(&column, 0.25).unwrap());
+        for layers in results {
+            assert_eq!(layers.len(), 3);
+            for layer in &layers {
+                let report = build_contours(&layer.segments, EPSILON, EPSILON);
+                assert_eq!(report.closed.len(), 1);
+                assert!(report.bridged.is_empty() && report.dangling.is_empty());
+            }
+        }
+    }
+
+    /// Test for the `slice_mesh_with_config` function returning the same layers top down.
+    #[test]
+    fn test_slice_mesh_top_down() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(1.0, 0.9, 1.0));
+        let bottom_up = SliceConfig {
+            first_layer_height: Some(0.3),
+            ..SliceConfig::default()
+        };
+        let top_down = SliceConfig {
+            direction: SliceDirection::TopDown,
+            ..bottom_up.clone()
+        };
+        let layers = slice_mesh_with_config(&mesh, 0.2, &bottom_up).unwrap();
+        let mut reversed = slice_mesh_with_config(&mesh, 0.2, &top_down).unwrap();
+        assert_eq!(reversed.len(), 4);
+        // The thick first layer is still on the bed, returned last.
+        assert_eq!(reversed[3].thickness, 0.3);
+        reversed.reverse();
+        assert_eq!(reversed, layers);
+    }
+
+    /// Test for the `slice_mesh_with_config` function with a thicker first layer.
+    #[test]
+    fn test_slice_mesh_first_layer_height() {
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(1.0, 0.9, 1.0));
+        let config = SliceConfig {
+            first_layer_height: Some(0.3),
+            ..SliceConfig::default()
+        };
+        let layers = slice_mesh_with_config(&mesh, 0.2, &config).unwrap();
+
+        let heights: Vec<f32> = layers.iter().map(|l| l.height).collect();
+        let expected = [0.15, 0.4, 0.6, 0.8];
+        assert_eq!(heights.len(), expected.len());
+        for (height, expected) in heights.iter().zip(expected) {
+            assert!((height - expected).abs() < 1e-6);
+        }
+        assert_eq!(layers[0].thickness, 0.3);
+        assert!(layers[1..].iter().all(|l| l.thickness == 0.2));
+        // The layers stack without gaps, so the nozzle heights of the G-code line up.
+        for pair in layers.windows(2) {
+            let top = pair[0].height + pair[0].thickness / 2.0;
+            assert!((pair[1].height - pair[1].thickness / 2.0 - top).abs() < 1e-6);
+        }
+
+        let invalid = SliceConfig {
+            first_layer_height: Some(-0.3),
+            ..SliceConfig::default()
+        };
+        assert!(matches!(
+            slice_mesh_with_config(&mesh, 0.2, &invalid),
+            Err(SlicerError::InvalidParameter {
+                name: "first_layer_height",
+                ..
+            })
+        ));
     }
 }
@@ -0,0 +1,393 @@
+use glam::f32::{Affine3A, Mat3A, Vec2, Vec3};
+
+use super::{
+    slice_mesh_with_config, validate_parameter, SliceConfig, SliceDirection, SlicerError, EPSILON,
+};
+use crate::clip;
+use crate::infill;
+use crate::mesh::Mesh;
+use crate::perimeter::offset_region;
+use crate::solid::classify_regions;
+
+/// The settings of a print, turning a mesh into the walls and infill of every layer.
+///
+/// # Remarks
+///
+/// - Settings are chained from `Slicer::new` and checked by `Slicer::build`, e.g.
+///   `Slicer::new().layer_height(0.2).perimeters(3).infill_density(0.2).build()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slicer {
+    pub(crate) layer_height: f32,
+    pub(crate) epsilon: f32,
+    pub(crate) first_layer_height: Option<f32>,
+    pub(crate) nozzle_width: f32,
+    pub(crate) perimeters: usize,
+    pub(crate) infill_density: f32,
+    pub(crate) infill_angle: f32,
+    pub(crate) top_layers: usize,
+    pub(crate) bottom_layers: usize,
+}
+
+impl Default for Slicer {
+    fn default() -> Self {
+        Slicer {
+            layer_height: 0.2,
+            epsilon: EPSILON,
+            first_layer_height: None,
+            nozzle_width: 0.4,
+            perimeters: 2,
+            infill_density: 0.2,
+            infill_angle: 45.0,
+            top_layers: 3,
+            bottom_layers: 3,
+        }
+    }
+}
+
+impl Slicer {
+    /// Starts from the default settings: 0.2mm layers, 2 walls of a 0.4mm nozzle, 3 solid layers at
+    /// the top and the bottom and 20% infill.
+    pub fn new() -> Self {
+        Slicer::default()
+    }
+
+    /// Sets the thickness of each layer, in millimeters.
+    pub fn layer_height(mut self, layer_height: f32) -> Self {
+        self.layer_height = layer_height;
+        self
+    }
+
+    /// Sets the distance under which two points are considered the same, see `SliceConfig`.
+    pub fn epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Sets the thickness of the first layer, in millimeters, see `SliceConfig::first_layer_height`.
+    pub fn first_layer_height(mut self, first_layer_height: f32) -> Self {
+        self.first_layer_height = Some(first_layer_height);
+        self
+    }
+
+    /// Sets the width of an extruded line, in millimeters.
+    pub fn nozzle_width(mut self, nozzle_width: f32) -> Self {
+        self.nozzle_width = nozzle_width;
+        self
+    }
+
+    /// Sets the number of walls printed around every region.
+    pub fn perimeters(mut self, perimeters: usize) -> Self {
+        self.perimeters = perimeters;
+        self
+    }
+
+    /// Sets the fraction of the inside of the part filled by sparse infill, from 0 excluded to 1.
+    pub fn infill_density(mut self, infill_density: f32) -> Self {
+        self.infill_density = infill_density;
+        self
+    }
+
+    /// Sets the direction of the infill lines of the first layer, in degrees. Each layer turns the
+    /// lines by 90 degrees.
+    pub fn infill_angle(mut self, infill_angle: f32) -> Self {
+        self.infill_angle = infill_angle;
+        self
+    }
+
+    /// Sets the number of solid layers at the top of the print.
+    pub fn top_layers(mut self, top_layers: usize) -> Self {
+        self.top_layers = top_layers;
+        self
+    }
+
+    /// Sets the number of solid layers at the bottom of the print.
+    pub fn bottom_layers(mut self, bottom_layers: usize) -> Self {
+        self.bottom_layers = bottom_layers;
+        self
+    }
+
+    /// Checks the settings.
+    ///
+    /// # Returns
+    ///
+    /// The slicer, or a `SlicerError::InvalidParameter` naming the first setting that is not a
+    /// positive finite number, or an infill density above 1.
+    pub fn build(self) -> Result<Slicer, SlicerError> {
+        validate_parameter("layer_height", self.layer_height)?;
+        validate_parameter("epsilon", self.epsilon)?;
+        if let Some(first_layer_height) = self.first_layer_height {
+            validate_parameter("first_layer_height", first_layer_height)?;
+        }
+        validate_parameter("nozzle_width", self.nozzle_width)?;
+        validate_parameter("infill_density", self.infill_density)?;
+        if self.infill_density > 1.0 {
+            return Err(SlicerError::InvalidParameter {
+                name: "infill_density",
+                value: self.infill_density,
+            });
+        }
+        if !self.infill_angle.is_finite() {
+            return Err(SlicerError::InvalidParameter {
+                name: "infill_angle",
+                value: self.infill_angle,
+            });
+        }
+        Ok(self)
+    }
+
+    /// Slices a mesh and generates the walls and infill of every layer.
+    ///
+    /// # Remarks
+    ///
+    /// - The mesh is sliced with `slice_mesh_with_config`, and the area of each layer is split into
+    ///   solid and sparse regions with `classify_regions`.
+    /// - The walls are offset inwards from the region of the layer with `offset_region`, the
+    ///   outermost one first. The infill covers what is left inside the innermost wall.
+    /// - Solid regions are filled with `infill::monotonic` lines one nozzle width apart, sparse
+    ///   regions with an `infill::grid` spaced to match the infill density.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The mesh to slice.
+    ///
+    /// # Returns
+    ///
+    /// The sliced model, or a `SlicerError` if the mesh cannot be sliced, see `validate_mesh`.
+    pub fn slice(&self, mesh: &Mesh) -> Result<SlicedModel, SlicerError> {
+        let config = SliceConfig {
+            epsilon: self.epsilon,
+            first_layer_height: self.first_layer_height,
+            // Solid layers are found from the neighbours below and above, in printing order.
+            direction: SliceDirection::BottomUp,
+            merge_collinear: None,
+            infill_density: self.infill_density,
+        };
+        let layers = slice_mesh_with_config(mesh, self.layer_height, &config)?;
+        let flags = classify_regions(&layers, self.top_layers, self.bottom_layers);
+        // A grid prints two lines per spacing, one along each axis.
+        let sparse_spacing = 2.0 * self.nozzle_width / self.infill_density;
+
+        let layers = layers
+            .iter()
+            .zip(flags)
+            .enumerate()
+            .map(|(index, (layer, flags))| {
+                let region = layer.regions();
+                let perimeters = (0..self.perimeters)
+                    .flat_map(|wall| {
+                        offset_region(&region, self.nozzle_width * (wall as f32 + 0.5))
+                    })
+                    .collect();
+
+                let inside = offset_region(&region, self.nozzle_width * self.perimeters as f32);
+                let angle = self.infill_angle + 90.0 * (index % 2) as f32;
+                let solid = clip::intersection(&flags.solid, &inside);
+                let sparse = clip::intersection(&flags.sparse, &inside);
+
+                SlicedLayer {
+                    height: layer.height,
+                    thickness: layer.thickness,
+                    contours: region,
+                    perimeters,
+                    solid_infill: infill::monotonic(&solid, self.nozzle_width, angle),
+                    sparse_infill: infill::grid(&sparse, sparse_spacing),
+                }
+            })
+            .collect();
+
+        Ok(SlicedModel {
+            bounds: mesh.bounding_box().unwrap_or_default(),
+            layer_height: self.layer_height,
+            layers,
+            slicer: self.clone(),
+        })
+    }
+}
+
+/// The walls and infill of a single layer, in the (x, z) plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlicedLayer {
+    /// The height of the slicing plane along the Y axis, at the middle of the layer.
+    pub height: f32,
+    /// The thickness of the layer.
+    pub thickness: f32,
+    /// The outlines of the layer, see `Layer::regions`.
+    pub contours: Vec<Vec<Vec2>>,
+    /// The centerlines of the walls, outermost first, without repeating their first points.
+    pub perimeters: Vec<Vec<Vec2>>,
+    /// The lines filling the solid regions, in printing order.
+    pub solid_infill: Vec<[Vec2; 2]>,
+    /// The lines filling the sparse regions.
+    pub sparse_infill: Vec<[Vec2; 2]>,
+}
+
+/// A mesh sliced by `Slicer::slice`, ready to be turned into moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlicedModel {
+    /// The minimum and maximum corners of the bounding box of the mesh.
+    pub bounds: (Vec3, Vec3),
+    /// The thickness of each layer.
+    pub layer_height: f32,
+    /// The layers of the model, from the bottom to the top.
+    pub layers: Vec<SlicedLayer>,
+    /// The settings the model was sliced with.
+    pub slicer: Slicer,
+}
+
+impl SlicedModel {
+    /// Updates the model for a mesh moved or reshaped by `delta`, e.g. while it is dragged on the bed.
+    ///
+    /// # Remarks
+    ///
+    /// - A **translation on the bed**, along X and Z only, moves every layer as a whole: the contours,
+    ///   walls and infill are shifted in 2D without slicing the mesh again. The infill pattern thus
+    ///   moves with the part, where a full slice would keep it anchored to the origin.
+    /// - Any other transform, lifting, rotating or scaling the mesh, changes the cross-sections, so
+    ///   the transformed mesh is sliced again from scratch with the same settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The mesh the model was sliced from, before `delta`.
+    /// * `delta` - The transform applied to the mesh since it was sliced.
+    ///
+    /// # Returns
+    ///
+    /// The model of the transformed mesh, or a `SlicerError` if it has to be sliced again and cannot.
+    pub fn reslice_transformed(
+        &self,
+        mesh: &Mesh,
+        delta: Affine3A,
+    ) -> Result<SlicedModel, SlicerError> {
+        let offset = Vec3::from(delta.translation);
+        if !delta.matrix3.abs_diff_eq(Mat3A::IDENTITY, EPSILON) || offset.y.abs() > EPSILON {
+            let mut transformed = mesh.clone();
+            transformed.transform(delta);
+            return self.slicer.slice(&transformed);
+        }
+
+        let shift = Vec2::new(offset.x, offset.z);
+        let lines = |lines: &[[Vec2; 2]]| -> Vec<[Vec2; 2]> {
+            lines.iter().map(|line| line.map(|p| p + shift)).collect()
+        };
+        let loops = |loops: &[Vec<Vec2>]| -> Vec<Vec<Vec2>> {
+            loops
+                .iter()
+                .map(|l| l.iter().map(|&p| p + shift).collect())
+                .collect()
+        };
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| SlicedLayer {
+                height: layer.height,
+                thickness: layer.thickness,
+                contours: loops(&layer.contours),
+                perimeters: loops(&layer.perimeters),
+                solid_infill: lines(&layer.solid_infill),
+                sparse_infill: lines(&layer.sparse_infill),
+            })
+            .collect();
+
+        Ok(SlicedModel {
+            bounds: (self.bounds.0 + offset, self.bounds.1 + offset),
+            layer_height: self.layer_height,
+            layers,
+            slicer: self.slicer.clone(),
+        })
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `Slicer` builder with non-default settings on a cube.
+    #[test]
+    fn test_slicer_builder() {
+        let slicer = Slicer::new()
+            .layer_height(0.5)
+            .nozzle_width(0.5)
+            .perimeters(3)
+            .infill_density(0.5)
+            .infill_angle(0.0)
+            .top_layers(2)
+            .bottom_layers(1)
+            .build()
+            .unwrap();
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(10.0));
+        let model = slicer.slice(&mesh).unwrap();
+
+        assert_eq!(model.layer_height, 0.5);
+        assert_eq!(model.bounds, (Vec3::ZERO, Vec3::splat(10.0)));
+        assert_eq!(model.layers.len(), 20);
+        for (i, layer) in model.layers.iter().enumerate() {
+            assert_eq!(layer.height, 0.25 + 0.5 * i as f32);
+            assert_eq!(layer.perimeters.len(), 3);
+            // Only the bottom layer and the 2 top layers are solid.
+            let solid = !(1..18).contains(&i);
+            assert_eq!(!layer.solid_infill.is_empty(), solid);
+            assert_eq!(!layer.sparse_infill.is_empty(), !solid);
+        }
+
+        // The infill stays inside the innermost wall, which is inset by 2.5 nozzle widths.
+        for line in model.layers.iter().flat_map(|l| &l.solid_infill) {
+            for p in line {
+                assert!(p.cmpge(Vec2::splat(1.5 - 1e-3)).all());
+                assert!(p.cmple(Vec2::splat(8.5 + 1e-3)).all());
+            }
+        }
+        // Solid lines are a nozzle width apart, sparse ones 2 nozzle widths / 50% on both axes.
+        assert_eq!(model.layers[0].solid_infill.len(), 14);
+        assert_eq!(model.layers[5].sparse_infill.len(), 2 * 4);
+
+        assert!(matches!(
+            Slicer::new().layer_height(0.0).build(),
+            Err(SlicerError::InvalidParameter {
+                name: "layer_height",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Slicer::new().infill_density(1.5).build(),
+            Err(SlicerError::InvalidParameter {
+                name: "infill_density",
+                ..
+            })
+        ));
+    }
+
+    /// Test for the `SlicedModel::reslice_transformed` function shifting a model dragged on the bed.
+    #[test]
+    fn test_sliced_model_reslice_transformed() {
+        let slicer = Slicer::new().layer_height(0.5).build().unwrap();
+        let mesh = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::new(10.0, 2.0, 6.0));
+        let model = slicer.slice(&mesh).unwrap();
+
+        let delta = Affine3A::from_translation(Vec3::new(3.0, 0.0, -2.0));
+        let shifted = model.reslice_transformed(&mesh, delta).unwrap();
+        let mut moved = mesh.clone();
+        moved.transform(delta);
+        let resliced = slicer.slice(&moved).unwrap();
+
+        assert_eq!(shifted.bounds, resliced.bounds);
+        assert_eq!(shifted.layers.len(), resliced.layers.len());
+        let same = |a: &[Vec<Vec2>], b: &[Vec<Vec2>]| {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| {
+                    a.len() == b.len() && a.iter().zip(b).all(|(p, q)| p.abs_diff_eq(*q, 1e-4))
+                })
+        };
+        for (shifted, resliced) in shifted.layers.iter().zip(&resliced.layers) {
+            assert_eq!(shifted.height, resliced.height);
+            assert!(same(&shifted.contours, &resliced.contours));
+            assert!(same(&shifted.perimeters, &resliced.perimeters));
+        }
+
+        // A rotation cannot be shifted, the mesh is sliced again.
+        let rotation = Affine3A::from_rotation_y(0.3);
+        let rotated = model.reslice_transformed(&mesh, rotation).unwrap();
+        let mut turned = mesh.clone();
+        turned.transform(rotation);
+        assert_eq!(rotated, slicer.slice(&turned).unwrap());
+    }
+}
@@ -0,0 +1,269 @@
+use glam::f32::{Vec2, Vec3};
+
+use super::{
+    layer_plane, validate_mesh, validate_parameter, PreparedMesh, SliceConfig, SlicerError, EPSILON,
+};
+use crate::clip;
+use crate::contour::build_contours;
+use crate::geometry::to_planar;
+use crate::infill;
+use crate::mesh::Mesh;
+use crate::perimeter::offset_region;
+
+/// A closed contour of a plate layer, tagged with the object it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlateContour {
+    /// The index of the mesh of the contour in the slice of meshes given to `slice_plate`.
+    pub object: usize,
+    /// The points of the loop, with its first point repeated at the end as in `build_contours`.
+    pub points: Vec<Vec3>,
+}
+
+/// The cross-section of every object of a plate by a single horizontal plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlateLayer {
+    /// The height of the slicing plane along the Y axis, at the middle of the layer.
+    pub height: f32,
+    /// The thickness of the layer, which spans `height ± thickness / 2`.
+    pub thickness: f32,
+    /// The closed contours of the layer, grouped by object in the order of the meshes.
+    pub contours: Vec<PlateContour>,
+}
+
+impl PlateLayer {
+    /// Returns an iterator over the contours of the object at index `object`.
+    pub fn object_contours(&self, object: usize) -> impl Iterator<Item = &PlateContour> + '_ {
+        self.contours.iter().filter(move |c| c.object == object)
+    }
+}
+
+/// Several meshes sliced together, see `slice_plate`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plate {
+    /// The layers of the plate from bottom to top.
+    pub layers: Vec<PlateLayer>,
+    /// The pairs of objects whose bounding boxes overlap, lowest index first. Such objects are
+    /// likely placed on top of each other and would collide while printing.
+    pub collisions: Vec<(usize, usize)>,
+    /// The settings of each object, in the order of the meshes, for the stages after slicing.
+    pub configs: Vec<SliceConfig>,
+    /// The objects whose `first_layer_height` differs from the first object's. The layers are shared,
+    /// so these objects were sliced with the first layer of the first object instead of their own.
+    pub mismatched_layers: Vec<usize>,
+}
+
+impl Plate {
+    /// Generates the sparse infill of an object on a layer, with the density of its own settings.
+    ///
+    /// # Remarks
+    ///
+    /// - The area of the object is inset by a nozzle width, leaving room for a wall, and filled with
+    ///   `infill::grid` lines spaced for the `infill_density` of the object, as in `Slicer::slice`.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The index of the layer in `Plate::layers`.
+    /// * `object` - The index of the object.
+    /// * `nozzle_width` - The width of an extruded line.
+    ///
+    /// # Returns
+    ///
+    /// The infill lines, empty if the layer or the object does not exist or the density is not
+    /// positive.
+    pub fn object_infill(&self, layer: usize, object: usize, nozzle_width: f32) -> Vec<[Vec2; 2]> {
+        let (Some(layer), Some(config)) = (self.layers.get(layer), self.configs.get(object)) else {
+            return Vec::new();
+        };
+        if config.infill_density <= 0.0 {
+            return Vec::new();
+        }
+        let loops: Vec<Vec<Vec2>> = layer
+            .object_contours(object)
+            .map(|contour| {
+                contour.points[..contour.points.len() - 1]
+                    .iter()
+                    .map(|&p| to_planar(p))
+                    .collect()
+            })
+            .collect();
+        let inside = offset_region(&clip::union(&loops, &[]), nozzle_width);
+        // A grid prints two lines per spacing, one along each axis.
+        infill::grid(&inside, 2.0 * nozzle_width / config.infill_density)
+    }
+}
+
+/// Slices several meshes printed at once into shared horizontal layers.
+///
+/// # Remarks
+///
+/// - The layers are placed as by `slice_mesh`, starting from the **lowest** point of all the meshes,
+///   so every object is cut by the same planes and a layer prints all of them at once.
+/// - The segments of each mesh are assembled into closed loops on their own, so contours of
+///   different objects are never joined even when they touch. Open chains are dropped.
+/// - Overlapping bounding boxes are **not** an error, they are reported in `Plate::collisions` so
+///   the caller can warn about the placement.
+/// - Each mesh is validated and cut with its own `SliceConfig`, kept in `Plate::configs` for the
+///   later stages. The layers being shared, they all follow `layer_height` and the first layer of
+///   the **first** object. Objects asking for another first layer are reported in
+///   `Plate::mismatched_layers`, so the caller can warn about it too.
+///
+/// # Arguments
+///
+/// * `objects` - The meshes of the plate, placed where they are printed, with their settings.
+/// * `layer_height` - The thickness of each layer.
+///
+/// # Returns
+///
+/// The layers of the plate, or a `SlicerError` if a mesh or the layer height is invalid, see
+/// `validate_mesh`. An empty plate has no layers.
+pub fn slice_plate(
+    objects: &[(Mesh, SliceConfig)],
+    layer_height: f32,
+) -> Result<Plate, SlicerError> {
+    for (mesh, config) in objects {
+        validate_mesh(mesh, config)?;
+        if let Some(first_layer_height) = config.first_layer_height {
+            validate_parameter("first_layer_height", first_layer_height)?;
+        }
+    }
+    validate_parameter("layer_height", layer_height)?;
+
+    let bounds: Vec<(Vec3, Vec3)> = objects
+        .iter()
+        .filter_map(|(mesh, _)| mesh.bounding_box())
+        .collect();
+    let Some(bottom) = bounds.iter().map(|(min, _)| min.y).reduce(f32::min) else {
+        return Ok(Plate::default());
+    };
+    let top = bounds.iter().map(|(_, max)| max.y).fold(bottom, f32::max);
+
+    let collisions = (0..bounds.len())
+        .flat_map(|a| (a + 1..bounds.len()).map(move |b| (a, b)))
+        .filter(|&(a, b)| {
+            let (min_a, max_a) = bounds[a];
+            let (min_b, max_b) = bounds[b];
+            min_a.cmplt(max_b).all() && min_b.cmplt(max_a).all()
+        })
+        .collect();
+
+    let configs: Vec<SliceConfig> = objects.iter().map(|(_, config)| config.clone()).collect();
+    let mismatched_layers = (1..configs.len())
+        .filter(|&object| configs[object].first_layer_height != configs[0].first_layer_height)
+        .collect();
+
+    let prepared: Vec<PreparedMesh> = objects
+        .iter()
+        .map(|(mesh, _)| PreparedMesh::new(mesh))
+        .collect();
+    let layers = (0..)
+        .map(|i| layer_plane(bottom, i, layer_height, &configs[0]))
+        .take_while(|&(height, _)| height <= top)
+        .map(|(height, thickness)| {
+            let contours = prepared
+                .iter()
+                .zip(&configs)
+                .enumerate()
+                .flat_map(|(object, (mesh, config))| {
+                    let layer = mesh.slice_at(height, thickness, config);
+                    build_contours(&layer.segments, EPSILON, EPSILON)
+                        .loops()
+                        .map(|points| PlateContour {
+                            object,
+                            points: points.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            PlateLayer {
+                height,
+                thickness,
+                contours,
+            }
+        })
+        .collect();
+
+    Ok(Plate {
+        layers,
+        collisions,
+        configs,
+        mismatched_layers,
+    })
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `slice_plate` function on two separated cubes of different heights.
+    #[test]
+    fn test_slice_plate_two_cubes() {
+        let meshes = [
+            crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE),
+            crate::fixtures::box_mesh(Vec3::new(3.0, 0.0, 0.0), Vec3::new(4.0, 0.5, 1.0)),
+        ]
+        .map(|mesh| (mesh, SliceConfig::default()));
+        let plate = slice_plate(&meshes, 0.25).unwrap();
+        assert!(plate.collisions.is_empty());
+        assert_eq!(plate.layers.len(), 4);
+
+        for (i, layer) in plate.layers.iter().enumerate() {
+            assert_eq!(layer.object_contours(0).count(), 1);
+            // The second cube is only half as tall.
+            let second: Vec<_> = layer.object_contours(1).collect();
+            assert_eq!(second.len(), if i < 2 { 1 } else { 0 });
+            for contour in second {
+                assert!(contour.points.iter().all(|p| p.x >= 3.0));
+            }
+        }
+
+        let overlapping = [
+            crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE),
+            crate::fixtures::box_mesh(Vec3::new(5.0, 0.0, 0.0), Vec3::new(6.0, 1.0, 1.0)),
+            crate::fixtures::box_mesh(Vec3::splat(0.5), Vec3::splat(1.5)),
+        ]
+        .map(|mesh| (mesh, SliceConfig::default()));
+        assert_eq!(
+            slice_plate(&overlapping, 0.25).unwrap().collisions,
+            vec![(0, 2)]
+        );
+        assert_eq!(slice_plate(&[], 0.25).unwrap(), Plate::default());
+    }
+
+    /// Test for the `slice_plate` function keeping the settings of each object, here their infill.
+    #[test]
+    fn test_slice_plate_per_object_config() {
+        let sparse = SliceConfig {
+            infill_density: 0.1,
+            ..SliceConfig::default()
+        };
+        let dense = SliceConfig {
+            infill_density: 0.4,
+            first_layer_height: Some(0.3),
+            ..SliceConfig::default()
+        };
+        let objects = [
+            (
+                crate::fixtures::box_mesh(Vec3::ZERO, Vec3::splat(10.0)),
+                sparse.clone(),
+            ),
+            (
+                crate::fixtures::box_mesh(Vec3::new(20.0, 0.0, 0.0), Vec3::new(30.0, 10.0, 10.0)),
+                dense.clone(),
+            ),
+        ];
+        let plate = slice_plate(&objects, 0.2).unwrap();
+        assert_eq!(plate.configs, vec![sparse, dense]);
+        // The layers follow the first object, the second one asked for a thicker first layer.
+        assert_eq!(plate.mismatched_layers, vec![1]);
+        assert_eq!(plate.layers[0].thickness, 0.2);
+
+        let first = plate.object_infill(10, 0, 0.4);
+        let second = plate.object_infill(10, 1, 0.4);
+        assert!(!first.is_empty());
+        assert!(first.iter().flatten().all(|p| p.x < 10.0));
+        assert!(second.iter().flatten().all(|p| p.x > 20.0));
+        // Four times the density, four times the lines on the same area.
+        assert!(second.len() >= 3 * first.len());
+        assert!(plate.object_infill(10, 2, 0.4).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use glam::f32::Vec2;
+
+use crate::clip;
+use crate::slicer::Layer;
+
+/// The areas of a layer split by the kind of infill they need.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegionFlags {
+    /// The areas that need solid infill, outer contours counter-clockwise and holes clockwise.
+    pub solid: Vec<Vec<Vec2>>,
+    /// The areas that can be filled sparsely, outer contours counter-clockwise and holes clockwise.
+    pub sparse: Vec<Vec<Vec2>>,
+}
+
+/// Splits the area of every layer into the regions needing solid infill and those filled sparsely.
+///
+/// # Remarks
+///
+/// - The whole area of the first `bottom_count` and the last `top_count` layers is **solid**.
+/// - On the other layers, the parts not covered by **both** the layer below and the layer above are
+///   solid: they are either overhangs resting on nothing or ceilings exposed to the air.
+/// - The area of a layer is made of its closed contours in the (x, z) plane, open polylines are ignored.
+///
+/// # Arguments
+///
+/// * `layers` - The sliced layers, from the bottom to the top.
+/// * `top_count` - The number of solid layers at the top of the print.
+/// * `bottom_count` - The number of solid layers at the bottom of the print.
+///
+/// # Returns
+///
+/// The regions of every layer, in the same order as `layers`.
+pub fn classify_regions(
+    layers: &[Layer],
+    top_count: usize,
+    bottom_count: usize,
+) -> Vec<RegionFlags> {
+    let areas: Vec<Vec<Vec<Vec2>>> = layers.iter().map(Layer::regions).collect();
+
+    (0..areas.len())
+        .map(|i| {
+            if i < bottom_count || i + top_count >= areas.len() {
+                return RegionFlags {
+                    solid: areas[i].clone(),
+                    sparse: Vec::new(),
+                };
+            }
+
+            let covered = clip::intersection(&areas[i - 1], &areas[i + 1]);
+            RegionFlags {
+                solid: clip::difference(&areas[i], &covered),
+                sparse: clip::intersection(&areas[i], &covered),
+            }
+        })
+        .collect()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::mesh::Mesh;
+    #[allow(unused_imports)]
+    use glam::f32::Vec3;
+
+    /// Computes the total signed area of a set of contours.
+    #[allow(dead_code)]
+    fn area(contours: &[Vec<Vec2>]) -> f32 {
+        contours
+            .iter()
+            .map(|c| {
+                (0..c.len())
+                    .map(|i| c[i].perp_dot(c[(i + 1) % c.len()]))
+                    .sum::<f32>()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// Test for the `classify_regions` function on a two step pyramid.
+    #[test]
+    fn test_classify_regions_stepped_pyramid() {
+        // A 4x4 base one unit tall, with a centered 2x2 step on top of it.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(4.0, 1.0, 4.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(3.0, 2.0, 3.0),
+        ));
+        let layers = crate::slicer::slice_mesh(&Mesh::from_triangles(&triangles), 0.25).unwrap();
+        assert_eq!(layers.len(), 8);
+
+        let regions = classify_regions(&layers, 1, 1);
+        assert_eq!(regions.len(), 8);
+
+        // The bottom and top layers are entirely solid.
+        assert!((area(&regions[0].solid) - 16.0).abs() < 1e-3);
+        assert!(regions[0].sparse.is_empty());
+        assert!((area(&regions[7].solid) - 4.0).abs() < 1e-3);
+
+        // Inside the base, everything is covered.
+        assert!(regions[1].solid.is_empty());
+        assert!((area(&regions[1].sparse) - 16.0).abs() < 1e-3);
+
+        // The last layer of the base has the exposed ring of the step face solid.
+        assert!((area(&regions[3].solid) - 12.0).abs() < 1e-3);
+        assert!((area(&regions[3].sparse) - 4.0).abs() < 1e-3);
+        assert_eq!(regions[3].solid.len(), 2);
+
+        // The first layer of the step rests entirely on the base.
+        assert!(regions[4].solid.is_empty());
+    }
+}
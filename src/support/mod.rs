@@ -0,0 +1,259 @@
+use glam::f32::{Vec2, Vec3};
+
+use crate::clip;
+use crate::geometry::to_planar;
+use crate::mesh::Mesh;
+use crate::slicer::{Layer, EPSILON};
+
+/// Finds the downward facing triangles of a mesh that are too flat to be printed without support.
+///
+/// # Remarks
+///
+/// - The normal of a triangle is computed from its **winding**, counter-clockwise vertices facing
+///   the viewer, and a triangle faces downwards when its normal has a negative Y component.
+/// - The overhang angle is measured from the **vertical**: a vertical wall overhangs by 0 degrees and
+///   a flat ceiling by 90 degrees. Triangles overhanging by more than `max_angle_deg` are returned.
+/// - Triangles lying on the **build plate**, at the lowest height of the mesh, rest on the bed and
+///   are never returned.
+/// - Degenerate triangles have no normal and are ignored.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh to inspect.
+/// * `max_angle_deg` - The steepest overhang the printer can bridge, in degrees from the vertical.
+///
+/// # Returns
+///
+/// The triangles needing support, in the order of the faces of the mesh.
+pub fn detect_overhangs(mesh: &Mesh, max_angle_deg: f32) -> Vec<[Vec3; 3]> {
+    let Some((min, _)) = mesh.bounding_box() else {
+        return Vec::new();
+    };
+    // A unit normal making an angle `a` with the vertical overhangs by `90 - a` degrees, i.e. its
+    // Y component is `-sin(overhang)` when it faces down.
+    let threshold = max_angle_deg.to_radians().sin();
+
+    mesh.iter_triangles()
+        .zip(mesh.face_normals())
+        .filter(|(_, normal)| -normal.y > threshold)
+        .map(|(t, _)| t)
+        .filter(|t| t.iter().any(|v| v.y - min.y > EPSILON))
+        .collect()
+}
+
+/// Computes, for every layer, the area where support must be printed under the overhanging faces.
+///
+/// # Remarks
+///
+/// - Each overhanging face casts its **shadow**, its projection on the (x, z) plane, on every layer
+///   below its lowest point.
+/// - The shadows are carried down from the top layer to the bottom one, and the region of the model
+///   is **subtracted** on each layer. Support thus never grows inside the part, and a column stops
+///   where it lands on the model instead of going through it.
+///
+/// # Arguments
+///
+/// * `layers` - The sliced layers, from the bottom to the top.
+/// * `overhang_faces` - The faces needing support, see `detect_overhangs`.
+///
+/// # Returns
+///
+/// The support region of every layer, in the same order as `layers`, outer contours
+/// counter-clockwise and holes clockwise.
+pub fn generate_support_areas(
+    layers: &[Layer],
+    overhang_faces: &[[Vec3; 3]],
+) -> Vec<Vec<Vec<Vec2>>> {
+    let mut shadows: Vec<(f32, Vec<Vec2>)> = overhang_faces
+        .iter()
+        .filter_map(|face| {
+            let lowest = face.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+            let mut shadow: Vec<Vec2> = face.iter().map(|&v| to_planar(v)).collect();
+            let area = (shadow[1] - shadow[0]).perp_dot(shadow[2] - shadow[0]);
+            if area.abs() <= EPSILON {
+                // Seen from above the face is a line, it has nothing to hold up.
+                return None;
+            }
+            if area < 0.0 {
+                shadow.reverse();
+            }
+            Some((lowest, shadow))
+        })
+        .collect();
+    // Sorted from the highest face down, so the faces above each layer form a prefix.
+    shadows.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut areas = vec![Vec::new(); layers.len()];
+    let mut carried: Vec<Vec<Vec2>> = Vec::new();
+    let mut next = 0;
+    for (i, layer) in layers.iter().enumerate().rev() {
+        let top = layer.height + layer.thickness / 2.0;
+        let start = next;
+        while next < shadows.len() && shadows[next].0 >= top - EPSILON {
+            next += 1;
+        }
+        if next > start {
+            let cast: Vec<Vec<Vec2>> = shadows[start..next]
+                .iter()
+                .map(|(_, shadow)| shadow.clone())
+                .collect();
+            carried = clip::union(&carried, &cast);
+        }
+
+        carried = clip::difference(&carried, &layer.regions());
+        areas[i] = carried.clone();
+    }
+
+    areas
+}
+
+/// Splits off the interface layers of the support, printed densely so it peels off cleanly.
+///
+/// # Remarks
+///
+/// - The parts of a support area with the model above them within the next `interface_count` layers
+///   form the **top** interface, right under the overhang it holds up.
+/// - The parts with the model below them within the previous `interface_count` layers, or lying on
+///   the bed in the first `interface_count` layers, form the **bottom** interface.
+/// - The interface is removed from `support_areas`, which keeps the body of the support, to be
+///   filled sparsely.
+///
+/// # Arguments
+///
+/// * `support_areas` - The support region of every layer, see `generate_support_areas`.
+/// * `model_layers` - The sliced layers of the model, in the same order as `support_areas`.
+/// * `interface_count` - The number of interface layers at the top and at the bottom of the support.
+///
+/// # Returns
+///
+/// The interface region of every layer, in the same order as `model_layers`, outer contours
+/// counter-clockwise and holes clockwise.
+pub fn add_interface_layers(
+    support_areas: &mut [Vec<Vec<Vec2>>],
+    model_layers: &[Layer],
+    interface_count: usize,
+) -> Vec<Vec<Vec<Vec2>>> {
+    let regions: Vec<Vec<Vec<Vec2>>> = model_layers.iter().map(Layer::regions).collect();
+
+    support_areas
+        .iter_mut()
+        .enumerate()
+        .map(|(i, area)| {
+            if i < interface_count {
+                return std::mem::take(area);
+            }
+            let above = (i + 1..=i + interface_count).filter_map(|j| regions.get(j));
+            let below = (i.saturating_sub(interface_count)..i).map(|j| &regions[j]);
+            let model = above
+                .chain(below)
+                .fold(Vec::new(), |model, region| clip::union(&model, region));
+
+            let interface = clip::intersection(area, &model);
+            *area = clip::difference(area, &model);
+            interface
+        })
+        .collect()
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `detect_overhangs` function on a ledge sticking out of a block.
+    #[test]
+    fn test_detect_overhangs_ledge() {
+        // A 4x2x2 block with a 2mm deep ledge on its right side, between y = 1 and y = 2.
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(4.0, 2.0, 2.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(4.0, 1.0, 0.0),
+            Vec3::new(6.0, 2.0, 2.0),
+        ));
+        let mesh = Mesh::from_triangles(&triangles);
+
+        let overhangs = detect_overhangs(&mesh, 45.0);
+        assert_eq!(overhangs.len(), 2);
+        for triangle in &overhangs {
+            assert!(triangle.iter().all(|v| v.y == 1.0 && v.x >= 4.0));
+        }
+
+        // Nothing overhangs on a plain block resting on the bed.
+        let block = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        assert!(detect_overhangs(&block, 45.0).is_empty());
+    }
+
+    /// Test for the `detect_overhangs` function comparing a slope to the threshold angle.
+    #[test]
+    fn test_detect_overhangs_threshold() {
+        // A downward facing triangle overhanging by 60 degrees, raised above the bed.
+        let slope = 60.0_f32.to_radians();
+        let run = Vec3::new(slope.sin(), -slope.cos(), 0.0);
+        let triangle = [Vec3::Y, Vec3::Y + run, Vec3::Y + Vec3::Z];
+        let anchor = [Vec3::ZERO, Vec3::X * 0.1, Vec3::Z * 0.1];
+        let mesh = Mesh::from_triangles(&[triangle, anchor]);
+
+        assert_eq!(detect_overhangs(&mesh, 45.0).len(), 1);
+        assert!(detect_overhangs(&mesh, 70.0).is_empty());
+    }
+
+    /// Test for the `generate_support_areas` function under a ledge and along a vertical wall.
+    #[test]
+    fn test_generate_support_areas_ledge() {
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(4.0, 2.0, 2.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(4.0, 1.0, 0.0),
+            Vec3::new(6.0, 2.0, 2.0),
+        ));
+        let mesh = Mesh::from_triangles(&triangles);
+        let layers = crate::slicer::slice_mesh(&mesh, 0.25).unwrap();
+        assert_eq!(layers.len(), 8);
+
+        let areas = generate_support_areas(&layers, &detect_overhangs(&mesh, 45.0));
+        assert_eq!(areas.len(), 8);
+        for (layer, area) in layers.iter().zip(&areas) {
+            if layer.height < 1.0 {
+                // A single 2x2 column under the ledge, right of the block's wall.
+                assert_eq!(area.len(), 1);
+                assert!(area[0].iter().all(|p| p.x >= 4.0 - 1e-4));
+                let shoelace: f32 = (0..area[0].len())
+                    .map(|i| area[0][i].perp_dot(area[0][(i + 1) % area[0].len()]))
+                    .sum();
+                assert!((shoelace / 2.0 - 4.0).abs() < 1e-3);
+            } else {
+                assert!(area.is_empty());
+            }
+        }
+
+        // A plain block has vertical walls only and needs no support.
+        let block = crate::fixtures::box_mesh(Vec3::ZERO, Vec3::ONE);
+        let layers = crate::slicer::slice_mesh(&block, 0.25).unwrap();
+        let areas = generate_support_areas(&layers, &detect_overhangs(&block, 45.0));
+        assert!(areas.iter().all(|area| area.is_empty()));
+    }
+
+    /// Test for the `add_interface_layers` function flagging the layers under a ledge and on the bed.
+    #[test]
+    fn test_add_interface_layers_ledge() {
+        let mut triangles = crate::fixtures::box_triangles(Vec3::ZERO, Vec3::new(4.0, 2.0, 2.0));
+        triangles.extend(crate::fixtures::box_triangles(
+            Vec3::new(4.0, 1.0, 0.0),
+            Vec3::new(6.0, 2.0, 2.0),
+        ));
+        let mesh = Mesh::from_triangles(&triangles);
+        let layers = crate::slicer::slice_mesh(&mesh, 0.25).unwrap();
+        let mut areas = generate_support_areas(&layers, &detect_overhangs(&mesh, 45.0));
+        let full = areas.clone();
+
+        let interface = add_interface_layers(&mut areas, &layers, 1);
+        assert_eq!(interface.len(), 8);
+        // The layer directly under the ledge and the one on the bed are interface only.
+        for i in [0, 3] {
+            assert_eq!(interface[i], full[i]);
+            assert!(areas[i].is_empty());
+        }
+        for i in [1, 2] {
+            assert!(interface[i].is_empty());
+            assert_eq!(areas[i], full[i]);
+        }
+        assert!(interface[4..].iter().all(|area| area.is_empty()));
+    }
+}
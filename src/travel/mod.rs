@@ -0,0 +1,169 @@
+use glam::f32::Vec2;
+
+use crate::geometry::point_in_polygon;
+use crate::slicer::EPSILON;
+
+/// Routes a travel move so it stays inside the printed region, instead of crossing its walls.
+///
+/// # Remarks
+///
+/// - A straight line is used whenever it stays inside the region. Otherwise the shortest path
+///   through a **visibility graph** of the vertices of the boundary is taken, so the travel hugs the
+///   corners of the walls and holes it goes around.
+/// - The region is defined by the **even-odd rule**, so the winding of the loops does not matter.
+///   Points on the boundary are inside, so a path may run along a wall. To keep some distance from
+///   the walls, pass a boundary inset with `offset_region`.
+/// - When either end lies outside the region, or no path exists, the straight line is returned.
+///
+/// # Arguments
+///
+/// * `start` - The position of the nozzle.
+/// * `end` - The destination of the travel.
+/// * `boundary` - The loops of the region, without repeating their first points.
+///
+/// # Returns
+///
+/// The points of the travel path, from `start` to `end` included.
+pub fn comb(start: Vec2, end: Vec2, boundary: &[Vec<Vec2>]) -> Vec<Vec2> {
+    if visible(start, end, boundary) || !inside(start, boundary) || !inside(end, boundary) {
+        return vec![start, end];
+    }
+
+    let mut nodes = vec![start, end];
+    nodes.extend(boundary.iter().flatten().copied());
+    let n = nodes.len();
+
+    // Dijkstra from the start, testing the visibility of each edge when it is first relaxed.
+    let mut distances = vec![f32::INFINITY; n];
+    let mut previous = vec![usize::MAX; n];
+    let mut done = vec![false; n];
+    distances[0] = 0.0;
+    while let Some(current) = (0..n)
+        .filter(|&i| !done[i] && distances[i].is_finite())
+        .min_by(|&a, &b| distances[a].total_cmp(&distances[b]))
+    {
+        if current == 1 {
+            break;
+        }
+        done[current] = true;
+        for next in 0..n {
+            if done[next] {
+                continue;
+            }
+            let distance = distances[current] + nodes[current].distance(nodes[next]);
+            if distance < distances[next] && visible(nodes[current], nodes[next], boundary) {
+                distances[next] = distance;
+                previous[next] = current;
+            }
+        }
+    }
+    if previous[1] == usize::MAX {
+        return vec![start, end];
+    }
+
+    let mut path = vec![end];
+    let mut current = 1;
+    while current != 0 {
+        current = previous[current];
+        path.push(nodes[current]);
+    }
+    path.reverse();
+    path
+}
+
+/// Returns whether a point lies inside the region, by the even-odd rule, or on its boundary.
+fn inside(p: Vec2, boundary: &[Vec<Vec2>]) -> bool {
+    let on_boundary = boundary.iter().any(|polygon| {
+        (0..polygon.len()).any(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+            p.distance(a + (b - a) * t) <= EPSILON
+        })
+    });
+    on_boundary
+        || boundary
+            .iter()
+            .filter(|polygon| point_in_polygon(p, polygon))
+            .count()
+            % 2
+            == 1
+}
+
+/// Returns whether the segment from `a` to `b` stays inside the region, touching its boundary at
+/// most.
+fn visible(a: Vec2, b: Vec2, boundary: &[Vec<Vec2>]) -> bool {
+    let direction = b - a;
+    let crosses = boundary.iter().any(|polygon| {
+        (0..polygon.len()).any(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            let side = q - p;
+            let denominator = direction.perp_dot(side);
+            if denominator.abs() <= EPSILON {
+                return false;
+            }
+            let t = (p - a).perp_dot(side) / denominator;
+            let u = (p - a).perp_dot(direction) / denominator;
+            t > EPSILON && t < 1.0 - EPSILON && u > EPSILON && u < 1.0 - EPSILON
+        })
+    });
+    // Passing exactly through vertices is not a crossing, sampling the segment catches shortcuts
+    // through the outside between two of them.
+    !crosses && (1..4).all(|k| inside(a + direction * (k as f32 / 4.0), boundary))
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    /// Test for the `comb` function going around a hole instead of crossing it.
+    #[test]
+    fn test_comb_around_hole() {
+        let boundary = vec![
+            vec![
+                Vec2::ZERO,
+                Vec2::new(10.0, 0.0),
+                Vec2::splat(10.0),
+                Vec2::new(0.0, 10.0),
+            ],
+            vec![
+                Vec2::splat(4.0),
+                Vec2::new(4.0, 6.0),
+                Vec2::splat(6.0),
+                Vec2::new(6.0, 4.0),
+            ],
+        ];
+
+        let path = comb(Vec2::new(2.0, 5.0), Vec2::new(8.0, 5.0), &boundary);
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0], Vec2::new(2.0, 5.0));
+        assert_eq!(path[3], Vec2::new(8.0, 5.0));
+        // The path goes around the hole, possibly along its sides but never through it.
+        for pair in path.windows(2) {
+            assert!(visible(pair[0], pair[1], &boundary));
+        }
+        let length: f32 = path.windows(2).map(|p| p[0].distance(p[1])).sum();
+        assert!((length - (2.0 * 5.0_f32.sqrt() + 2.0)).abs() < 1e-4);
+
+        // An unobstructed travel stays straight.
+        let straight = comb(Vec2::new(1.0, 1.0), Vec2::new(9.0, 2.0), &boundary);
+        assert_eq!(straight, vec![Vec2::new(1.0, 1.0), Vec2::new(9.0, 2.0)]);
+    }
+
+    /// Test for the `comb` function following a concave region around its inner corner.
+    #[test]
+    fn test_comb_l_shape() {
+        let boundary = vec![vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 2.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(2.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ]];
+        let path = comb(Vec2::new(9.0, 1.0), Vec2::new(1.0, 9.0), &boundary);
+        assert_eq!(
+            path,
+            vec![Vec2::new(9.0, 1.0), Vec2::splat(2.0), Vec2::new(1.0, 9.0)]
+        );
+    }
+}